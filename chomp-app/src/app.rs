@@ -1,23 +1,30 @@
-use chomp_services::{Connection, Services};
-use chrono::{Local, Months, NaiveDate};
+use std::time::Duration;
+
+use chomp_services::{Connection, ReportPeriod, ServiceError, Services};
+use chrono::{Local, NaiveDate};
 use iced::{
     keyboard::{self, Modifiers},
-    widget, Element, Subscription, Task,
+    time, widget, Element, Subscription, Task, Theme,
 };
 
 use crate::widget::{
-    CalendarMonth, CreateNutritionTarget, CreateNutritionTargetMessage, CreateProduct,
-    CreateProductMessage, CreateProductPortion, CreateProductPortionMessage, CreateWeight,
-    CreateWeightMessage, Dashboard, DashboardMessage, MealList, MealListMessage,
-    NutritionTargetList, NutritionTargetListMessage, ProductList, ProductListMessage,
-    ProductPortionList, ProductPortionListMessage, Tools, ToolsMessage, UpdateNutritionTarget,
+    CalendarMonth, ChartRange, CreateMeasurement, CreateMeasurementMessage, CreateNutritionTarget,
+    CreateNutritionTargetMessage, CreateProduct, CreateProductMessage, CreateProductPortion,
+    CreateProductPortionMessage, CreateRecipe, CreateRecipeMessage, CreateWeight,
+    CreateWeightMessage, Dashboard, DashboardMessage, MealList, MealListMessage, MeasurementList,
+    MeasurementListMessage, NutritionTargetList, NutritionTargetListMessage, ProductList,
+    ProductListMessage, ProductPortionList, ProductPortionListMessage, RecipeList,
+    RecipeListMessage, ReportRange, Reports, ReportsMessage, Settings, SettingsMessage, Tools,
+    ToolsMessage, UpdateMeasurement, UpdateMeasurementMessage, UpdateNutritionTarget,
     UpdateNutritionTargetMessage, UpdateProduct, UpdateProductMessage, UpdateProductPortion,
-    UpdateProductPortionMessage, UpdateWeight, UpdateWeightMessage, WeightList, WeightListMessage,
-    Widget,
+    UpdateProductPortionMessage, UpdateRecipe, UpdateRecipeMessage, UpdateWeight,
+    UpdateWeightMessage, WeightList, WeightListMessage, Widget, DEFAULT_REPORT_TOLERANCE,
 };
 
 type ProductId = usize;
 type ProductPortionId = usize;
+type RecipeId = usize;
+type MeasurementId = usize;
 
 #[derive(Debug, Clone)]
 pub enum NextWidget {
@@ -28,14 +35,22 @@ pub enum NextWidget {
     ProductPortionList(ProductId),
     CreateProductPortion(ProductId),
     UpdateProductPortion(ProductId, ProductPortionId),
+    RecipeList,
+    CreateRecipe,
+    UpdateRecipe(RecipeId),
     WeightList,
     CreateWeight,
     UpdateWeight(NaiveDate),
+    MeasurementList,
+    CreateMeasurement,
+    UpdateMeasurement(MeasurementId),
     MealList,
     NutritionTargetList,
     CreateNutritionTarget,
     UpdateNutritionTarget(NaiveDate),
+    Reports,
     Tools,
+    Settings,
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +63,9 @@ pub enum Message {
     TabClicked,
     ShiftTabClicked,
     EscapeClicked,
+    Undo,
+    Redo,
+    BackupTick,
     ChangeWidget(NextWidget),
     Dashboard(DashboardMessage),
     ProductList(ProductListMessage),
@@ -56,14 +74,22 @@ pub enum Message {
     ProductPortionList(ProductPortionListMessage),
     CreateProductPortion(CreateProductPortionMessage),
     UpdateProductPortion(UpdateProductPortionMessage),
+    RecipeList(RecipeListMessage),
+    CreateRecipe(CreateRecipeMessage),
+    UpdateRecipe(UpdateRecipeMessage),
     WeightList(WeightListMessage),
     CreateWeight(CreateWeightMessage),
     UpdateWeight(UpdateWeightMessage),
+    MeasurementList(MeasurementListMessage),
+    CreateMeasurement(CreateMeasurementMessage),
+    UpdateMeasurement(UpdateMeasurementMessage),
     MealList(MealListMessage),
     NutritionTargetList(NutritionTargetListMessage),
     CreateNutritionTarget(CreateNutritionTargetMessage),
     UpdateNutritionTarget(UpdateNutritionTargetMessage),
+    Reports(ReportsMessage),
     Tools(ToolsMessage),
+    Settings(SettingsMessage),
 }
 
 pub struct Context {
@@ -74,58 +100,64 @@ pub struct Context {
 pub struct App {
     ctx: Context,
     active_widget: Box<dyn Widget>,
+    current_widget: NextWidget,
+    theme: Theme,
 }
 
 impl App {
     pub fn new(db: Connection) -> Self {
         let services = Services::new(db);
+        create_backup_if_due(&services);
 
-        let weights_end = Local::now().date_naive();
-        let weights_start = Local::now()
-            .checked_sub_months(Months::new(1))
-            .unwrap()
-            .date_naive();
-        let weights = services
-            .weight
-            .list_between(weights_start, weights_end)
-            .unwrap_or_default();
+        let settings = services.settings.read().unwrap_or_default();
+        let dashboard = Dashboard::load(&services, dashboard_range(&settings));
 
         App {
             ctx: Context {
                 services,
                 next_widget: None,
             },
-            active_widget: Box::new(Dashboard::new(weights)),
+            active_widget: Box::new(dashboard),
+            current_widget: NextWidget::Dashboard,
+            theme: theme(&settings),
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
         self.active_widget.view()
     }
 
     pub fn update(&mut self, msg: Message) -> Task<Message> {
+        match msg {
+            Message::Undo => self.undo_redo(self.ctx.services.history.undo(), "undo"),
+            Message::Redo => self.undo_redo(self.ctx.services.history.redo(), "redo"),
+            _ => {}
+        }
+
         let widget_task = self.active_widget.update(&mut self.ctx, msg.clone());
 
+        if let Message::Settings(_) = msg {
+            let settings = self.ctx.services.settings.read().unwrap_or_default();
+            self.theme = theme(&settings);
+        }
+
         if let Message::ChangeWidget(w) = msg.clone() {
             self.ctx.next_widget = Some(w);
         }
 
         if let Some(w) = self.ctx.next_widget.take() {
+            self.current_widget = w.clone();
+            let settings = self.ctx.services.settings.read().unwrap_or_default();
+            let units = settings.units;
             self.active_widget = match w {
-                NextWidget::Dashboard => {
-                    let end = Local::now().date_naive();
-                    let start = Local::now()
-                        .checked_sub_months(Months::new(1))
-                        .unwrap()
-                        .date_naive();
-                    let weights = self
-                        .ctx
-                        .services
-                        .weight
-                        .list_between(start, end)
-                        .unwrap_or_default();
-                    Box::new(Dashboard::new(weights))
-                }
+                NextWidget::Dashboard => Box::new(Dashboard::load(
+                    &self.ctx.services,
+                    dashboard_range(&settings),
+                )),
                 NextWidget::ProductList => {
                     let products = match self.ctx.services.product.list() {
                         Ok(p) => p,
@@ -134,18 +166,45 @@ impl App {
                             std::process::exit(1);
                         }
                     };
-                    Box::new(ProductList::new(products))
+                    Box::new(ProductList::new(products, units))
+                }
+                NextWidget::CreateProduct => {
+                    let nutrients = self.ctx.services.nutrient.list().unwrap_or_default();
+                    Box::new(CreateProduct::new(nutrients, units))
                 }
-                NextWidget::CreateProduct => Box::new(CreateProduct::new()),
                 NextWidget::UpdateProduct(product_id) => {
-                    let product = match self.ctx.services.product.read(product_id) {
-                        Ok(p) => p,
-                        Err(err) => {
-                            tracing::error!("Failed to get product by id: {}", err);
-                            std::process::exit(1);
-                        }
-                    };
-                    Box::new(UpdateProduct::new(product))
+                    if let Ok(recipe) = self.ctx.services.recipe.read_by_product_id(product_id) {
+                        let products = self.ctx.services.product.list().unwrap_or_default();
+                        Box::new(UpdateRecipe::new(recipe, products, units))
+                    } else {
+                        let product = match self.ctx.services.product.read(product_id) {
+                            Ok(p) => p,
+                            Err(err) => {
+                                tracing::error!("Failed to get product by id: {}", err);
+                                std::process::exit(1);
+                            }
+                        };
+                        let nutrients = self.ctx.services.nutrient.list().unwrap_or_default();
+                        let product_nutrients = self
+                            .ctx
+                            .services
+                            .nutrient
+                            .list_for_product(product_id)
+                            .unwrap_or_default();
+                        let aliases = self
+                            .ctx
+                            .services
+                            .product
+                            .list_aliases(product_id)
+                            .unwrap_or_default();
+                        Box::new(UpdateProduct::new(
+                            product,
+                            nutrients,
+                            product_nutrients,
+                            aliases,
+                            units,
+                        ))
+                    }
                 }
                 NextWidget::ProductPortionList(product_id) => {
                     let product = match self.ctx.services.product.read(product_id) {
@@ -162,7 +221,7 @@ impl App {
                             std::process::exit(1);
                         }
                     };
-                    Box::new(ProductPortionList::new(product, portions))
+                    Box::new(ProductPortionList::new(product, portions, units))
                 }
                 NextWidget::CreateProductPortion(product_id) => {
                     let product = match self.ctx.services.product.read(product_id) {
@@ -173,7 +232,7 @@ impl App {
                         }
                     };
 
-                    Box::new(CreateProductPortion::new(&product))
+                    Box::new(CreateProductPortion::new(&product, units))
                 }
                 NextWidget::UpdateProductPortion(product_id, product_portion_id) => {
                     let product = match self.ctx.services.product.read(product_id) {
@@ -190,13 +249,41 @@ impl App {
                             std::process::exit(1);
                         }
                     };
-                    Box::new(UpdateProductPortion::new(&product, &portion))
+                    Box::new(UpdateProductPortion::new(&product, &portion, units))
+                }
+                NextWidget::RecipeList => {
+                    let recipes = match self.ctx.services.recipe.list() {
+                        Ok(r) => r,
+                        Err(err) => {
+                            tracing::error!("Failed to get recipe list: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(RecipeList::new(recipes, units))
+                }
+                NextWidget::CreateRecipe => Box::new(CreateRecipe::new(units)),
+                NextWidget::UpdateRecipe(recipe_id) => {
+                    let recipe = match self.ctx.services.recipe.read(recipe_id) {
+                        Ok(r) => r,
+                        Err(err) => {
+                            tracing::error!("Failed to get recipe by id: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    let products = self.ctx.services.product.list().unwrap_or_default();
+                    Box::new(UpdateRecipe::new(recipe, products, units))
                 }
                 NextWidget::WeightList => {
-                    let weights = self.ctx.services.weight.list().unwrap_or_default();
-                    Box::new(WeightList::new(weights))
+                    let weights = match self.ctx.services.weight.analysis() {
+                        Ok(w) => w,
+                        Err(err) => {
+                            tracing::error!("Failed to analyse weights: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(WeightList::new(weights, units))
                 }
-                NextWidget::CreateWeight => Box::new(CreateWeight::new()),
+                NextWidget::CreateWeight => Box::new(CreateWeight::new(units, settings.week_start)),
                 NextWidget::UpdateWeight(day) => {
                     let weight = match self.ctx.services.weight.read(day) {
                         Ok(w) => w,
@@ -205,7 +292,36 @@ impl App {
                             panic!()
                         }
                     };
-                    Box::new(UpdateWeight::new(weight))
+                    Box::new(UpdateWeight::new(weight, units))
+                }
+                NextWidget::MeasurementList => {
+                    let measurements = self.ctx.services.measurement.list().unwrap_or_default();
+                    let types = self
+                        .ctx
+                        .services
+                        .measurement
+                        .list_types()
+                        .unwrap_or_default();
+                    Box::new(MeasurementList::new(measurements, types))
+                }
+                NextWidget::CreateMeasurement => {
+                    let types = self
+                        .ctx
+                        .services
+                        .measurement
+                        .list_types()
+                        .unwrap_or_default();
+                    Box::new(CreateMeasurement::new(types, settings.week_start))
+                }
+                NextWidget::UpdateMeasurement(measurement_id) => {
+                    let measurement = match self.ctx.services.measurement.read(measurement_id) {
+                        Ok(m) => m,
+                        Err(err) => {
+                            tracing::error!("Failed to read measurement: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(UpdateMeasurement::new(measurement))
                 }
                 NextWidget::MealList => {
                     let day = Local::now().date_naive();
@@ -235,6 +351,25 @@ impl App {
                             panic!()
                         }
                     };
+                    let target = match self.ctx.services.nutrition_target.read_in_effect(day) {
+                        Ok(t) => t.unwrap_or(target),
+                        Err(err) => {
+                            tracing::error!("Failed to get nutrition target: {}", err);
+                            panic!()
+                        }
+                    };
+                    let budgets = match self
+                        .ctx
+                        .services
+                        .nutrition_target
+                        .list_meal_budgets(target.day)
+                    {
+                        Ok(b) => b,
+                        Err(err) => {
+                            tracing::error!("Failed to get meal budgets: {}", err);
+                            panic!()
+                        }
+                    };
                     let portions = match self.ctx.services.product_portion.list_all() {
                         Ok(m) => m,
                         Err(err) => {
@@ -243,7 +378,18 @@ impl App {
                         }
                     };
 
-                    Box::new(MealList::new(day, meals, stats, target, portions))
+                    Box::new(
+                        MealList::new(
+                            day,
+                            meals,
+                            stats,
+                            target,
+                            portions,
+                            units,
+                            settings.week_start,
+                        )
+                        .with_budgets(budgets),
+                    )
                 }
                 NextWidget::NutritionTargetList => {
                     let targets = self
@@ -252,9 +398,11 @@ impl App {
                         .nutrition_target
                         .list()
                         .unwrap_or_default();
-                    Box::new(NutritionTargetList::new(targets))
+                    Box::new(NutritionTargetList::new(targets, units))
+                }
+                NextWidget::CreateNutritionTarget => {
+                    Box::new(CreateNutritionTarget::new(units, settings.week_start))
                 }
-                NextWidget::CreateNutritionTarget => Box::new(CreateNutritionTarget::new()),
                 NextWidget::UpdateNutritionTarget(day) => {
                     let target = match self.ctx.services.nutrition_target.read(day) {
                         Ok(t) => t,
@@ -263,21 +411,129 @@ impl App {
                             panic!()
                         }
                     };
-                    Box::new(UpdateNutritionTarget::new(target))
+                    let weekdays = match self.ctx.services.nutrition_target.list_weekdays(day) {
+                        Ok(w) => w,
+                        Err(err) => {
+                            tracing::error!("Failed to read weekday targets: {}", err);
+                            panic!()
+                        }
+                    };
+                    let budgets = match self.ctx.services.nutrition_target.list_meal_budgets(day) {
+                        Ok(b) => b,
+                        Err(err) => {
+                            tracing::error!("Failed to read meal budgets: {}", err);
+                            panic!()
+                        }
+                    };
+                    Box::new(UpdateNutritionTarget::new(target, weekdays, budgets, units))
+                }
+                NextWidget::Reports => {
+                    let end = Local::now().date_naive();
+                    let range = ReportRange::FourWeeks;
+                    let period = ReportPeriod::Week;
+                    let report = match self.ctx.services.report.report(
+                        range.start(end),
+                        end,
+                        period,
+                        DEFAULT_REPORT_TOLERANCE / 100.0,
+                    ) {
+                        Ok(r) => r,
+                        Err(err) => {
+                            tracing::error!("Failed to create report: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(Reports::new(
+                        end,
+                        range,
+                        period,
+                        report,
+                        settings.week_start,
+                    ))
+                }
+                NextWidget::Tools => {
+                    let backups = match self.ctx.services.backup.list() {
+                        Ok(b) => b,
+                        Err(err) => {
+                            tracing::error!("Failed to list backups: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(Tools::new(backups))
+                }
+                NextWidget::Settings => {
+                    let meals = match self.ctx.services.meal_template.list(settings.week_start) {
+                        Ok(m) => m,
+                        Err(err) => {
+                            tracing::error!("Failed to list meal templates: {}", err);
+                            std::process::exit(1);
+                        }
+                    };
+                    Box::new(Settings::new(
+                        settings.clone(),
+                        theme(&settings),
+                        dashboard_range(&settings),
+                        meals,
+                    ))
                 }
-                NextWidget::Tools => Box::new(Tools::new()),
             };
         }
 
         match msg {
+            Message::BackupTick => {
+                create_backup_if_due(&self.ctx.services);
+                widget_task
+            }
             Message::TabClicked => widget::focus_next(),
             Message::ShiftTabClicked => widget::focus_previous(),
             _ => widget_task,
         }
     }
 
+    /// Reloads the current view after undo or redo changed the data it shows.
+    fn undo_redo(&mut self, result: Result<Option<String>, ServiceError>, action: &str) {
+        match result {
+            Ok(Some(label)) => tracing::info!("Applied {} of: {}", action, label),
+            Ok(None) => return,
+            Err(err) => {
+                tracing::error!("Failed to {}: {}", action, err);
+                return;
+            }
+        }
+
+        // Meal list refreshes itself to keep the selected day, forms may edit rows that no
+        // longer exist, so their list is opened instead.
+        self.ctx.next_widget = match &self.current_widget {
+            NextWidget::MealList => None,
+            NextWidget::CreateProduct
+            | NextWidget::UpdateProduct(_)
+            | NextWidget::ProductPortionList(_)
+            | NextWidget::CreateProductPortion(_)
+            | NextWidget::UpdateProductPortion(_, _) => Some(NextWidget::ProductList),
+            NextWidget::CreateRecipe | NextWidget::UpdateRecipe(_) => Some(NextWidget::RecipeList),
+            NextWidget::CreateWeight | NextWidget::UpdateWeight(_) => Some(NextWidget::WeightList),
+            NextWidget::CreateMeasurement | NextWidget::UpdateMeasurement(_) => {
+                Some(NextWidget::MeasurementList)
+            }
+            NextWidget::CreateNutritionTarget | NextWidget::UpdateNutritionTarget(_) => {
+                Some(NextWidget::NutritionTargetList)
+            }
+            w => Some(w.clone()),
+        };
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        keyboard::on_key_press(|key, modifiers| {
+        let keys = keyboard::on_key_press(|key, modifiers| {
+            if let keyboard::Key::Character(c) = key.as_ref() {
+                if modifiers.command() && c.eq_ignore_ascii_case("z") {
+                    return Some(if modifiers.shift() {
+                        Message::Redo
+                    } else {
+                        Message::Undo
+                    });
+                }
+            }
+
             let keyboard::Key::Named(key) = key else {
                 return None;
             };
@@ -288,6 +544,37 @@ impl App {
                 (keyboard::key::Named::Escape, _) => Some(Message::EscapeClicked),
                 _ => None,
             }
-        })
+        });
+
+        // App can stay open for days, daily backup is checked periodically and not only on start.
+        let backups = time::every(Duration::from_secs(60 * 60)).map(|_| Message::BackupTick);
+
+        Subscription::batch([keys, backups])
+    }
+}
+
+/// Theme saved by name, falls back to the default when the name is unknown.
+fn theme(settings: &chomp_services::Settings) -> Theme {
+    settings
+        .theme
+        .as_deref()
+        .and_then(|name| Theme::ALL.iter().find(|t| t.to_string() == name))
+        .cloned()
+        .unwrap_or(Theme::CatppuccinFrappe)
+}
+
+fn dashboard_range(settings: &chomp_services::Settings) -> ChartRange {
+    settings
+        .dashboard_range
+        .as_deref()
+        .and_then(|range| range.parse().ok())
+        .unwrap_or(ChartRange::Month)
+}
+
+fn create_backup_if_due(services: &Services) {
+    match services.backup.create_if_due() {
+        Ok(Some(path)) => tracing::info!("Created database backup {}", path.display()),
+        Ok(None) => {}
+        Err(err) => tracing::error!("Failed to create database backup: {}", err),
     }
 }
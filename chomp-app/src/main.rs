@@ -1,9 +1,10 @@
 use app::App;
-use chomp_db::prepare_conn;
-use iced::{Task, Theme};
+use chomp_db::{prepare_conn, DbLocation};
+use iced::Task;
 use tracing_subscriber::EnvFilter;
 
 mod app;
+mod open_food_facts;
 mod widget;
 
 fn main() -> iced::Result {
@@ -17,16 +18,34 @@ fn main() -> iced::Result {
     });
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
-    let db = match prepare_conn() {
+    let mut args = pico_args::Arguments::from_env();
+    let location = match parse_location(&mut args) {
+        Ok(l) => l,
+        Err(err) => {
+            tracing::error!("Invalid arguments: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    let db = match prepare_conn(&location) {
         Ok(db) => db,
         Err(err) => {
-            tracing::error!("Failed to prepare database connection: {err:?}");
+            tracing::error!("Failed to prepare database connection: {err}");
             std::process::exit(1);
         }
     };
 
     iced::application("Chomp", App::update, App::view)
-        .theme(|_| Theme::CatppuccinFrappe)
+        .theme(App::theme)
         .subscription(App::subscription)
         .run_with(|| (App::new(db), Task::none()))
 }
+
+/// Database location can be chosen with `--db <path>` or `--profile <name>`, see
+/// `chomp_db::resolve_db_path` for the rest of the lookup.
+fn parse_location(args: &mut pico_args::Arguments) -> Result<DbLocation, pico_args::Error> {
+    Ok(DbLocation {
+        path: args.opt_value_from_str("--db")?,
+        profile: args.opt_value_from_str("--profile")?,
+    })
+}
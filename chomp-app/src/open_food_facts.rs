@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use chomp_services::{is_valid_barcode, CreateUpdateProduct};
+use csv::{ReaderBuilder, StringRecord};
+use serde_json::Value;
+
+const KJ_PER_KCAL: f32 = 4.184;
+
+/// Product read from Open Food Facts data export, nutrition values are per 100g.
+#[derive(Debug, Clone, Default)]
+pub struct OffProduct {
+    pub code: String,
+    pub name: String,
+    /// Names from `product_name_<language>` fields, keyed by language code.
+    pub localized_names: HashMap<String, String>,
+    pub brands: Option<String>,
+    /// Country tags like `en:poland`.
+    pub countries: Vec<String>,
+    pub language: Option<String>,
+    pub calories: Option<f32>,
+    pub fats: Option<f32>,
+    pub proteins: Option<f32>,
+    pub carbohydrates: Option<f32>,
+    pub fiber: Option<f32>,
+    pub sugar: Option<f32>,
+    pub saturated_fats: Option<f32>,
+    pub salt: Option<f32>,
+}
+
+/// Limits imported products to ones sold in given country and/or in given language. Exports
+/// contain products from the whole world and most of them are useless for a single person.
+#[derive(Debug, Clone, Default)]
+pub struct OffFilter {
+    pub country: Option<String>,
+    pub language: Option<String>,
+}
+
+impl OffFilter {
+    pub fn new(country: &str, language: &str) -> Self {
+        let country = country.trim().to_lowercase();
+        let language = language.trim().to_lowercase();
+
+        OffFilter {
+            // Country tags are english names prefixed with language, e.g. `en:united-kingdom`.
+            country: (!country.is_empty()).then(|| {
+                if country.contains(':') {
+                    country
+                } else {
+                    format!("en:{}", country.replace(' ', "-"))
+                }
+            }),
+            language: (!language.is_empty()).then_some(language),
+        }
+    }
+
+    pub fn matches(&self, product: &OffProduct) -> bool {
+        let country_matches = self
+            .country
+            .as_ref()
+            .is_none_or(|c| product.countries.iter().any(|pc| pc == c));
+        let language_matches = self.language.as_ref().is_none_or(|l| {
+            product.language.as_ref() == Some(l) || product.localized_names.contains_key(l)
+        });
+
+        country_matches && language_matches
+    }
+}
+
+impl OffProduct {
+    /// Converts product into one that can be stored in product library, products without
+    /// name, valid barcode or any of the main macros are not usable and return `None`. Name in
+    /// `language` is preferred when product has one.
+    pub fn into_create_product(self, language: Option<&str>) -> Option<CreateUpdateProduct> {
+        let name = language
+            .and_then(|l| self.localized_names.get(l).cloned())
+            .unwrap_or(self.name);
+
+        if name.is_empty() || !is_valid_barcode(&self.code) {
+            return None;
+        }
+
+        Some(CreateUpdateProduct {
+            name,
+            company: self.brands.and_then(|b| {
+                b.split(',')
+                    .map(|brand| brand.trim().to_string())
+                    .find(|brand| !brand.is_empty())
+            }),
+            calories: self.calories?,
+            fats: self.fats?,
+            proteins: self.proteins?,
+            carbohydrates: self.carbohydrates?,
+            fiber: self.fiber,
+            sugar: self.sugar,
+            saturated_fats: self.saturated_fats,
+            salt: self.salt,
+            barcode: Some(self.code),
+            nutrients: Vec::new(),
+        })
+    }
+}
+
+/// Names to try, in order, when product name is already taken by a different product.
+/// Unrelated products often share generic names like "Milk" so brand is added first and
+/// barcode, which is unique, as a last resort.
+pub fn alternative_names(product: &CreateUpdateProduct) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(company) = &product.company {
+        names.push(format!("{} ({})", product.name, company));
+    }
+    if let Some(barcode) = &product.barcode {
+        names.push(format!("{} ({})", product.name, barcode));
+    }
+
+    names
+}
+
+/// Reads Open Food Facts export product by product, so that whole dump doesn't have to fit
+/// in memory. Files with `jsonl`, `ndjson` or `json` extension are read as JSON lines, anything
+/// else as tab separated CSV export.
+pub fn read_products<F>(path: &Path, mut handle: F) -> Result<(), String>
+where
+    F: FnMut(Result<OffProduct, String>),
+{
+    let file = File::open(path).map_err(|err| err.to_string())?;
+
+    let is_jsonl = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e, "jsonl" | "ndjson" | "json"));
+
+    if is_jsonl {
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|err| err.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            handle(parse_json_line(&line));
+        }
+    } else {
+        let mut rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .quoting(false)
+            .flexible(true)
+            .from_reader(file);
+
+        let headers: HashMap<String, usize> = rdr
+            .headers()
+            .map_err(|err| err.to_string())?
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.to_string(), i))
+            .collect();
+
+        for record in rdr.records() {
+            handle(
+                record
+                    .map_err(|err| err.to_string())
+                    .map(|r| parse_csv_record(&headers, &r)),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_csv_record(headers: &HashMap<String, usize>, record: &StringRecord) -> OffProduct {
+    let field = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|i| record.get(*i))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+    let number = |name: &str| field(name).and_then(|v| v.parse::<f32>().ok());
+
+    let localized_names = headers
+        .keys()
+        .filter_map(|h| {
+            let language = h.strip_prefix("product_name_")?;
+            Some((language.to_string(), field(h)?.to_string()))
+        })
+        .collect();
+
+    OffProduct {
+        code: field("code").unwrap_or_default().to_string(),
+        name: field("product_name").unwrap_or_default().to_string(),
+        localized_names,
+        brands: field("brands").map(|b| b.to_string()),
+        countries: field("countries_tags")
+            .map(|c| c.split(',').map(|t| t.trim().to_string()).collect())
+            .unwrap_or_default(),
+        language: field("lang").map(|l| l.to_string()),
+        // Some products only have energy in kJ, `energy_100g` is in kJ as well.
+        calories: number("energy-kcal_100g")
+            .or(number("energy-kj_100g").map(|kj| kj / KJ_PER_KCAL))
+            .or(number("energy_100g").map(|kj| kj / KJ_PER_KCAL)),
+        fats: number("fat_100g"),
+        proteins: number("proteins_100g"),
+        carbohydrates: number("carbohydrates_100g"),
+        fiber: number("fiber_100g"),
+        sugar: number("sugars_100g"),
+        saturated_fats: number("saturated-fat_100g"),
+        salt: number("salt_100g"),
+    }
+}
+
+fn parse_json_line(line: &str) -> Result<OffProduct, String> {
+    let product: Value = serde_json::from_str(line).map_err(|err| err.to_string())?;
+
+    let text = |value: &Value| match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    // Numbers in exports are not always stored as JSON numbers.
+    let number = |name: &str| match &product["nutriments"][name] {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        Value::String(s) => s.trim().parse::<f32>().ok(),
+        _ => None,
+    };
+
+    let localized_names = product
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|(key, value)| {
+                    let language = key.strip_prefix("product_name_")?;
+                    Some((language.to_string(), text(value)?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let countries = match &product["countries_tags"] {
+        Value::Array(tags) => tags.iter().filter_map(text).collect(),
+        Value::String(tags) => tags.split(',').map(|t| t.trim().to_string()).collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(OffProduct {
+        code: text(&product["code"]).unwrap_or_default(),
+        name: text(&product["product_name"]).unwrap_or_default(),
+        localized_names,
+        brands: text(&product["brands"]),
+        countries,
+        language: text(&product["lang"]),
+        calories: number("energy-kcal_100g")
+            .or(number("energy-kj_100g").map(|kj| kj / KJ_PER_KCAL))
+            .or(number("energy_100g").map(|kj| kj / KJ_PER_KCAL)),
+        fats: number("fat_100g"),
+        proteins: number("proteins_100g"),
+        carbohydrates: number("carbohydrates_100g"),
+        fiber: number("fiber_100g"),
+        sugar: number("sugars_100g"),
+        saturated_fats: number("saturated-fat_100g"),
+        salt: number("salt_100g"),
+    })
+}
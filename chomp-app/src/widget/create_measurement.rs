@@ -0,0 +1,147 @@
+use chomp_services::{MeasurementType, ServiceError};
+use chrono::Weekday;
+use iced::{
+    widget::{column, pick_list, row, Button, Text},
+    Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{modal, sidebar, DatePicker, InputFormField, InputFormFieldError, Widget};
+
+#[derive(Debug, Clone)]
+pub enum CreateMeasurementMessage {
+    SelectType(MeasurementType),
+    UpdateValue(String),
+    Submit,
+}
+
+impl From<CreateMeasurementMessage> for Message {
+    fn from(value: CreateMeasurementMessage) -> Self {
+        Message::CreateMeasurement(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateMeasurement {
+    day: DatePicker,
+    types: Vec<MeasurementType>,
+    measurement_type: Option<MeasurementType>,
+    type_error: Option<InputFormFieldError>,
+    value: InputFormField<f32>,
+}
+
+impl CreateMeasurement {
+    pub fn new(types: Vec<MeasurementType>, week_start: Weekday) -> Self {
+        CreateMeasurement {
+            day: DatePicker::new("Date*", week_start),
+            measurement_type: types.first().cloned(),
+            types,
+            type_error: None,
+            value: InputFormField::new("Value*", "85.5"),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<chomp_services::CreateMeasurement, String> {
+        self.value.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(val),
+                }
+            }
+        });
+        self.type_error = match self.measurement_type {
+            Some(_) => None,
+            None => Some(InputFormFieldError::MissingRequiredValue),
+        };
+
+        Ok(chomp_services::CreateMeasurement {
+            type_id: self
+                .measurement_type
+                .as_ref()
+                .map(|t| t.id)
+                .ok_or("validation failed")?,
+            day: self.day.value(),
+            value: self.value.value.ok_or("validation failed")?,
+        })
+    }
+}
+
+impl Widget for CreateMeasurement {
+    fn view(&self) -> Element<'_, Message> {
+        let mut measurement_type = column![
+            Text::new("Measurement*"),
+            pick_list(self.types.as_slice(), self.measurement_type.clone(), |t| {
+                CreateMeasurementMessage::SelectType(t).into()
+            })
+        ]
+        .spacing(2);
+        if let Some(err) = &self.type_error {
+            measurement_type = measurement_type.push(Text::new(err.to_string()));
+        }
+
+        let form = column![
+            self.day.view(),
+            measurement_type,
+            self.value
+                .view(|v| CreateMeasurementMessage::UpdateValue(v).into()),
+        ]
+        .spacing(10);
+
+        let content = column![
+            Text::new("Create measurement").size(40),
+            form,
+            Button::new("Create").on_press(CreateMeasurementMessage::Submit.into())
+        ]
+        .spacing(10);
+
+        let content_with_sidebar = row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20);
+
+        modal(
+            content_with_sidebar.into(),
+            self.day.view_modal(),
+            Message::CloseDatePicker,
+            self.day.calendar_open(),
+        )
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        self.day.handle_message(msg.clone());
+
+        if let Message::CreateMeasurement(msg) = msg {
+            match msg {
+                CreateMeasurementMessage::SelectType(measurement_type) => {
+                    self.measurement_type = Some(measurement_type);
+                }
+                CreateMeasurementMessage::UpdateValue(raw_value) => {
+                    self.value.raw_input = raw_value;
+                }
+                CreateMeasurementMessage::Submit => {
+                    if let Ok(measurement) = self.parse() {
+                        match ctx.services.measurement.create(measurement) {
+                            Ok(_) => ctx.next_widget = Some(NextWidget::MeasurementList),
+                            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                                self.day.error = Some(InputFormFieldError::Custom(
+                                    "This measurement already exists for this date".to_string(),
+                                ))
+                            }
+                            Err(err) => {
+                                tracing::error!("Failed to create measurement: {}", err);
+                                std::process::exit(1);
+                            }
+                        }
+                    };
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
@@ -1,4 +1,5 @@
-use chomp_services::{NutritionTarget, ServiceError};
+use chomp_services::{NutritionTarget, ServiceError, Units};
+use chrono::Weekday;
 use iced::{
     widget::{column, row, Button, Text},
     Element, Length, Task,
@@ -30,16 +31,18 @@ pub struct CreateNutritionTarget {
     fats: InputFormField<f32>,
     proteins: InputFormField<f32>,
     carbohydrates: InputFormField<f32>,
+    units: Units,
 }
 
 impl CreateNutritionTarget {
-    pub fn new() -> Self {
+    pub fn new(units: Units, week_start: Weekday) -> Self {
         CreateNutritionTarget {
-            day: DatePicker::new("Date*"),
-            calories: InputFormField::new("Calories* (kcal/day)", "2500.0"),
+            day: DatePicker::new("Date*", week_start),
+            calories: InputFormField::new(&format!("Calories* ({}/day)", units.energy), "2500.0"),
             fats: InputFormField::new_with_raw_value("Fats* (%)", "20.0", "20"),
             proteins: InputFormField::new_with_raw_value("Proteins* (%)", "30.0", "30"),
             carbohydrates: InputFormField::new_with_raw_value("Carbohydrates* (%)", "50.0", "50"),
+            units,
         }
     }
 
@@ -56,7 +59,10 @@ impl CreateNutritionTarget {
             }
         });
 
-        let calories = self.calories.value.ok_or("validation failed")?;
+        let calories = self
+            .units
+            .energy
+            .to_canonical(self.calories.value.ok_or("validation failed")?);
 
         self.fats.validate(|input| {
             if input.is_empty() {
@@ -130,7 +136,7 @@ impl CreateNutritionTarget {
 }
 
 impl Widget for CreateNutritionTarget {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![
             self.day.view(),
             self.calories
@@ -1,21 +1,33 @@
-use chomp_services::{CreateUpdateProduct, ServiceError};
+use chomp_services::{
+    is_valid_barcode, CreateUpdateProduct, Nutrient, ServiceError, SetProductNutrient, Units,
+};
 use iced::{
-    widget::{column, row, Button, Text},
+    widget::{column, row, Button, Scrollable, Text},
     Element, Length, Task,
 };
 
 use crate::app::{Context, Message, NextWidget};
 
-use super::{sidebar::sidebar, InputFormField, InputFormFieldError, Widget};
+use super::{
+    sidebar::sidebar, taken_product_name_error, InputFormField, InputFormFieldError, Widget,
+};
+
+type NutrientId = usize;
 
 #[derive(Debug, Clone)]
 pub enum CreateProductMessage {
     UpdateName(String),
     UpdateCompany(String),
+    UpdateBarcode(String),
     UpdateCalories(String),
     UpdateFats(String),
     UpdateProteins(String),
     UpdateCarbohydrates(String),
+    UpdateFiber(String),
+    UpdateSugar(String),
+    UpdateSaturatedFats(String),
+    UpdateSalt(String),
+    UpdateNutrient(NutrientId, String),
     Submit,
 }
 
@@ -29,21 +41,42 @@ impl From<CreateProductMessage> for Message {
 pub struct CreateProduct {
     name: InputFormField<String>,
     company: InputFormField<Option<String>>,
+    barcode: InputFormField<Option<String>>,
     calories: InputFormField<f32>,
     fats: InputFormField<f32>,
     proteins: InputFormField<f32>,
     carbohydrates: InputFormField<f32>,
+    fiber: InputFormField<Option<f32>>,
+    sugar: InputFormField<Option<f32>>,
+    saturated_fats: InputFormField<Option<f32>>,
+    salt: InputFormField<Option<f32>>,
+    nutrients: Vec<(Nutrient, InputFormField<Option<f32>>)>,
+    units: Units,
 }
 
 impl CreateProduct {
-    pub fn new() -> Self {
+    pub fn new(nutrients: Vec<Nutrient>, units: Units) -> Self {
         CreateProduct {
             name: InputFormField::new("Name*", "Chicken"),
             company: InputFormField::new("Company", "Chicken Inc."),
-            calories: InputFormField::new("Calories* (kcal/100g)", "100.0"),
+            barcode: InputFormField::new("Barcode (EAN/UPC)", "5901234123457"),
+            calories: InputFormField::new(&format!("Calories* ({}/100g)", units.energy), "100.0"),
             fats: InputFormField::new("Fats* (g/100g)", "2.0"),
             proteins: InputFormField::new("Proteins* (g/100g)", "20.0"),
             carbohydrates: InputFormField::new("Carbohydrates* (g/100g)", "1.0"),
+            fiber: InputFormField::new("Fiber (g/100g)", "0.5"),
+            sugar: InputFormField::new("Sugar (g/100g)", "0.5"),
+            saturated_fats: InputFormField::new("Saturated fats (g/100g)", "0.5"),
+            salt: InputFormField::new("Salt (g/100g)", "0.1"),
+            nutrients: nutrients
+                .into_iter()
+                .map(|n| {
+                    let field =
+                        InputFormField::new(&format!("{} ({}/100g)", n.name, n.unit), "0.0");
+                    (n, field)
+                })
+                .collect(),
+            units,
         }
     }
 
@@ -66,6 +99,19 @@ impl CreateProduct {
             }
         });
 
+        self.barcode.validate(|input| {
+            let input = input.trim();
+            if input.is_empty() {
+                Ok(None)
+            } else if !is_valid_barcode(input) {
+                Err(InputFormFieldError::Custom(
+                    "Invalid barcode, expected EAN-8, UPC-A, EAN-13 or GTIN-14".to_string(),
+                ))
+            } else {
+                Ok(Some(input.to_string()))
+            }
+        });
+
         self.calories.validate(|input| {
             if input.is_empty() {
                 Err(InputFormFieldError::MissingRequiredValue)
@@ -114,24 +160,107 @@ impl CreateProduct {
             }
         });
 
+        self.fiber.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.sugar.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.saturated_fats.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.salt.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        for (_, field) in self.nutrients.iter_mut() {
+            field.validate(|input| {
+                if input.is_empty() {
+                    Ok(None)
+                } else {
+                    match input.parse::<f32>() {
+                        Err(_) => Err(InputFormFieldError::InvalidNumber),
+                        Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                        Ok(val) => Ok(Some(val)),
+                    }
+                }
+            });
+        }
+
+        let mut nutrients = Vec::new();
+        for (nutrient, field) in self.nutrients.iter() {
+            if let Some(amount) = field.value.ok_or("validation failed")? {
+                nutrients.push(SetProductNutrient {
+                    nutrient_id: nutrient.id,
+                    amount,
+                });
+            }
+        }
+
         Ok(CreateUpdateProduct {
             name: self.name.value.clone().ok_or("validation failed")?,
             company: self.company.value.clone().ok_or("validation failed")?,
-            calories: self.calories.value.ok_or("validation failed")?,
+            calories: self
+                .units
+                .energy
+                .to_canonical(self.calories.value.ok_or("validation failed")?),
             fats: self.fats.value.ok_or("validation failed")?,
             proteins: self.proteins.value.ok_or("validation failed")?,
             carbohydrates: self.carbohydrates.value.ok_or("validation failed")?,
+            fiber: self.fiber.value.ok_or("validation failed")?,
+            sugar: self.sugar.value.ok_or("validation failed")?,
+            saturated_fats: self.saturated_fats.value.ok_or("validation failed")?,
+            salt: self.salt.value.ok_or("validation failed")?,
+            barcode: self.barcode.value.clone().ok_or("validation failed")?,
+            nutrients,
         })
     }
 }
 
 impl Widget for CreateProduct {
-    fn view(&self) -> Element<Message> {
-        let form = column![
+    fn view(&self) -> Element<'_, Message> {
+        let mut form = column![
             self.name
                 .view(|n| { CreateProductMessage::UpdateName(n).into() }),
             self.company
                 .view(|c| { CreateProductMessage::UpdateCompany(c).into() }),
+            self.barcode
+                .view(|b| { CreateProductMessage::UpdateBarcode(b).into() }),
             self.calories
                 .view(|c| { CreateProductMessage::UpdateCalories(c).into() }),
             self.fats
@@ -140,12 +269,27 @@ impl Widget for CreateProduct {
                 .view(|p| { CreateProductMessage::UpdateProteins(p).into() }),
             self.carbohydrates
                 .view(|c| { CreateProductMessage::UpdateCarbohydrates(c).into() }),
+            self.fiber
+                .view(|f| { CreateProductMessage::UpdateFiber(f).into() }),
+            self.sugar
+                .view(|s| { CreateProductMessage::UpdateSugar(s).into() }),
+            self.saturated_fats
+                .view(|s| { CreateProductMessage::UpdateSaturatedFats(s).into() }),
+            self.salt
+                .view(|s| { CreateProductMessage::UpdateSalt(s).into() }),
         ]
         .spacing(10);
 
+        for (nutrient, field) in self.nutrients.iter() {
+            let nutrient_id = nutrient.id;
+            form = form.push(
+                field.view(move |a| CreateProductMessage::UpdateNutrient(nutrient_id, a).into()),
+            );
+        }
+
         let content = column![
             Text::new("Create product").size(40),
-            form,
+            Scrollable::new(form).height(Length::Fill),
             Button::new("Create").on_press(CreateProductMessage::Submit.into())
         ]
         .spacing(10);
@@ -166,6 +310,9 @@ impl Widget for CreateProduct {
                 CreateProductMessage::UpdateCompany(company) => {
                     self.company.raw_input = company;
                 }
+                CreateProductMessage::UpdateBarcode(barcode) => {
+                    self.barcode.raw_input = barcode;
+                }
                 CreateProductMessage::UpdateCalories(raw_calories) => {
                     self.calories.raw_input = raw_calories;
                 }
@@ -178,6 +325,25 @@ impl Widget for CreateProduct {
                 CreateProductMessage::UpdateCarbohydrates(raw_carbohydrates) => {
                     self.carbohydrates.raw_input = raw_carbohydrates;
                 }
+                CreateProductMessage::UpdateFiber(raw_fiber) => {
+                    self.fiber.raw_input = raw_fiber;
+                }
+                CreateProductMessage::UpdateSugar(raw_sugar) => {
+                    self.sugar.raw_input = raw_sugar;
+                }
+                CreateProductMessage::UpdateSaturatedFats(raw_saturated_fats) => {
+                    self.saturated_fats.raw_input = raw_saturated_fats;
+                }
+                CreateProductMessage::UpdateSalt(raw_salt) => {
+                    self.salt.raw_input = raw_salt;
+                }
+                CreateProductMessage::UpdateNutrient(nutrient_id, raw_amount) => {
+                    if let Some((_, field)) =
+                        self.nutrients.iter_mut().find(|(n, _)| n.id == nutrient_id)
+                    {
+                        field.raw_input = raw_amount;
+                    }
+                }
                 CreateProductMessage::Submit => {
                     if let Ok(product) = self.parse() {
                         if let Some(err) = ctx.services.product.create(product).err() {
@@ -185,8 +351,16 @@ impl Widget for CreateProduct {
                                 ServiceError::UniqueConstraintViolation(unique_field)
                                     if unique_field == "products.name" =>
                                 {
-                                    self.name.error = Some(InputFormFieldError::Custom(
-                                        "Product with this name already exists".to_string(),
+                                    self.name.error = Some(taken_product_name_error(
+                                        &ctx.services,
+                                        &self.name.raw_input,
+                                    ))
+                                }
+                                ServiceError::UniqueConstraintViolation(unique_field)
+                                    if unique_field == "products.barcode" =>
+                                {
+                                    self.barcode.error = Some(InputFormFieldError::Custom(
+                                        "Product with this barcode already exists".to_string(),
                                     ))
                                 }
                                 _ => {
@@ -1,4 +1,4 @@
-use chomp_services::{Product, ServiceError};
+use chomp_services::{Product, ServiceError, Units};
 use iced::{
     widget::{column, row, Button, Text},
     Element, Length, Task,
@@ -26,14 +26,16 @@ pub struct CreateProductPortion {
     product: Product,
     name: InputFormField<String>,
     weight: InputFormField<f32>,
+    units: Units,
 }
 
 impl CreateProductPortion {
-    pub fn new(product: &Product) -> Self {
+    pub fn new(product: &Product, units: Units) -> Self {
         CreateProductPortion {
             product: product.to_owned(),
             name: InputFormField::new("Name*", "One package"),
-            weight: InputFormField::new("Weight (g)*", "100.0"),
+            weight: InputFormField::new(&format!("Weight ({})*", units.food_weight), "100.0"),
+            units,
         }
     }
 
@@ -63,13 +65,16 @@ impl CreateProductPortion {
         Ok(chomp_services::CreateProductPortion {
             product_id: self.product.id,
             name: self.name.value.clone().ok_or("validation failed")?,
-            weight: self.weight.value.ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
 impl Widget for CreateProductPortion {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![
             self.name
                 .view(|n| { CreateProductPortionMessage::UpdateName(n).into() }),
@@ -0,0 +1,139 @@
+use chomp_services::{CreateUpdateRecipe, ServiceError, Units};
+use iced::{
+    widget::{column, row, Button, Text},
+    Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{
+    sidebar::sidebar, taken_product_name_error, InputFormField, InputFormFieldError, Widget,
+};
+
+#[derive(Debug, Clone)]
+pub enum CreateRecipeMessage {
+    UpdateName(String),
+    UpdateWeight(String),
+    Submit,
+}
+
+impl From<CreateRecipeMessage> for Message {
+    fn from(value: CreateRecipeMessage) -> Self {
+        Message::CreateRecipe(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateRecipe {
+    name: InputFormField<String>,
+    weight: InputFormField<f32>,
+    units: Units,
+}
+
+impl CreateRecipe {
+    pub fn new(units: Units) -> Self {
+        CreateRecipe {
+            name: InputFormField::new("Name*", "Chili con carne"),
+            weight: InputFormField::new(
+                &format!("Weight after cooking* ({})", units.food_weight),
+                "2500.0",
+            ),
+            units,
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<CreateUpdateRecipe, String> {
+        self.name.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else if input.len() < 3 {
+                Err(InputFormFieldError::TooShort(3))
+            } else {
+                Ok(input.to_string())
+            }
+        });
+
+        self.weight.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val <= 0.0 => Err(InputFormFieldError::Custom(
+                        "Invalid number. Must be greater than zero".to_string(),
+                    )),
+                    Ok(val) => Ok(val),
+                }
+            }
+        });
+
+        Ok(CreateUpdateRecipe {
+            name: self.name.value.clone().ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
+        })
+    }
+}
+
+impl Widget for CreateRecipe {
+    fn view(&self) -> Element<'_, Message> {
+        let form = column![
+            self.name
+                .view(|n| { CreateRecipeMessage::UpdateName(n).into() }),
+            self.weight
+                .view(|w| { CreateRecipeMessage::UpdateWeight(w).into() }),
+        ]
+        .spacing(10);
+
+        let content = column![
+            Text::new("Create recipe").size(40),
+            form,
+            Button::new("Create").on_press(CreateRecipeMessage::Submit.into())
+        ]
+        .spacing(10);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::CreateRecipe(msg) = msg {
+            match msg {
+                CreateRecipeMessage::UpdateName(name) => {
+                    self.name.raw_input = name;
+                }
+                CreateRecipeMessage::UpdateWeight(raw_weight) => {
+                    self.weight.raw_input = raw_weight;
+                }
+                CreateRecipeMessage::Submit => {
+                    if let Ok(recipe) = self.parse() {
+                        if let Some(err) = ctx.services.recipe.create(recipe).err() {
+                            match err {
+                                ServiceError::UniqueConstraintViolation(unique_field)
+                                    if unique_field == "products.name" =>
+                                {
+                                    self.name.error = Some(taken_product_name_error(
+                                        &ctx.services,
+                                        &self.name.raw_input,
+                                    ))
+                                }
+                                _ => {
+                                    eprintln!("Error: {err:?}");
+                                }
+                            }
+                        } else {
+                            ctx.next_widget = Some(NextWidget::RecipeList);
+                        }
+                    };
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
@@ -1,4 +1,5 @@
-use chomp_services::{ServiceError, Weight};
+use chomp_services::{ServiceError, Units, Weight};
+use chrono::Weekday;
 use iced::{
     widget::{column, row, Button, Text},
     Element, Length, Task,
@@ -24,13 +25,15 @@ impl From<CreateWeightMessage> for Message {
 pub struct CreateWeight {
     day: DatePicker,
     weight: InputFormField<f32>,
+    units: Units,
 }
 
 impl CreateWeight {
-    pub fn new() -> Self {
+    pub fn new(units: Units, week_start: Weekday) -> Self {
         CreateWeight {
-            day: DatePicker::new("Date*"),
-            weight: InputFormField::new("Weight* (g)", "80.1"),
+            day: DatePicker::new("Date*", week_start),
+            weight: InputFormField::new(&format!("Weight* ({})", units.body_weight), "80.1"),
+            units,
         }
     }
 
@@ -49,13 +52,16 @@ impl CreateWeight {
 
         Ok(Weight {
             day: self.day.value(),
-            weight: self.weight.value.ok_or("validation failed")?,
+            weight: self
+                .units
+                .body_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
 impl Widget for CreateWeight {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![
             self.day.view(),
             self.weight
@@ -1,15 +1,198 @@
-use chomp_services::Weight;
+use std::{fmt, str::FromStr};
+
+use chomp_services::{
+    DayTotals, EnergyEstimate, Macros, NutritionTarget, ServiceError, Services, Units,
+    WeightAnalysis, ENERGY_WINDOW_DAYS,
+};
+use chrono::{Days, Local, Months, NaiveDate};
 use iced::{
-    widget::{column, row, Canvas, Text},
-    Element, Length, Task,
+    widget::{button, column, horizontal_space, pick_list, row, Button, Canvas, Row, Text},
+    Alignment, Color, Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{
+    line_chart::{LineChartEntry, LineChartSeries},
+    sidebar::sidebar,
+    InputFormField, InputFormFieldError, LineChart, Widget,
 };
 
-use crate::app::{Context, Message};
+const CALORIES_COLOR: Color = Color::from_rgb(0.95, 0.55, 0.15);
+const FATS_COLOR: Color = Color::from_rgb(0.9, 0.75, 0.2);
+const PROTEINS_COLOR: Color = Color::from_rgb(0.85, 0.3, 0.35);
+const CARBOHYDRATES_COLOR: Color = Color::from_rgb(0.3, 0.55, 0.9);
+const WEIGHT_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const WEIGHT_TREND_COLOR: Color = Color::from_rgb(0.2, 0.65, 0.45);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartRange {
+    Week,
+    Month,
+    ThreeMonths,
+    Year,
+    All,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 5] = [
+        ChartRange::Week,
+        ChartRange::Month,
+        ChartRange::ThreeMonths,
+        ChartRange::Year,
+        ChartRange::All,
+    ];
+
+    /// First day of the range ending on `end`. Days are compared as text in the database, so
+    /// the whole history starts with the first day that still formats as plain `YYYY-MM-DD`.
+    fn start(&self, end: NaiveDate) -> NaiveDate {
+        let start = match self {
+            ChartRange::Week => end.checked_sub_days(Days::new(7)),
+            ChartRange::Month => end.checked_sub_months(Months::new(1)),
+            ChartRange::ThreeMonths => end.checked_sub_months(Months::new(3)),
+            ChartRange::Year => end.checked_sub_months(Months::new(12)),
+            ChartRange::All => return NaiveDate::from_ymd_opt(1, 1, 1).unwrap(),
+        };
+
+        start.and_then(|d| d.succ_opt()).unwrap_or(end)
+    }
+}
+
+impl fmt::Display for ChartRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChartRange::Week => write!(f, "1W"),
+            ChartRange::Month => write!(f, "1M"),
+            ChartRange::ThreeMonths => write!(f, "3M"),
+            ChartRange::Year => write!(f, "1Y"),
+            ChartRange::All => write!(f, "All"),
+        }
+    }
+}
+
+impl FromStr for ChartRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChartRange::ALL
+            .into_iter()
+            .find(|r| r.to_string() == s)
+            .ok_or(format!("unknown chart range '{s}'"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NutritionChart {
+    Calories,
+    Macros,
+    Fats,
+    Proteins,
+    Carbohydrates,
+}
+
+impl NutritionChart {
+    const ALL: [NutritionChart; 5] = [
+        NutritionChart::Calories,
+        NutritionChart::Macros,
+        NutritionChart::Fats,
+        NutritionChart::Proteins,
+        NutritionChart::Carbohydrates,
+    ];
+
+    fn chart(&self, days: &[DayTotals], units: Units) -> LineChart {
+        let unit = match self {
+            NutritionChart::Calories => units.energy.symbol(),
+            _ => "g",
+        };
+
+        LineChart::new(self.series(days, units)).with_unit(unit)
+    }
+
+    fn series(&self, days: &[DayTotals], units: Units) -> Vec<LineChartSeries> {
+        match self {
+            NutritionChart::Calories => nutrient_series(
+                "Calories",
+                CALORIES_COLOR,
+                days,
+                |m| m.calories,
+                |t| t.calories,
+            )
+            .into_iter()
+            .map(|mut s| {
+                for (_, value) in s.data.iter_mut() {
+                    *value = units.energy.from_canonical(*value);
+                }
+                s
+            })
+            .collect(),
+            NutritionChart::Macros => [
+                NutritionChart::Fats,
+                NutritionChart::Proteins,
+                NutritionChart::Carbohydrates,
+            ]
+            .iter()
+            .flat_map(|chart| chart.series(days, units))
+            .collect(),
+            NutritionChart::Fats => {
+                nutrient_series("Fats", FATS_COLOR, days, |m| m.fats, |t| t.fats)
+            }
+            NutritionChart::Proteins => nutrient_series(
+                "Proteins",
+                PROTEINS_COLOR,
+                days,
+                |m| m.proteins,
+                |t| t.proteins,
+            ),
+            NutritionChart::Carbohydrates => nutrient_series(
+                "Carbohydrates",
+                CARBOHYDRATES_COLOR,
+                days,
+                |m| m.carbohydrates,
+                |t| t.carbohydrates,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for NutritionChart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NutritionChart::Calories => write!(f, "Calories"),
+            NutritionChart::Macros => write!(f, "Macros"),
+            NutritionChart::Fats => write!(f, "Fats"),
+            NutritionChart::Proteins => write!(f, "Proteins"),
+            NutritionChart::Carbohydrates => write!(f, "Carbohydrates"),
+        }
+    }
+}
+
+/// Series of daily values together with dashed line of the target in effect on each day.
+fn nutrient_series(
+    name: &str,
+    color: Color,
+    days: &[DayTotals],
+    value: fn(&Macros) -> f32,
+    target: fn(&NutritionTarget) -> f32,
+) -> Vec<LineChartSeries> {
+    let values: Vec<LineChartEntry> = days.iter().map(|d| (d.day, value(&d.totals))).collect();
+    let targets: Vec<LineChartEntry> = days
+        .iter()
+        .filter_map(|d| d.target.as_ref().map(|t| (d.day, target(t))))
+        .collect();
 
-use super::{line_chart::LineChartEntry, sidebar::sidebar, LineChart, Widget};
+    vec![
+        LineChartSeries::new(name, Some(color), values),
+        LineChartSeries::new(&format!("{name} target"), Some(color), targets).dashed(),
+    ]
+}
 
 #[derive(Debug, Clone)]
-pub enum DashboardMessage {}
+pub enum DashboardMessage {
+    ChangeRange(ChartRange),
+    ChangeNutritionChart(NutritionChart),
+    UpdateTargetAdjustment(String),
+    SubmitTarget,
+}
 
 impl From<DashboardMessage> for Message {
     fn from(value: DashboardMessage) -> Self {
@@ -19,25 +202,258 @@ impl From<DashboardMessage> for Message {
 
 #[derive(Debug)]
 pub struct Dashboard {
-    chart: LineChart,
+    range: ChartRange,
+    nutrition_chart: NutritionChart,
+    days: Vec<DayTotals>,
+    weight_chart: LineChart,
+    weight_summary: String,
+    energy: Option<EnergyEstimate>,
+    target_adjustment: InputFormField<f32>,
+    nutrition_line_chart: LineChart,
+    units: Units,
 }
 
 impl Dashboard {
-    pub fn new(weights: Vec<Weight>) -> Self {
-        let weights: Vec<LineChartEntry> = weights.into_iter().map(|w| (w.day, w.weight)).collect();
+    /// Weight analysis covers whole history so that the trend is correct from the first day of
+    /// the range, chart only shows days from the range.
+    pub fn new(
+        range: ChartRange,
+        weights: WeightAnalysis,
+        days: Vec<DayTotals>,
+        energy: Option<EnergyEstimate>,
+        units: Units,
+    ) -> Self {
+        let nutrition_chart = NutritionChart::Calories;
+        let (start, _) = range_days(range);
+        let default_adjustment = format!("{:.0}", units.energy.from_canonical(-500.0));
 
         Dashboard {
-            chart: LineChart::new(weights),
+            range,
+            nutrition_chart,
+            nutrition_line_chart: nutrition_chart.chart(&days, units),
+            days,
+            weight_chart: weight_chart(&weights, start, units),
+            weight_summary: weight_summary(&weights, units),
+            energy,
+            target_adjustment: InputFormField::new_with_raw_value(
+                &format!("Calories over maintenance ({})", units.energy),
+                &default_adjustment,
+                &default_adjustment,
+            ),
+            units,
+        }
+    }
+
+    /// Loads data shown on the dashboard for the range ending today.
+    pub fn load(services: &Services, range: ChartRange) -> Self {
+        let (start, end) = range_days(range);
+        let weights = match services.weight.analysis() {
+            Ok(w) => w,
+            Err(err) => {
+                tracing::error!("Failed to analyse weights: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let days = match services.report.day_totals(start, end) {
+            Ok(d) => d,
+            Err(err) => {
+                tracing::error!("Failed to get daily nutrition totals: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+        // Maintenance is always estimated from the recent weeks, range only changes the charts.
+        let energy = match services.energy.estimate(end, ENERGY_WINDOW_DAYS) {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::error!("Failed to estimate maintenance calories: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+        let units = services.settings.read().unwrap_or_default().units;
+
+        Dashboard::new(range, weights, days, energy, units)
+    }
+
+    fn energy_view(&self) -> Element<'_, Message> {
+        let Some(energy) = &self.energy else {
+            return Text::new(format!(
+                "Log meals on at least half of the last {} days and weigh yourself for at least a week to estimate maintenance calories",
+                ENERGY_WINDOW_DAYS
+            ))
+            .into();
+        };
+
+        let energy_unit = self.units.energy;
+        let weight_unit = self.units.body_weight;
+        row![
+            Text::new(format!(
+                "Estimated maintenance {:.0} {energy_unit} ({} days logged, average intake {:.0} {energy_unit}, {:+.2} {weight_unit} per week)",
+                energy_unit.from_canonical(energy.maintenance),
+                energy.days_logged,
+                energy_unit.from_canonical(energy.average_intake),
+                weight_unit.from_canonical(energy.weekly_change)
+            ))
+            .width(Length::Fill),
+            self.target_adjustment.view_with_submit(
+                |a| DashboardMessage::UpdateTargetAdjustment(a).into(),
+                DashboardMessage::SubmitTarget.into()
+            ),
+            Button::new("Set as target").on_press(DashboardMessage::SubmitTarget.into())
+        ]
+        .spacing(10)
+        .align_y(Alignment::End)
+        .into()
+    }
+
+    fn submit_target(&mut self, ctx: &mut Context) {
+        let Some(energy) = &self.energy else {
+            return;
+        };
+        self.target_adjustment.validate(|input| {
+            input
+                .parse::<f32>()
+                .map_err(|_| InputFormFieldError::InvalidNumber)
+        });
+        let Some(adjustment) = self
+            .target_adjustment
+            .value
+            .filter(|_| self.target_adjustment.error.is_none())
+        else {
+            return;
+        };
+
+        let base = match ctx.services.nutrition_target.read_last_or_create_default() {
+            Ok(t) => t,
+            Err(err) => {
+                tracing::error!("Failed to read nutrition target: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let today = Local::now().date_naive();
+        let adjustment = self.units.energy.to_canonical(adjustment);
+        let target = energy.target(today, adjustment, &base);
+        let result = match ctx.services.nutrition_target.create(target.clone()) {
+            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                ctx.services.nutrition_target.update(target)
+            }
+            result => result,
+        };
+        if let Err(err) = result {
+            tracing::error!("Failed to save nutrition target: {}", err);
+            std::process::exit(1);
         }
+
+        // Macros only follow the split of the previous target, so let the user adjust them.
+        ctx.next_widget = Some(NextWidget::UpdateNutritionTarget(today));
     }
 }
 
+fn range_days(range: ChartRange) -> (NaiveDate, NaiveDate) {
+    let end = Local::now().date_naive();
+    (range.start(end), end)
+}
+
+fn range_buttons(selected: ChartRange) -> Element<'static, Message> {
+    ChartRange::ALL
+        .into_iter()
+        .map(|range| {
+            Button::new(Text::new(range.to_string()))
+                .style(if range == selected {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(DashboardMessage::ChangeRange(range).into())
+                .into()
+        })
+        .collect::<Row<Message>>()
+        .spacing(2)
+        .into()
+}
+
+fn weight_chart(weights: &WeightAnalysis, start: NaiveDate, units: Units) -> LineChart {
+    let unit = units.body_weight;
+    let in_range = || weights.trend.iter().filter(|t| t.day >= start);
+    let raw: Vec<LineChartEntry> = in_range()
+        .map(|t| (t.day, unit.from_canonical(t.weight)))
+        .collect();
+    let trend: Vec<LineChartEntry> = in_range()
+        .map(|t| (t.day, unit.from_canonical(t.trend)))
+        .collect();
+
+    LineChart::new(vec![
+        LineChartSeries::new("Weight", Some(WEIGHT_COLOR), raw).points(),
+        LineChartSeries::new("Trend", Some(WEIGHT_TREND_COLOR), trend),
+    ])
+    .with_unit(unit.symbol())
+}
+
+fn weight_summary(weights: &WeightAnalysis, units: Units) -> String {
+    let Some(current) = weights.current() else {
+        return "No weights yet".to_string();
+    };
+
+    let unit = units.body_weight;
+    let mut summary = format!("Trend {:.1} {unit}", unit.from_canonical(current.trend));
+    if let Some(change) = weights.weekly_change {
+        summary.push_str(&format!(
+            ", {:+.2} {unit} per week",
+            unit.from_canonical(change)
+        ));
+    }
+    if let Some(goal) = weights.goal {
+        let goal = unit.from_canonical(goal);
+        match weights.goal_day {
+            Some(day) => summary.push_str(&format!(
+                ", goal of {:.1} {unit} reached around {}",
+                goal,
+                day.format("%Y-%m-%d")
+            )),
+            None => summary.push_str(&format!(
+                ", goal of {:.1} {unit} not reached at current rate",
+                goal
+            )),
+        }
+    }
+
+    summary
+}
+
 impl Widget for Dashboard {
-    fn view(&self) -> Element<Message> {
-        let canvas = Canvas::new(&self.chart)
+    fn view(&self) -> Element<'_, Message> {
+        let nutrition_canvas = Canvas::new(&self.nutrition_line_chart)
+            .width(Length::Fill)
+            .height(Length::Fill);
+        let weight_canvas = Canvas::new(&self.weight_chart)
             .width(Length::Fill)
             .height(Length::Fill);
-        let content = column![Text::new("Dashboard").size(40), canvas].spacing(10);
+
+        let content = column![
+            row![
+                Text::new("Dashboard").size(40),
+                horizontal_space(),
+                pick_list(NutritionChart::ALL, Some(self.nutrition_chart), |c| {
+                    DashboardMessage::ChangeNutritionChart(c).into()
+                }),
+                range_buttons(self.range),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            self.energy_view(),
+            Text::new("Scroll to zoom, drag to move and right click to reset the chart").size(12),
+            Text::new(self.nutrition_chart.to_string()).size(20),
+            nutrition_canvas,
+            row![
+                Text::new("Weight").size(20),
+                Text::new(&self.weight_summary)
+            ]
+            .spacing(20)
+            .align_y(Alignment::Center),
+            weight_canvas
+        ]
+        .spacing(10);
 
         row![sidebar(), content]
             .height(Length::Fill)
@@ -46,7 +462,36 @@ impl Widget for Dashboard {
             .into()
     }
 
-    fn update(&mut self, _ctx: &mut Context, _msg: Message) -> Task<Message> {
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::Dashboard(msg) = msg {
+            match msg {
+                DashboardMessage::ChangeRange(range) => {
+                    let Dashboard {
+                        days,
+                        weight_chart,
+                        weight_summary,
+                        ..
+                    } = Dashboard::load(&ctx.services, range);
+                    self.range = range;
+                    self.days = days;
+                    self.weight_chart = weight_chart;
+                    self.weight_summary = weight_summary;
+                }
+                DashboardMessage::ChangeNutritionChart(chart) => {
+                    self.nutrition_chart = chart;
+                }
+                DashboardMessage::UpdateTargetAdjustment(raw_adjustment) => {
+                    self.target_adjustment.raw_input = raw_adjustment;
+                    return Task::none();
+                }
+                DashboardMessage::SubmitTarget => {
+                    self.submit_target(ctx);
+                    return Task::none();
+                }
+            }
+            self.nutrition_line_chart = self.nutrition_chart.chart(&self.days, self.units);
+        }
+
         Task::none()
     }
 }
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use chrono::{Datelike, Days, Local, Month, NaiveDate};
+use chrono::{Datelike, Days, Local, Month, NaiveDate, Weekday};
 use iced::{
     widget::{button, column, container, horizontal_space, pick_list, row, Button, Row, Text},
     Color, Element, Length,
@@ -46,28 +46,31 @@ pub struct DatePicker {
     value: NaiveDate,
     pub error: Option<InputFormFieldError>,
     calendar_state: Option<CalendarState>,
+    week_start: Weekday,
 }
 
 impl DatePicker {
-    pub fn new(name: &str) -> Self {
+    pub fn new(name: &str, week_start: Weekday) -> Self {
         DatePicker {
             name: name.to_string(),
             value: Local::now().date_naive(),
             error: None,
             calendar_state: None,
+            week_start,
         }
     }
 
-    pub fn new_with_value(name: &str, date: &NaiveDate) -> Self {
+    pub fn new_with_value(name: &str, date: &NaiveDate, week_start: Weekday) -> Self {
         DatePicker {
             name: name.to_string(),
             value: date.to_owned(),
             error: None,
             calendar_state: None,
+            week_start,
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let today = Local::now().date_naive();
         let tomorrow = today.checked_add_days(Days::new(1)).unwrap();
         let yesterday = today.checked_sub_days(Days::new(1)).unwrap();
@@ -92,7 +95,7 @@ impl DatePicker {
         column.into()
     }
 
-    pub fn view_modal(&self) -> Element<Message> {
+    pub fn view_modal(&self) -> Element<'_, Message> {
         let calendar_state = match self.calendar_state.as_ref() {
             Some(cs) => cs,
             None => return row![].into(),
@@ -117,9 +120,14 @@ impl DatePicker {
 
         let mut calendar = column![month_and_year_row].spacing(SPACING);
 
-        calendar = calendar.push(row![["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
-            .into_iter()
-            .map(|label| Text::new(label).width(CALENDAR_ITEM_WIDTH).into())
+        calendar = calendar.push(row![(0..7)
+            .map(|offset| {
+                let weekday = (0..offset).fold(self.week_start, |day, _| day.succ());
+                let label = &weekday.to_string()[..2];
+                Text::new(label.to_string())
+                    .width(CALENDAR_ITEM_WIDTH)
+                    .into()
+            })
             .collect::<Row<Message>>()
             .spacing(SPACING)]);
 
@@ -127,7 +135,7 @@ impl DatePicker {
 
         let days_from_prev_month = calendar_displayed_month_first_day
             .weekday()
-            .num_days_from_monday();
+            .days_since(self.week_start);
         let first_day_in_calendar = calendar_displayed_month_first_day
             .checked_sub_days(Days::new(days_from_prev_month as u64))
             .unwrap();
@@ -196,6 +204,10 @@ impl DatePicker {
         }
     }
 
+    pub fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
     pub fn value(&self) -> NaiveDate {
         self.value
     }
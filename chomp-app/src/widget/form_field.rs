@@ -5,6 +5,8 @@ use iced::{
     Color, Element,
 };
 
+use chomp_services::Services;
+
 use crate::app::Message;
 
 type Length = usize;
@@ -84,15 +86,28 @@ impl<T> InputFormField<T> {
         }
     }
 
-    pub fn view<F>(&self, handle_message: F) -> Element<Message>
+    pub fn view<F>(&self, handle_message: F) -> Element<'_, Message>
     where
         F: Fn(String) -> Message + 'static,
     {
-        let mut column = column![
-            Text::new(&self.name),
-            TextInput::new(&self.placeholder, &self.raw_input).on_input(handle_message)
-        ]
-        .spacing(2);
+        self.render(TextInput::new(&self.placeholder, &self.raw_input).on_input(handle_message))
+    }
+
+    /// Same as `view` but also sends `on_submit` when enter is pressed, barcode scanners
+    /// type the code followed by enter.
+    pub fn view_with_submit<F>(&self, handle_message: F, on_submit: Message) -> Element<'_, Message>
+    where
+        F: Fn(String) -> Message + 'static,
+    {
+        self.render(
+            TextInput::new(&self.placeholder, &self.raw_input)
+                .on_input(handle_message)
+                .on_submit(on_submit),
+        )
+    }
+
+    fn render<'a>(&'a self, input: TextInput<'a, Message>) -> Element<'a, Message> {
+        let mut column = column![Text::new(&self.name), input].spacing(2);
 
         if let Some(err) = &self.error {
             column = column.push(Text::new(err.to_string()).color(Color::from_rgb(1.0, 0.0, 0.0)));
@@ -101,3 +116,14 @@ impl<T> InputFormField<T> {
         column.into()
     }
 }
+
+/// Error for product name that is already taken. Archived products are not in the product list,
+/// so name taken by one of them is pointed out.
+pub fn taken_product_name_error(services: &Services, name: &str) -> InputFormFieldError {
+    match services.product.is_name_archived(name.trim()) {
+        Ok(true) => InputFormFieldError::Custom(
+            "Archived product with this name exists, restore it instead".to_string(),
+        ),
+        _ => InputFormFieldError::Custom("Product with this name already exists".to_string()),
+    }
+}
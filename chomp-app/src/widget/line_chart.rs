@@ -3,19 +3,111 @@ use std::f32;
 use chrono::{Datelike, NaiveDate};
 use iced::{
     event::Status,
-    mouse::{Cursor, Interaction},
-    widget::canvas::{self, path::Builder, Cache, Event, Frame, Geometry, LineCap, Stroke, Text},
-    Color, Pixels, Point, Rectangle, Renderer, Theme,
+    mouse::{self, Cursor, Interaction, ScrollDelta},
+    widget::canvas::{
+        self, path::Builder, Cache, Event, Fill, Frame, Geometry, LineCap, LineDash, Path, Stroke,
+        Text,
+    },
+    Color, Pixels, Point, Rectangle, Renderer, Size, Theme,
 };
 
 use crate::app::Message;
 
 pub type LineChartEntry = (NaiveDate, f32);
 
+/// Smallest amount of days that can be shown when zooming in.
+const MIN_VISIBLE_DAYS: f32 = 3.0;
+/// How much one step of mouse wheel zooms in or out.
+const ZOOM_STEP: f32 = 0.8;
+/// Pixels of smooth scrolling (touchpads) treated as one step of mouse wheel.
+const PIXELS_PER_SCROLL_STEP: f32 = 20.0;
+
+/// Single line in a chart. Series without color use primary color of the theme, dashed series
+/// are meant for reference values like targets and series drawn as points for noisy values.
+#[derive(Debug, Clone)]
+pub struct LineChartSeries {
+    pub name: String,
+    pub color: Option<Color>,
+    pub dashed: bool,
+    pub points: bool,
+    pub data: Vec<LineChartEntry>,
+}
+
+impl LineChartSeries {
+    pub fn new(name: &str, color: Option<Color>, data: Vec<LineChartEntry>) -> Self {
+        let mut sorted_data = data;
+        sorted_data.sort_by_key(|(date_a, _)| *date_a);
+
+        LineChartSeries {
+            name: name.to_string(),
+            color,
+            dashed: false,
+            points: false,
+            data: sorted_data,
+        }
+    }
+
+    pub fn dashed(mut self) -> Self {
+        self.dashed = true;
+        self
+    }
+
+    /// Draws entries as separate points without connecting them.
+    pub fn points(mut self) -> Self {
+        self.points = true;
+        self
+    }
+}
+
+/// Part of the date axis in days from the common era.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DayWindow {
+    first_day: f32,
+    last_day: f32,
+}
+
+impl DayWindow {
+    fn span(&self) -> f32 {
+        self.last_day - self.first_day
+    }
+
+    /// Moves window so that it fits into `outer`, keeping its span if possible.
+    fn clamp(self, outer: DayWindow) -> DayWindow {
+        let span = self.span().min(outer.span());
+        let first_day = self.first_day.clamp(outer.first_day, outer.last_day - span);
+
+        DayWindow {
+            first_day,
+            last_day: first_day + span,
+        }
+    }
+}
+
+/// Zoom and drag state of the chart, kept by iced between redraws.
+#[derive(Debug, Default)]
+pub struct LineChartState {
+    /// Visible part of the data, `None` shows everything.
+    window: Option<DayWindow>,
+    /// Window of the data the zoom was made for, zoom is reset when data changes.
+    data_window: Option<DayWindow>,
+    /// Cursor x and visible window at the start of dragging.
+    drag: Option<(f32, DayWindow)>,
+}
+
+/// Range of days and values shown in the chart.
+#[derive(Debug, Clone, Copy)]
+struct ChartBounds {
+    first_day: f32,
+    amount_of_days: f32,
+    min_val: f32,
+    val_diff: f32,
+}
+
 #[derive(Debug)]
 pub struct LineChart {
     cache: Cache,
-    data: Vec<LineChartEntry>,
+    series: Vec<LineChartSeries>,
+    unit: Option<String>,
     margin: f32,
     grid_x_density: usize,
     grid_y_density: usize,
@@ -27,13 +119,11 @@ pub struct LineChart {
 
 #[allow(unused)]
 impl LineChart {
-    pub fn new(data: Vec<LineChartEntry>) -> Self {
-        let mut sorted_data = data;
-        sorted_data.sort_by(|(date_a, _), (date_b, _)| date_a.cmp(date_b));
-
+    pub fn new(series: Vec<LineChartSeries>) -> Self {
         LineChart {
             cache: Cache::new(),
-            data: sorted_data,
+            series,
+            unit: None,
             margin: 50.0,
             grid_x_density: 5,
             grid_y_density: 20,
@@ -44,21 +134,74 @@ impl LineChart {
         }
     }
 
-    fn draw_data_points(&self, frame: &mut Frame, plot_area: Rectangle, stroke: Stroke<'_>) {
-        if self.data.is_empty() {
-            return;
+    /// Unit of values, shown above the value axis and in the hover tooltip.
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    fn plot_area(&self, bounds: Rectangle) -> Rectangle {
+        Rectangle {
+            x: self.margin,
+            y: self.margin,
+            width: bounds.width - 2.0 * self.margin,
+            height: bounds.height - 2.0 * self.margin,
         }
+    }
 
-        let mut min_val = self.data[0].1;
-        let mut max_val = self.data[0].1;
+    fn entries(&self) -> impl Iterator<Item = &LineChartEntry> {
+        self.series.iter().flat_map(|s| s.data.iter())
+    }
 
-        for &(_, val) in self.data.iter() {
-            if min_val > val {
-                min_val = val;
-            }
-            if max_val < val {
-                max_val = val;
-            }
+    /// Days covered by all series.
+    fn data_window(&self) -> Option<DayWindow> {
+        let days = || self.entries().map(|(date, _)| date.num_days_from_ce());
+        let first_day = days().min()?;
+        let last_day = days().max()?;
+
+        if first_day < 0 {
+            tracing::error!("First day in line chart is before year 1970");
+            return None;
+        }
+
+        let mut last_day = last_day as f32;
+        if last_day - first_day as f32 <= f32::EPSILON {
+            tracing::warn!("First and last day in line chart are the same");
+            last_day += 1.0;
+        }
+
+        Some(DayWindow {
+            first_day: first_day as f32,
+            last_day,
+        })
+    }
+
+    fn visible_window(&self, state: &LineChartState) -> Option<DayWindow> {
+        let data_window = self.data_window()?;
+
+        match state.window {
+            Some(window) if state.data_window == Some(data_window) => Some(window),
+            _ => Some(data_window),
+        }
+    }
+
+    /// Bounds of the visible window, values are scaled to the entries that are visible.
+    fn bounds(&self, window: DayWindow) -> ChartBounds {
+        let is_visible = |(date, _): &&LineChartEntry| {
+            let day = date.num_days_from_ce() as f32;
+            day >= window.first_day && day <= window.last_day
+        };
+
+        let mut min_val = f32::MAX;
+        let mut max_val = f32::MIN;
+        for &(_, val) in self.entries().filter(is_visible) {
+            min_val = min_val.min(val);
+            max_val = max_val.max(val);
+        }
+
+        if min_val > max_val {
+            min_val = 0.0;
+            max_val = 1.0;
         }
 
         let mut val_diff = max_val - min_val;
@@ -67,40 +210,65 @@ impl LineChart {
             val_diff = 1.0;
         }
 
-        let first_day = self.data.first().unwrap().0.num_days_from_ce();
-        let last_day = self.data.last().unwrap().0.num_days_from_ce();
-
-        if first_day < 0 {
-            tracing::error!("First day in line chart is before year 1970");
-            return;
+        ChartBounds {
+            first_day: window.first_day,
+            amount_of_days: window.span(),
+            min_val,
+            val_diff,
         }
+    }
 
-        let first_day = first_day as f32;
-        let last_day = last_day as f32;
-
-        let mut amount_of_days = last_day - first_day;
-        if amount_of_days.abs() < f32::EPSILON {
-            tracing::warn!("First and last day in line chart are the same");
-            amount_of_days = 1.0;
-        }
+    fn point(
+        &self,
+        plot_area: Rectangle,
+        bounds: ChartBounds,
+        (date, val): LineChartEntry,
+    ) -> Point {
+        let day = date.num_days_from_ce() as f32;
+        let days_since_first = day - bounds.first_day;
+        let val_from_min = val - bounds.min_val;
+
+        let x = plot_area.x + (days_since_first / bounds.amount_of_days) * plot_area.width;
+        let y =
+            plot_area.y + plot_area.height - (val_from_min / bounds.val_diff) * plot_area.height;
+        Point::new(x, y)
+    }
 
-        let points = self
+    fn draw_data_points(
+        &self,
+        frame: &mut Frame,
+        plot_area: Rectangle,
+        bounds: ChartBounds,
+        series: &LineChartSeries,
+        stroke: Stroke<'_>,
+    ) {
+        // Zoomed chart has lines outside of plot area, clipped frame has its origin in the
+        // corner of the plot area.
+        let clipped_area = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            ..plot_area
+        };
+        let points = series
             .data
             .iter()
-            .enumerate()
-            .map(|(i, &(date, val))| {
-                let day = date.num_days_from_ce() as f32;
-                let days_since_first = day - first_day;
-                let val_from_min = val - min_val;
-
-                let x = plot_area.x + (days_since_first / amount_of_days) * plot_area.width;
-                let y =
-                    plot_area.y + plot_area.height - (val_from_min / val_diff) * plot_area.height;
-                Point::new(x, y)
-            })
+            .map(|&entry| self.point(clipped_area, bounds, entry))
             .collect::<Vec<Point>>();
 
         let mut builder = Builder::new();
+        if series.points {
+            for point in points.iter() {
+                builder.circle(*point, 3.0);
+            }
+            let path = builder.build();
+            let fill = Fill {
+                style: stroke.style,
+                ..Fill::default()
+            };
+            frame.with_clip(plot_area, |frame| frame.fill(&path, fill));
+            return;
+        }
+
         if let Some((first, rest)) = points.split_first() {
             builder.move_to(*first);
             for point in rest {
@@ -109,13 +277,139 @@ impl LineChart {
         }
 
         let path = builder.build();
-        frame.stroke(&path, stroke.with_width(4.0).with_line_cap(LineCap::Round));
+        frame.with_clip(plot_area, |frame| frame.stroke(&path, stroke));
+    }
+
+    /// Draws date and values of the entries nearest to the cursor.
+    fn draw_hover(
+        &self,
+        frame: &mut Frame,
+        plot_area: Rectangle,
+        bounds: ChartBounds,
+        cursor: Point,
+        colors: &[Color],
+        theme: &Theme,
+    ) {
+        let text_color = theme.extended_palette().primary.base.color;
+        let background = theme.palette().background;
+        let cursor_day =
+            bounds.first_day + (cursor.x - plot_area.x) / plot_area.width * bounds.amount_of_days;
+        let Some(&(nearest_date, _)) = self
+            .entries()
+            .filter(|(date, _)| {
+                let day = date.num_days_from_ce() as f32;
+                day >= bounds.first_day && day <= bounds.first_day + bounds.amount_of_days
+            })
+            .min_by(|(a, _), (b, _)| {
+                let a = (a.num_days_from_ce() as f32 - cursor_day).abs();
+                let b = (b.num_days_from_ce() as f32 - cursor_day).abs();
+                a.total_cmp(&b)
+            })
+        else {
+            return;
+        };
+
+        let x = self.point(plot_area, bounds, (nearest_date, 0.0)).x;
+        let mut builder = Builder::new();
+        builder.move_to(Point::new(x, plot_area.y));
+        builder.line_to(Point::new(x, plot_area.y + plot_area.height));
+        frame.stroke(
+            &builder.build(),
+            Stroke::default().with_color(text_color).with_width(1.0),
+        );
+
+        let mut lines = vec![(nearest_date.format("%Y-%m-%d").to_string(), text_color)];
+        for (series, color) in self.series.iter().zip(colors) {
+            if let Some(&(_, val)) = series.data.iter().find(|(date, _)| *date == nearest_date) {
+                let point = self.point(plot_area, bounds, (nearest_date, val));
+                frame.fill(&Path::circle(point, 5.0), *color);
+                let line = match &self.unit {
+                    Some(unit) => format!("{} {val:.1} {unit}", series.name),
+                    None => format!("{} {val:.1}", series.name),
+                };
+                lines.push((line, *color));
+            }
+        }
+
+        let line_height = self.text_size * 1.4;
+        let letter_width = self.text_size / 2.0;
+        let padding = self.label_offset / 2.0;
+        let width = lines
+            .iter()
+            .map(|(line, _)| line.len() as f32 * letter_width)
+            .fold(0.0, f32::max)
+            + 2.0 * padding;
+        let height = lines.len() as f32 * line_height + 2.0 * padding;
+
+        // Tooltip is shown next to the cursor, on the other side when there's no space left.
+        let mut top_left = Point::new(cursor.x + self.label_offset, cursor.y + self.label_offset);
+        if top_left.x + width > plot_area.x + plot_area.width {
+            top_left.x = cursor.x - self.label_offset - width;
+        }
+        if top_left.y + height > plot_area.y + plot_area.height {
+            top_left.y = cursor.y - self.label_offset - height;
+        }
+
+        frame.fill_rectangle(top_left, Size::new(width, height), background);
+        for (i, (line, color)) in lines.into_iter().enumerate() {
+            frame.fill_text(Text {
+                content: line,
+                position: Point::new(
+                    top_left.x + padding,
+                    top_left.y + padding + i as f32 * line_height,
+                ),
+                color,
+                size: Pixels::from(self.text_size),
+                ..Default::default()
+            });
+        }
+    }
+
+    fn draw_legend(
+        &self,
+        frame: &mut Frame,
+        plot_area: Rectangle,
+        strokes: &[Stroke<'_>],
+        text_color: Color,
+    ) {
+        let line_length = 20.0;
+        let letter_width = self.text_size / 2.0;
+        let y = plot_area.y - self.margin / 2.0;
+        let mut x = plot_area.x;
+
+        for (series, stroke) in self.series.iter().zip(strokes) {
+            let mut builder = Builder::new();
+            if series.points {
+                builder.circle(Point::new(x + line_length / 2.0, y), 3.0);
+                let fill = Fill {
+                    style: stroke.style,
+                    ..Fill::default()
+                };
+                frame.fill(&builder.build(), fill);
+            } else {
+                builder.move_to(Point::new(x, y));
+                builder.line_to(Point::new(x + line_length, y));
+                frame.stroke(&builder.build(), *stroke);
+            }
+
+            x += line_length + self.label_offset / 2.0;
+            frame.fill_text(Text {
+                content: series.name.clone(),
+                position: Point::new(x, y - self.text_size / 2.0),
+                color: text_color,
+                size: Pixels::from(self.text_size),
+                ..Default::default()
+            });
+
+            x += series.name.len() as f32 * letter_width + self.label_offset * 2.0;
+        }
     }
 
     fn draw_x_axis(
         &self,
         frame: &mut Frame,
         plot_area: Rectangle,
+        bounds: ChartBounds,
         stroke: Stroke<'_>,
         text_color: Color,
     ) {
@@ -130,13 +424,8 @@ impl LineChart {
         let path = builder.build();
         frame.stroke(&path, stroke.with_width(self.axis_line_width));
 
-        if self.data.is_empty() {
-            return;
-        }
-
-        let first_day = self.data.first().unwrap().0.num_days_from_ce() as f32;
-        let last_day = self.data.last().unwrap().0.num_days_from_ce() as f32;
-        let amount_of_days = (last_day - first_day).max(1.0);
+        let first_day = bounds.first_day;
+        let amount_of_days = bounds.amount_of_days;
 
         let x_spacing = plot_area.width / self.grid_x_density as f32;
 
@@ -169,6 +458,7 @@ impl LineChart {
         &self,
         frame: &mut Frame,
         plot_area: Rectangle,
+        bounds: ChartBounds,
         stroke: Stroke<'_>,
         text_color: Color,
     ) {
@@ -180,27 +470,8 @@ impl LineChart {
         let path = builder.build();
         frame.stroke(&path, stroke.with_width(self.axis_line_width));
 
-        if self.data.is_empty() {
-            return;
-        }
-
-        let mut min_val = self.data[0].1;
-        let mut max_val = self.data[0].1;
-
-        for &(_, val) in self.data.iter() {
-            if min_val > val {
-                min_val = val;
-            }
-            if max_val < val {
-                max_val = val;
-            }
-        }
-
-        let mut val_diff = max_val - min_val;
-        if val_diff.abs() < f32::EPSILON {
-            tracing::warn!("First and last value in line chart are the same");
-            val_diff = 1.0;
-        }
+        let min_val = bounds.min_val;
+        let val_diff = bounds.val_diff;
 
         let y_spacing = plot_area.height / self.grid_x_density as f32;
 
@@ -226,6 +497,20 @@ impl LineChart {
 
             frame.fill_text(text);
         }
+
+        if let Some(unit) = &self.unit {
+            let letter_width = self.text_size / 2.0;
+            frame.fill_text(Text {
+                content: unit.clone(),
+                position: Point::new(
+                    plot_area.x - unit.len() as f32 * letter_width - self.label_offset,
+                    plot_area.y - self.margin / 2.0 - self.text_size / 2.0,
+                ),
+                color: text_color,
+                size: Pixels::from(self.text_size),
+                ..Default::default()
+            });
+        }
     }
 
     fn draw_grid(&self, frame: &mut Frame, plot_area: Rectangle, stroke: Stroke<'_>) {
@@ -261,56 +546,179 @@ impl LineChart {
 }
 
 impl canvas::Program<Message> for LineChart {
-    type State = ();
+    type State = LineChartState;
 
     fn update(
         &self,
-        _state: &mut Self::State,
-        _event: Event,
-        _bounds: Rectangle,
-        _cursor: Cursor,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
     ) -> (Status, Option<Message>) {
-        (Status::Captured, None)
+        let Some(data_window) = self.data_window() else {
+            return (Status::Ignored, None);
+        };
+
+        if state.data_window != Some(data_window) {
+            state.data_window = Some(data_window);
+            state.window = None;
+            state.drag = None;
+        }
+
+        let plot_area = self.plot_area(bounds);
+        let window = state.window.unwrap_or(data_window);
+        // Dragging continues when cursor leaves the chart, so position is not limited to bounds.
+        let position = cursor
+            .position()
+            .map(|p| Point::new(p.x - bounds.x, p.y - bounds.y));
+        let over_plot_area =
+            cursor.is_over(bounds) && position.is_some_and(|p| plot_area.contains(p));
+
+        let Event::Mouse(mouse_event) = event else {
+            return (Status::Ignored, None);
+        };
+
+        match mouse_event {
+            mouse::Event::WheelScrolled { delta } if over_plot_area => {
+                let steps = match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_SCROLL_STEP,
+                };
+                let cursor_ratio = (position.unwrap().x - plot_area.x) / plot_area.width;
+                let cursor_day = window.first_day + cursor_ratio * window.span();
+                let span = (window.span() * ZOOM_STEP.powf(steps))
+                    .max(MIN_VISIBLE_DAYS)
+                    .min(data_window.span());
+
+                let first_day = cursor_day - cursor_ratio * span;
+                state.window = Some(
+                    DayWindow {
+                        first_day,
+                        last_day: first_day + span,
+                    }
+                    .clamp(data_window),
+                );
+                self.cache.clear();
+                (Status::Captured, None)
+            }
+            mouse::Event::ButtonPressed(mouse::Button::Left) if over_plot_area => {
+                state.drag = Some((position.unwrap().x, window));
+                (Status::Captured, None)
+            }
+            mouse::Event::ButtonPressed(mouse::Button::Right) if over_plot_area => {
+                state.window = None;
+                self.cache.clear();
+                (Status::Captured, None)
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) if state.drag.is_some() => {
+                state.drag = None;
+                (Status::Captured, None)
+            }
+            mouse::Event::CursorMoved { .. } => {
+                if let (Some((start_x, start_window)), Some(position)) = (state.drag, position) {
+                    let shift = (start_x - position.x) / plot_area.width * start_window.span();
+                    state.window = Some(
+                        DayWindow {
+                            first_day: start_window.first_day + shift,
+                            last_day: start_window.last_day + shift,
+                        }
+                        .clamp(data_window),
+                    );
+                    self.cache.clear();
+                    return (Status::Captured, None);
+                }
+                (Status::Ignored, None)
+            }
+            _ => (Status::Ignored, None),
+        }
     }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         theme: &Theme,
         bounds: Rectangle,
-        _cursor: Cursor,
+        cursor: Cursor,
     ) -> Vec<Geometry<Renderer>> {
-        if self.data.is_empty() {
+        let Some(window) = self.visible_window(state) else {
             return vec![];
-        }
-
-        let plot_area = Rectangle {
-            x: self.margin,
-            y: self.margin,
-            width: bounds.width - 2.0 * self.margin,
-            height: bounds.height - 2.0 * self.margin,
         };
+        let chart_bounds = self.bounds(window);
+        let plot_area = self.plot_area(bounds);
 
         let text_color = theme.extended_palette().primary.base.color;
-        let data_line_stroke = Stroke::default().with_color(text_color);
         let grid_stroke = Stroke::default();
+        let series_colors: Vec<Color> = self
+            .series
+            .iter()
+            .map(|s| s.color.unwrap_or(text_color))
+            .collect();
+        let series_strokes: Vec<Stroke> = self
+            .series
+            .iter()
+            .zip(series_colors.iter())
+            .map(|(s, color)| {
+                let stroke = Stroke::default()
+                    .with_color(*color)
+                    .with_line_cap(LineCap::Round);
+                if s.dashed {
+                    Stroke {
+                        line_dash: LineDash {
+                            segments: &[8.0, 8.0],
+                            offset: 0,
+                        },
+                        ..stroke.with_width(2.0)
+                    }
+                } else {
+                    stroke.with_width(4.0)
+                }
+            })
+            .collect();
 
         let graph = self.cache.draw(renderer, bounds.size(), |frame| {
             self.draw_grid(frame, plot_area, grid_stroke);
-            self.draw_x_axis(frame, plot_area, grid_stroke, text_color);
-            self.draw_y_axis(frame, plot_area, grid_stroke, text_color);
-            self.draw_data_points(frame, plot_area, data_line_stroke);
+            self.draw_x_axis(frame, plot_area, chart_bounds, grid_stroke, text_color);
+            self.draw_y_axis(frame, plot_area, chart_bounds, grid_stroke, text_color);
+            for (series, stroke) in self.series.iter().zip(series_strokes.iter()) {
+                self.draw_data_points(frame, plot_area, chart_bounds, series, *stroke);
+            }
+            if self.series.len() > 1 {
+                self.draw_legend(frame, plot_area, &series_strokes, text_color);
+            }
         });
-        vec![graph]
+
+        let mut hover = Frame::new(renderer, bounds.size());
+        if let Some(position) = cursor.position_in(bounds) {
+            if state.drag.is_none() && plot_area.contains(position) {
+                self.draw_hover(
+                    &mut hover,
+                    plot_area,
+                    chart_bounds,
+                    position,
+                    &series_colors,
+                    theme,
+                );
+            }
+        }
+
+        vec![graph, hover.into_geometry()]
     }
 
     fn mouse_interaction(
         &self,
-        _state: &Self::State,
-        _bounds: Rectangle,
-        _cursor: Cursor,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: Cursor,
     ) -> Interaction {
-        Interaction::None
+        let over_plot_area = cursor
+            .position_in(bounds)
+            .is_some_and(|p| self.plot_area(bounds).contains(p));
+
+        match state.drag {
+            Some(_) => Interaction::Grabbing,
+            None if over_plot_area => Interaction::Crosshair,
+            None => Interaction::None,
+        }
     }
 }
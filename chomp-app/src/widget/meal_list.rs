@@ -1,14 +1,15 @@
 use std::collections::HashSet;
 
 use chomp_services::{
-    AddMealProduct, Meal, MealDayStats, MealProduct, NutritionTarget, Product, ProductPortion,
+    AddMealProduct, Macros, Meal, MealBudget, MealDayStats, MealPreset, MealProduct,
+    NutritionTarget, Product, ProductPortion, ProductUsage, ServiceError, Units,
     UpdateMealProductWeight,
 };
-use chrono::{Days, NaiveDate};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
 use iced::{
     widget::{
-        button, column, combo_box, container, horizontal_space, progress_bar, row, vertical_space,
-        Button, Container, Scrollable, Text,
+        button, column, container, horizontal_space, progress_bar, row, vertical_space, Button,
+        Container, Scrollable, Text,
     },
     Alignment, Element, Length, Task,
 };
@@ -21,11 +22,17 @@ use super::{
 
 type PortionId = usize;
 
+/// Number of best matching products offered when adding product to a meal.
+const PRODUCT_SEARCH_RESULTS: usize = 8;
+
 #[derive(Debug, Clone)]
 pub enum MealListMessage {
     CreateMealProductFormMeal(Option<usize>),
     CreateMealProductFormWeight(String),
+    CreateMealProductFormSearch(String),
     CreateMealProductFormProduct(usize),
+    CreateMealProductFormBarcode(String),
+    SubmitCreateMealProductFormBarcode,
     SubmitAddMealProductFormWithWeight,
     SubmitAddMealProductFormWithPortion(PortionId),
 
@@ -38,6 +45,22 @@ pub enum MealListMessage {
 
     CopyMealProductsMeal(Option<usize>),
     SubmitCopyMealProductsForm,
+
+    MealNameFormOpen(Option<usize>),
+    MealNameFormClose,
+    MealNameFormName(String),
+    SubmitMealNameForm,
+    MoveMealUp(usize),
+    MoveMealDown(usize),
+    DeleteMeal(usize),
+    SaveMealTemplate,
+
+    SaveMealPresetFormMeal(Option<usize>),
+    SaveMealPresetFormName(String),
+    SubmitSaveMealPresetForm,
+    ApplyMealPresetFormMeal(Option<usize>),
+    SubmitApplyMealPreset(usize),
+    DeleteMealPreset(usize),
 }
 
 impl From<MealListMessage> for Message {
@@ -52,11 +75,16 @@ pub struct MealList {
     meals: Vec<Meal>,
     stats: MealDayStats,
     target: NutritionTarget,
+    budgets: Vec<MealBudget>,
     product_portions: Vec<ProductPortion>,
+    units: Units,
 
     add_meal_product_form: Option<MealProductForm>,
     update_meal_product_form: Option<UpdateMealProductForm>,
     copy_meal_products_form: Option<CopyMealProductsForm>,
+    meal_name_form: Option<MealNameForm>,
+    save_meal_preset_form: Option<SaveMealPresetForm>,
+    apply_meal_preset_form: Option<ApplyMealPresetForm>,
 }
 
 impl MealList {
@@ -66,20 +94,33 @@ impl MealList {
         stats: MealDayStats,
         target: NutritionTarget,
         product_portions: Vec<ProductPortion>,
+        units: Units,
+        week_start: Weekday,
     ) -> Self {
         assert!(!meals.is_empty());
         MealList {
-            day: DatePicker::new_with_value("Date", &day),
+            day: DatePicker::new_with_value("Date", &day, week_start),
             meals,
             stats,
             target,
+            budgets: Vec::new(),
             product_portions,
+            units,
             add_meal_product_form: None,
             update_meal_product_form: None,
             copy_meal_products_form: None,
+            meal_name_form: None,
+            save_meal_preset_form: None,
+            apply_meal_preset_form: None,
         }
     }
 
+    /// Shares of the target planned for each meal.
+    pub fn with_budgets(mut self, budgets: Vec<MealBudget>) -> Self {
+        self.budgets = budgets;
+        self
+    }
+
     fn refresh(&mut self, ctx: &Context) {
         self.meals = match ctx.services.meal.list_or_create_default(self.day.value()) {
             Ok(m) => m,
@@ -95,26 +136,75 @@ impl MealList {
                 std::process::exit(1);
             }
         };
+        // Days before the first target show the last one, like the day the list was opened with.
+        let target = match ctx
+            .services
+            .nutrition_target
+            .read_in_effect(self.day.value())
+        {
+            Ok(Some(t)) => Ok(t),
+            Ok(None) => ctx.services.nutrition_target.read_last_or_create_default(),
+            Err(err) => Err(err),
+        };
+        self.target = match target {
+            Ok(t) => t,
+            Err(err) => {
+                tracing::error!("Failed to get nutrition target: {}", err);
+                std::process::exit(1);
+            }
+        };
+        self.budgets = match ctx
+            .services
+            .nutrition_target
+            .list_meal_budgets(self.target.day)
+        {
+            Ok(b) => b,
+            Err(err) => {
+                tracing::error!("Failed to get meal budgets: {}", err);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    /// Part of the day target planned for the meal, if the target splits the day between meals.
+    fn budget(&self, meal: &Meal) -> Option<Macros> {
+        self.budgets
+            .iter()
+            .find(|b| b.meal.eq_ignore_ascii_case(&meal.name))
+            .map(|b| self.target.portion(b.share))
     }
 }
 
 impl Widget for MealList {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let mut tables = column![].spacing(20);
-        for meal in self.meals.iter() {
-            tables = tables.push(render_meal(meal))
+        for (i, meal) in self.meals.iter().enumerate() {
+            tables = tables.push(render_meal(
+                meal,
+                i,
+                self.meals.len(),
+                self.budget(meal),
+                self.units,
+            ))
         }
 
         let content = column![
             row![
                 Text::new("Meals").size(40),
                 horizontal_space(),
+                Button::new("Add Meal").on_press(MealListMessage::MealNameFormOpen(None).into()),
+                Button::new(Text::new(format!(
+                    "Use Meals For Every {}",
+                    self.day.value().format("%A")
+                )))
+                .on_press(MealListMessage::SaveMealTemplate.into()),
                 self.day.view(),
             ]
+            .spacing(10)
             .align_y(Alignment::Center),
             Scrollable::new(tables),
             vertical_space(),
-            meal_stats(&self.stats, &self.target)
+            meal_stats(&self.stats, &self.target, self.units)
         ]
         .spacing(10);
 
@@ -132,6 +222,33 @@ impl Widget for MealList {
             );
         }
 
+        if let Some(name_form) = &self.meal_name_form {
+            return modal(
+                content_with_sidebar.into(),
+                render_meal_name_form(name_form),
+                MealListMessage::MealNameFormClose.into(),
+                true,
+            );
+        }
+
+        if let Some(save_preset_form) = &self.save_meal_preset_form {
+            return modal(
+                content_with_sidebar.into(),
+                render_save_meal_preset_form(save_preset_form),
+                MealListMessage::SaveMealPresetFormMeal(None).into(),
+                true,
+            );
+        }
+
+        if let Some(apply_preset_form) = &self.apply_meal_preset_form {
+            return modal(
+                content_with_sidebar.into(),
+                render_apply_meal_preset_form(apply_preset_form, self.units),
+                MealListMessage::ApplyMealPresetFormMeal(None).into(),
+                true,
+            );
+        }
+
         if let Some(update_form) = &self.update_meal_product_form {
             return modal(
                 content_with_sidebar.into(),
@@ -171,6 +288,12 @@ impl Widget for MealList {
         }
 
         match msg {
+            // Open forms may refer to meal products that undo or redo removed.
+            Message::Undo | Message::Redo => {
+                self.add_meal_product_form = None;
+                self.update_meal_product_form = None;
+                self.refresh(ctx);
+            }
             Message::DatePickerDateChange(new_day) => {
                 if let Some(form) = self.copy_meal_products_form.as_mut() {
                     let new_products = ctx
@@ -183,6 +306,9 @@ impl Widget for MealList {
                     form.meal_products = new_products;
                 } else if self.add_meal_product_form.is_none()
                     && self.update_meal_product_form.is_none()
+                    && self.meal_name_form.is_none()
+                    && self.save_meal_preset_form.is_none()
+                    && self.apply_meal_preset_form.is_none()
                 {
                     self.refresh(ctx);
                 };
@@ -197,8 +323,21 @@ impl Widget for MealList {
                                 std::process::exit(1);
                             }
                         };
-                        let products = ctx.services.product.list().unwrap_or_default();
-                        self.add_meal_product_form = Some(MealProductForm::new(products, &meal));
+                        let usage =
+                            match ctx
+                                .services
+                                .meal
+                                .product_usage(meal.day, Some(&meal.name), None)
+                            {
+                                Ok(u) => u,
+                                Err(err) => {
+                                    tracing::error!("Failed to get product usage: {}", err);
+                                    std::process::exit(1);
+                                }
+                            };
+                        let mut form = MealProductForm::new(&meal, usage, self.units);
+                        form.search(ctx);
+                        self.add_meal_product_form = Some(form);
                     }
                     None => {
                         self.add_meal_product_form = None;
@@ -209,18 +348,47 @@ impl Widget for MealList {
                     let form = self.add_meal_product_form.as_mut().unwrap();
                     form.weight.raw_input = raw_weight;
                 }
+                MealListMessage::CreateMealProductFormSearch(raw_search) => {
+                    let form = self.add_meal_product_form.as_mut().unwrap();
+                    form.search.raw_input = raw_search;
+                    form.search(ctx);
+                }
                 MealListMessage::CreateMealProductFormProduct(product_id) => {
                     let form = self.add_meal_product_form.as_mut().unwrap();
-
-                    let portions = self
-                        .product_portions
-                        .iter()
-                        .filter(|p| p.product_id == product_id)
-                        .cloned()
-                        .collect();
-
-                    form.product_id = Some(product_id);
-                    form.available_product_portions = portions;
+                    if let Some(product) = form.results.iter().find(|p| p.id == product_id) {
+                        form.select_product(product.clone(), &self.product_portions);
+                    }
+                }
+                MealListMessage::CreateMealProductFormBarcode(raw_barcode) => {
+                    let form = self.add_meal_product_form.as_mut().unwrap();
+                    form.barcode.raw_input = raw_barcode;
+                }
+                MealListMessage::SubmitCreateMealProductFormBarcode => {
+                    let form = self.add_meal_product_form.as_mut().unwrap();
+                    let barcode = form.barcode.raw_input.trim().to_string();
+
+                    match ctx.services.product.read_by_barcode(&barcode) {
+                        Ok(product) => {
+                            if !form.products_in_meal.contains(&product.id) {
+                                form.barcode.error = None;
+                                form.select_product(product, &self.product_portions);
+                            } else {
+                                form.barcode.error = Some(InputFormFieldError::Custom(format!(
+                                    "{} is already in this meal",
+                                    product.name
+                                )));
+                            }
+                        }
+                        Err(ServiceError::NoRows) => {
+                            form.barcode.error = Some(InputFormFieldError::Custom(
+                                "No product with this barcode".to_string(),
+                            ));
+                        }
+                        Err(err) => {
+                            tracing::error!("Failed to find product by barcode: {}", err);
+                            std::process::exit(1);
+                        }
+                    }
                 }
                 MealListMessage::SubmitAddMealProductFormWithWeight => {
                     match self.add_meal_product_form.as_mut().unwrap().parse() {
@@ -247,7 +415,7 @@ impl Widget for MealList {
 
                     let add_meal_product = AddMealProduct {
                         meal_id: form.meal.id,
-                        product_id: form.product_id.unwrap(),
+                        product_id: form.product.as_ref().unwrap().id,
                         weight: portion.weight,
                     };
 
@@ -276,7 +444,7 @@ impl Widget for MealList {
                                 .cloned()
                                 .collect();
 
-                            let mut form = UpdateMealProductForm::new(&meal_product);
+                            let mut form = UpdateMealProductForm::new(&meal_product, self.units);
                             form.available_product_portions = portions;
                             self.update_meal_product_form = Some(form);
                         }
@@ -351,8 +519,12 @@ impl Widget for MealList {
 
                         let meal_products = prev_day_meal.map(|m| m.products).unwrap_or_default();
 
-                        self.copy_meal_products_form =
-                            Some(CopyMealProductsForm::new(&meal_products, &prev_day, &meal));
+                        self.copy_meal_products_form = Some(CopyMealProductsForm::new(
+                            &meal_products,
+                            &prev_day,
+                            &meal,
+                            self.day.week_start(),
+                        ));
                     }
                     None => self.copy_meal_products_form = None,
                 },
@@ -379,8 +551,145 @@ impl Widget for MealList {
                         }
                     }
                 }
+                MealListMessage::MealNameFormOpen(meal_id) => {
+                    let meal = meal_id.and_then(|id| self.meals.iter().find(|m| m.id == id));
+                    self.meal_name_form = Some(MealNameForm::new(meal));
+                }
+                MealListMessage::MealNameFormClose => {
+                    self.meal_name_form = None;
+                }
+                MealListMessage::MealNameFormName(raw_name) => {
+                    let form = self.meal_name_form.as_mut().unwrap();
+                    form.name.raw_input = raw_name;
+                }
+                MealListMessage::SubmitMealNameForm => {
+                    let form = self.meal_name_form.as_mut().unwrap();
+                    if let Ok(name) = form.parse() {
+                        let result = match form.meal_id {
+                            Some(id) => ctx.services.meal.rename(id, &name),
+                            None => ctx.services.meal.add(self.day.value(), &name),
+                        };
+
+                        match result {
+                            Ok(_) => {
+                                self.meal_name_form = None;
+                                self.refresh(ctx);
+                            }
+                            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                                form.name.error = Some(InputFormFieldError::Custom(
+                                    "Meal with this name already exists on this day".to_string(),
+                                ));
+                            }
+                            Err(err) => {
+                                tracing::error!("Failed to save meal: {}", err);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
+                MealListMessage::MoveMealUp(meal_id) => {
+                    if let Err(err) = ctx.services.meal.move_up(meal_id) {
+                        tracing::error!("Failed to move meal: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+                MealListMessage::MoveMealDown(meal_id) => {
+                    if let Err(err) = ctx.services.meal.move_down(meal_id) {
+                        tracing::error!("Failed to move meal: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+                MealListMessage::DeleteMeal(meal_id) => {
+                    if let Err(err) = ctx.services.meal.delete(meal_id) {
+                        tracing::error!("Failed to delete meal: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+                MealListMessage::SaveMealTemplate => {
+                    let names: Vec<String> = self.meals.iter().map(|m| m.name.clone()).collect();
+                    if let Err(err) = ctx
+                        .services
+                        .meal_template
+                        .replace(self.day.value().weekday(), &names)
+                    {
+                        tracing::error!("Failed to save meal template: {}", err);
+                        std::process::exit(1);
+                    }
+                }
+                MealListMessage::SaveMealPresetFormMeal(meal_id) => {
+                    self.save_meal_preset_form = meal_id
+                        .and_then(|id| self.meals.iter().find(|m| m.id == id))
+                        .map(SaveMealPresetForm::new);
+                }
+                MealListMessage::SaveMealPresetFormName(raw_name) => {
+                    let form = self.save_meal_preset_form.as_mut().unwrap();
+                    form.name.raw_input = raw_name;
+                }
+                MealListMessage::SubmitSaveMealPresetForm => {
+                    let form = self.save_meal_preset_form.as_mut().unwrap();
+                    if let Ok(name) = form.parse() {
+                        match ctx
+                            .services
+                            .meal_preset
+                            .create_from_meal(&name, form.meal.id)
+                        {
+                            Ok(_) => self.save_meal_preset_form = None,
+                            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                                form.name.error = Some(InputFormFieldError::Custom(
+                                    "Preset with this name already exists".to_string(),
+                                ));
+                            }
+                            Err(err) => {
+                                tracing::error!("Failed to save meal preset: {}", err);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
+                MealListMessage::ApplyMealPresetFormMeal(meal_id) => {
+                    self.apply_meal_preset_form =
+                        match meal_id.and_then(|id| self.meals.iter().find(|m| m.id == id)) {
+                            Some(meal) => {
+                                let presets = match ctx.services.meal_preset.list() {
+                                    Ok(p) => p,
+                                    Err(err) => {
+                                        tracing::error!("Failed to get meal presets: {}", err);
+                                        std::process::exit(1);
+                                    }
+                                };
+                                Some(ApplyMealPresetForm {
+                                    meal: meal.to_owned(),
+                                    presets,
+                                })
+                            }
+                            None => None,
+                        };
+                }
+                MealListMessage::SubmitApplyMealPreset(preset_id) => {
+                    let form = self.apply_meal_preset_form.as_ref().unwrap();
+                    if let Err(err) = ctx.services.meal_preset.apply(preset_id, form.meal.id) {
+                        tracing::error!("Failed to apply meal preset: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                    self.apply_meal_preset_form = None;
+                }
+                MealListMessage::DeleteMealPreset(preset_id) => {
+                    let form = self.apply_meal_preset_form.as_mut().unwrap();
+                    if let Err(err) = ctx.services.meal_preset.delete(preset_id) {
+                        tracing::error!("Failed to delete meal preset: {}", err);
+                        std::process::exit(1);
+                    }
+                    form.presets.retain(|p| p.id != preset_id);
+                }
             },
             Message::EscapeClicked => {
+                self.save_meal_preset_form = None;
+                self.apply_meal_preset_form = None;
+                self.meal_name_form = None;
                 self.update_meal_product_form = None;
                 self.copy_meal_products_form = None;
                 self.add_meal_product_form = None;
@@ -392,17 +701,41 @@ impl Widget for MealList {
     }
 }
 
-fn render_meal(meal: &Meal) -> Element<Message> {
+fn render_meal(
+    meal: &Meal,
+    index: usize,
+    meal_count: usize,
+    budget: Option<Macros>,
+    units: Units,
+) -> Element<'_, Message> {
     let mut table = column![
         row![
             Text::new(&meal.name).size(20),
             Button::new("Add Product")
                 .on_press(MealListMessage::CreateMealProductFormMeal(Some(meal.id)).into()),
             Button::new("Copy From Different Day")
-                .on_press(MealListMessage::CopyMealProductsMeal(Some(meal.id)).into())
+                .on_press(MealListMessage::CopyMealProductsMeal(Some(meal.id)).into()),
+            Button::new("Apply Preset")
+                .on_press(MealListMessage::ApplyMealPresetFormMeal(Some(meal.id)).into()),
+            Button::new("Save As Preset").on_press_maybe(
+                (!meal.products.is_empty())
+                    .then_some(MealListMessage::SaveMealPresetFormMeal(Some(meal.id)).into())
+            ),
+            horizontal_space(),
+            Button::new("Up")
+                .on_press_maybe((index > 0).then_some(MealListMessage::MoveMealUp(meal.id).into())),
+            Button::new("Down").on_press_maybe(
+                (index + 1 < meal_count).then_some(MealListMessage::MoveMealDown(meal.id).into())
+            ),
+            Button::new("Rename").on_press(MealListMessage::MealNameFormOpen(Some(meal.id)).into()),
+            Button::new("Delete Meal")
+                .style(button::danger)
+                .on_press_maybe(
+                    (meal_count > 1).then_some(MealListMessage::DeleteMeal(meal.id).into())
+                ),
         ]
         .spacing(10),
-        list_header_row()
+        list_header_row(units)
     ];
 
     let mut calories_sum = 0f32;
@@ -416,26 +749,57 @@ fn render_meal(meal: &Meal) -> Element<Message> {
         proteins_sum += meal_product.proteins;
         carbohydrates_sum += meal_product.carbohydrates;
 
-        table = table.push(list_row(meal_product, i % 2 == 0));
+        table = table.push(list_row(meal_product, units, i % 2 == 0));
     }
 
     if !meal.products.is_empty() {
         table = table.push(list_footer(
-            calories_sum,
+            units.energy.from_canonical(calories_sum),
             fats_sum,
             proteins_sum,
             carbohydrates_sum,
         ));
     }
 
+    if let Some(budget) = budget {
+        table = table.push(meal_budget(
+            &budget,
+            &Macros {
+                calories: calories_sum,
+                fats: fats_sum,
+                proteins: proteins_sum,
+                carbohydrates: carbohydrates_sum,
+            },
+            units,
+        ));
+    }
+
     table.into()
 }
 
-fn list_header_row() -> Element<'static, Message> {
+fn meal_budget(budget: &Macros, eaten: &Macros, units: Units) -> Element<'static, Message> {
+    let energy = units.energy;
+
+    Text::new(format!(
+        "Budget {:.0}{energy}, {:.1}g fats, {:.1}g proteins, {:.1}g carbohydrates \
+        (remaining {:.0}{energy}, {:.1}g fats, {:.1}g proteins, {:.1}g carbohydrates)",
+        energy.from_canonical(budget.calories),
+        budget.fats,
+        budget.proteins,
+        budget.carbohydrates,
+        energy.from_canonical(budget.calories - eaten.calories),
+        budget.fats - eaten.fats,
+        budget.proteins - eaten.proteins,
+        budget.carbohydrates - eaten.carbohydrates,
+    ))
+    .into()
+}
+
+fn list_header_row(units: Units) -> Element<'static, Message> {
     let row = row![
         Text::new("Name").width(Length::Fill),
-        Text::new("Weight (g)").width(Length::Fill),
-        Text::new("Calories (kcal)").width(Length::Fill),
+        Text::new(format!("Weight ({})", units.food_weight)).width(Length::Fill),
+        Text::new(format!("Calories ({})", units.energy)).width(Length::Fill),
         Text::new("Fats (g)").width(Length::Fill),
         Text::new("Proteins (g)").width(Length::Fill),
         Text::new("Carbohydrates (g)").width(Length::Fill),
@@ -447,11 +811,15 @@ fn list_header_row() -> Element<'static, Message> {
     Container::new(row).width(Length::Fill).into()
 }
 
-fn list_row(mp: &MealProduct, even: bool) -> Element<Message> {
+fn list_row(mp: &MealProduct, units: Units, even: bool) -> Element<'_, Message> {
     let row = row![
         Text::new(&mp.name).width(Length::Fill),
-        Text::new(format!("{:.1}", mp.weight)).width(Length::Fill),
-        Text::new(format!("{:.1}", mp.calories)).width(Length::Fill),
+        Text::new(format!(
+            "{:.1}",
+            units.food_weight.from_canonical(mp.weight)
+        ))
+        .width(Length::Fill),
+        Text::new(format!("{:.1}", units.energy.from_canonical(mp.calories))).width(Length::Fill),
         Text::new(format!("{:.1}", mp.fats)).width(Length::Fill),
         Text::new(format!("{:.1}", mp.proteins)).width(Length::Fill),
         Text::new(format!("{:.1}", mp.carbohydrates)).width(Length::Fill),
@@ -504,19 +872,50 @@ fn list_footer(
         .into()
 }
 
-pub fn meal_stats(stats: &MealDayStats, target: &NutritionTarget) -> Element<'static, Message> {
-    row![
-        meal_stat("Calories", stats.calories, target.calories),
-        meal_stat("Proteins", stats.proteins, target.proteins),
-        meal_stat("Fats", stats.fats, target.fats),
-        meal_stat("Carbohydrates", stats.carbohydrates, target.carbohydrates)
+pub fn meal_stats(
+    stats: &MealDayStats,
+    target: &NutritionTarget,
+    units: Units,
+) -> Element<'static, Message> {
+    let mut extended_stats = row![
+        Text::new(format!("Fiber {:.1}g", stats.fiber)),
+        Text::new(format!("Sugar {:.1}g", stats.sugar)),
+        Text::new(format!("Saturated fats {:.1}g", stats.saturated_fats)),
+        Text::new(format!(
+            "Salt {:.2}g (sodium {:.2}g)",
+            stats.salt,
+            stats.sodium()
+        )),
+    ]
+    .spacing(20);
+
+    for nutrient in stats.nutrients.iter() {
+        extended_stats = extended_stats.push(Text::new(format!(
+            "{} {:.1}{}",
+            nutrient.name, nutrient.amount, nutrient.unit
+        )));
+    }
+
+    column![
+        row![
+            meal_stat(
+                "Calories",
+                units.energy.from_canonical(stats.calories),
+                units.energy.from_canonical(target.calories)
+            ),
+            meal_stat("Proteins", stats.proteins, target.proteins),
+            meal_stat("Fats", stats.fats, target.fats),
+            meal_stat("Carbohydrates", stats.carbohydrates, target.carbohydrates)
+        ]
+        .spacing(40)
+        .width(Length::Fill),
+        extended_stats
     ]
-    .spacing(40)
-    .width(Length::Fill)
+    .spacing(10)
     .into()
 }
 
-fn meal_stat(label: &str, value: f32, max_value: f32) -> Element<Message> {
+fn meal_stat(label: &str, value: f32, max_value: f32) -> Element<'_, Message> {
     column![
         Text::new(format!("{label} {value:.1}/{max_value:.1}")),
         progress_bar(0.0..=100.0, value / max_value * 100.0),
@@ -526,37 +925,88 @@ fn meal_stat(label: &str, value: f32, max_value: f32) -> Element<Message> {
     .into()
 }
 
+fn portion_label(portion: &ProductPortion, units: Units) -> String {
+    format!(
+        "{} ({:.1}{})",
+        portion.name,
+        units.food_weight.from_canonical(portion.weight),
+        units.food_weight
+    )
+}
+
 #[derive(Debug)]
 pub struct MealProductForm {
-    pub combo_box_state: combo_box::State<Product>,
-    pub combo_box_error: Option<InputFormFieldError>,
+    pub search: InputFormField<String>,
+    pub results: Vec<Product>,
+    pub usage: Vec<ProductUsage>,
+    pub products_in_meal: HashSet<usize>,
+    pub barcode: InputFormField<String>,
     pub weight: InputFormField<f32>,
     pub meal: Meal,
-    pub product_id: Option<usize>,
+    pub product: Option<Product>,
     pub available_product_portions: Vec<ProductPortion>,
+    pub units: Units,
 }
 
 impl MealProductForm {
-    pub fn new(products: Vec<Product>, meal: &Meal) -> Self {
-        let meal_product_names: HashSet<String> =
-            meal.products.iter().map(|mp| mp.name.clone()).collect();
+    pub fn new(meal: &Meal, usage: Vec<ProductUsage>, units: Units) -> Self {
+        MealProductForm {
+            search: InputFormField::new("Product", "Search product..."),
+            results: Vec::new(),
+            usage,
+            products_in_meal: meal.products.iter().map(|mp| mp.product_id).collect(),
+            barcode: InputFormField::new("Barcode", "Scan or type barcode and press enter"),
+            weight: InputFormField::new(&format!("Weight ({})", units.food_weight), "20.0"),
+            meal: meal.to_owned(),
+            product: None,
+            available_product_portions: Vec::new(),
+            units,
+        }
+    }
 
-        let mut products_not_in_meal: Vec<Product> = products
-            .iter()
-            .filter(|p| !meal_product_names.contains(&p.name))
-            .cloned()
+    /// Offers best matches of the search that are not in the meal yet. Without search the most
+    /// used products go first.
+    fn search(&mut self, ctx: &Context) {
+        let mut products = match ctx.services.product.search(&self.search.raw_input) {
+            Ok(p) => p,
+            Err(err) => {
+                tracing::error!("Failed to search products: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+        if self.search.raw_input.trim().is_empty() {
+            let used: Vec<Product> = self.usage.iter().map(|u| u.product.clone()).collect();
+            products.retain(|p| !used.iter().any(|u| u.id == p.id));
+            products.splice(0..0, used);
+        }
+
+        self.results = products
+            .into_iter()
+            .filter(|p| !self.products_in_meal.contains(&p.id))
+            .take(PRODUCT_SEARCH_RESULTS)
             .collect();
+    }
 
-        products_not_in_meal.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    fn last_used(&self, product_id: usize) -> Option<&ProductUsage> {
+        self.usage.iter().find(|u| u.product.id == product_id)
+    }
 
-        MealProductForm {
-            combo_box_state: combo_box::State::new(products_not_in_meal),
-            combo_box_error: None,
-            weight: InputFormField::new("Weight (g)", "20.0"),
-            meal: meal.to_owned(),
-            product_id: None,
-            available_product_portions: Vec::new(),
+    /// Selects product and fills in the weight it was last eaten in.
+    fn select_product(&mut self, product: Product, product_portions: &[ProductPortion]) {
+        self.search.error = None;
+        if let Some(usage) = self.last_used(product.id) {
+            self.weight.raw_input = format!(
+                "{:.1}",
+                self.units.food_weight.from_canonical(usage.last_weight)
+            );
         }
+        self.available_product_portions = product_portions
+            .iter()
+            .filter(|p| p.product_id == product.id)
+            .cloned()
+            .collect();
+        self.product = Some(product);
     }
 
     pub fn parse(&mut self) -> Result<AddMealProduct, String> {
@@ -572,40 +1022,69 @@ impl MealProductForm {
             }
         });
 
-        if self.product_id.is_none() {
-            self.combo_box_error = Some(InputFormFieldError::MissingRequiredValue);
+        if self.product.is_none() {
+            self.search.error = Some(InputFormFieldError::MissingRequiredValue);
         }
 
         Ok(AddMealProduct {
             meal_id: self.meal.id,
-            product_id: self.product_id.ok_or("validation failed")?,
-            weight: self.weight.value.ok_or("validation failed")?,
+            product_id: self
+                .product
+                .as_ref()
+                .map(|p| p.id)
+                .ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
-fn render_add_product_to_meal_form(form: &MealProductForm) -> Element<Message> {
-    let selected_product = match &form.product_id {
-        Some(id) => form.combo_box_state.options().iter().find(|p| p.id == *id),
-        None => None,
-    };
+fn render_add_product_to_meal_form(form: &MealProductForm) -> Element<'_, Message> {
+    let mut results = column![].spacing(2);
+    for product in &form.results {
+        let selected = form.product.as_ref().is_some_and(|p| p.id == product.id);
+        let label = match form.last_used(product.id) {
+            Some(usage) => format!(
+                "{product} (last {:.0}{})",
+                form.units.food_weight.from_canonical(usage.last_weight),
+                form.units.food_weight
+            ),
+            None => product.to_string(),
+        };
+        results = results.push(
+            Button::new(Text::new(label))
+                .width(Length::Fill)
+                .style(if selected {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press(MealListMessage::CreateMealProductFormProduct(product.id).into()),
+        );
+    }
 
-    let combo_box = combo_box(
-        &form.combo_box_state,
-        "Search product...",
-        selected_product,
-        |p| MealListMessage::CreateMealProductFormProduct(p.id).into(),
-    )
-    .width(Length::Fill);
+    let selected_product = match &form.product {
+        Some(product) => Text::new(format!("Selected: {product}")),
+        None => Text::new("No product selected"),
+    };
 
     let mut column = column![
         Text::new(format!("Add product to {}", form.meal.name)).size(30),
-        combo_box,
+        form.search
+            .view(|s| MealListMessage::CreateMealProductFormSearch(s).into()),
+        results,
+        selected_product,
+        form.barcode.view_with_submit(
+            |b| MealListMessage::CreateMealProductFormBarcode(b).into(),
+            MealListMessage::SubmitCreateMealProductFormBarcode.into()
+        ),
     ]
     .spacing(10);
 
     for portion in &form.available_product_portions {
-        let button = Button::new(Text::new(format!("{} ({}g)", portion.name, portion.weight)))
+        let button = Button::new(Text::new(portion_label(portion, form.units)))
             .width(Length::Fill)
             .on_press(MealListMessage::SubmitAddMealProductFormWithPortion(portion.id).into());
 
@@ -644,18 +1123,22 @@ pub struct UpdateMealProductForm {
     pub meal_product: MealProduct,
     pub weight: InputFormField<f32>,
     pub available_product_portions: Vec<ProductPortion>,
+    pub units: Units,
 }
 
 impl UpdateMealProductForm {
-    pub fn new(meal_product: &MealProduct) -> Self {
+    pub fn new(meal_product: &MealProduct, units: Units) -> Self {
+        let weight = units.food_weight.from_canonical(meal_product.weight);
+
         UpdateMealProductForm {
             meal_product: meal_product.clone(),
             weight: InputFormField::new_with_raw_value(
-                "Weight (g)",
+                &format!("Weight ({})", units.food_weight),
                 "20.0",
-                &meal_product.weight.to_string(),
+                &format!("{weight:.1}"),
             ),
             available_product_portions: Vec::new(),
+            units,
         }
     }
 
@@ -674,18 +1157,21 @@ impl UpdateMealProductForm {
 
         Ok(UpdateMealProductWeight {
             meal_product_id: self.meal_product.id,
-            weight: self.weight.value.ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
-fn render_update_meal_product_form(form: &UpdateMealProductForm) -> Element<Message> {
+fn render_update_meal_product_form(form: &UpdateMealProductForm) -> Element<'_, Message> {
     let mut column =
         column![Text::new(format!("Edit weight of {}", form.meal_product.name)).size(30),]
             .spacing(10);
 
     for portion in &form.available_product_portions {
-        let button = Button::new(Text::new(format!("{} ({}g)", portion.name, portion.weight)))
+        let button = Button::new(Text::new(portion_label(portion, form.units)))
             .width(Length::Fill)
             .on_press(MealListMessage::SubmitUpdateMealProductFormWithPortion(portion.id).into());
 
@@ -727,11 +1213,16 @@ pub struct CopyMealProductsForm {
 }
 
 impl CopyMealProductsForm {
-    pub fn new(meal_products: &Vec<MealProduct>, from_day: &NaiveDate, to_meal: &Meal) -> Self {
+    pub fn new(
+        meal_products: &Vec<MealProduct>,
+        from_day: &NaiveDate,
+        to_meal: &Meal,
+        week_start: Weekday,
+    ) -> Self {
         CopyMealProductsForm {
             target_meal: to_meal.to_owned(),
             meal_products: meal_products.to_owned(),
-            from_day: DatePicker::new_with_value("From date", from_day),
+            from_day: DatePicker::new_with_value("From date", from_day, week_start),
         }
     }
 
@@ -748,7 +1239,7 @@ impl CopyMealProductsForm {
     }
 }
 
-fn render_copy_meal_products_form(form: &CopyMealProductsForm) -> Element<Message> {
+fn render_copy_meal_products_form(form: &CopyMealProductsForm) -> Element<'_, Message> {
     container(
         column![
             Text::new(format!(
@@ -772,3 +1263,187 @@ fn render_copy_meal_products_form(form: &CopyMealProductsForm) -> Element<Messag
     .style(container::rounded_box)
     .into()
 }
+
+#[derive(Debug)]
+pub struct MealNameForm {
+    pub meal_id: Option<usize>,
+    pub name: InputFormField<String>,
+}
+
+impl MealNameForm {
+    pub fn new(meal: Option<&Meal>) -> Self {
+        MealNameForm {
+            meal_id: meal.map(|m| m.id),
+            name: InputFormField::new_with_raw_value(
+                "Name",
+                "Pre-workout",
+                meal.map(|m| m.name.as_str()).unwrap_or_default(),
+            ),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<String, String> {
+        self.name.validate(|input| {
+            let name = input.trim();
+            if name.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                Ok(name.to_string())
+            }
+        });
+
+        self.name
+            .value
+            .clone()
+            .ok_or("validation failed".to_string())
+    }
+}
+
+fn render_meal_name_form(form: &MealNameForm) -> Element<'_, Message> {
+    let (title, submit) = match form.meal_id {
+        Some(_) => ("Rename meal", "Rename Meal"),
+        None => ("Add meal", "Add Meal"),
+    };
+
+    container(
+        column![
+            Text::new(title).size(30),
+            form.name.view_with_submit(
+                |n| MealListMessage::MealNameFormName(n).into(),
+                MealListMessage::SubmitMealNameForm.into()
+            ),
+            Button::new(submit)
+                .width(Length::Fill)
+                .on_press(MealListMessage::SubmitMealNameForm.into()),
+            Button::new("Cancel")
+                .width(Length::Fill)
+                .on_press(MealListMessage::MealNameFormClose.into())
+        ]
+        .spacing(10),
+    )
+    .width(300)
+    .padding(30)
+    .style(container::rounded_box)
+    .into()
+}
+
+#[derive(Debug)]
+pub struct SaveMealPresetForm {
+    pub meal: Meal,
+    pub name: InputFormField<String>,
+}
+
+impl SaveMealPresetForm {
+    pub fn new(meal: &Meal) -> Self {
+        SaveMealPresetForm {
+            meal: meal.to_owned(),
+            name: InputFormField::new("Name", "Porridge with berries"),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<String, String> {
+        self.name.validate(|input| {
+            let name = input.trim();
+            if name.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                Ok(name.to_string())
+            }
+        });
+
+        self.name
+            .value
+            .clone()
+            .ok_or("validation failed".to_string())
+    }
+}
+
+fn render_save_meal_preset_form(form: &SaveMealPresetForm) -> Element<'_, Message> {
+    container(
+        column![
+            Text::new(format!(
+                "Save {} products of {} as preset",
+                form.meal.products.len(),
+                form.meal.name
+            ))
+            .size(30),
+            form.name.view_with_submit(
+                |n| MealListMessage::SaveMealPresetFormName(n).into(),
+                MealListMessage::SubmitSaveMealPresetForm.into()
+            ),
+            Button::new("Save Preset")
+                .width(Length::Fill)
+                .on_press(MealListMessage::SubmitSaveMealPresetForm.into()),
+            Button::new("Cancel")
+                .width(Length::Fill)
+                .on_press(MealListMessage::SaveMealPresetFormMeal(None).into())
+        ]
+        .spacing(10),
+    )
+    .width(300)
+    .padding(30)
+    .style(container::rounded_box)
+    .into()
+}
+
+#[derive(Debug)]
+pub struct ApplyMealPresetForm {
+    pub meal: Meal,
+    pub presets: Vec<MealPreset>,
+}
+
+fn render_apply_meal_preset_form(form: &ApplyMealPresetForm, units: Units) -> Element<'_, Message> {
+    let mut presets = column![].spacing(10);
+    for preset in form.presets.iter() {
+        let products = preset
+            .products
+            .iter()
+            .map(|p| {
+                format!(
+                    "{} ({:.1}{})",
+                    p.name,
+                    units.food_weight.from_canonical(p.weight),
+                    units.food_weight
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        presets = presets.push(
+            row![
+                Button::new(column![
+                    Text::new(&preset.name),
+                    Text::new(products).size(12)
+                ])
+                .width(Length::Fill)
+                .on_press(MealListMessage::SubmitApplyMealPreset(preset.id).into()),
+                Button::new("Delete")
+                    .style(button::danger)
+                    .on_press(MealListMessage::DeleteMealPreset(preset.id).into())
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+    }
+
+    if form.presets.is_empty() {
+        presets = presets.push(Text::new(
+            "No presets yet, save a meal as preset to reuse it later",
+        ));
+    }
+
+    container(
+        column![
+            Text::new(format!("Add preset to {}", form.meal.name)).size(30),
+            Scrollable::new(presets).height(Length::Shrink),
+            Button::new("Cancel")
+                .width(Length::Fill)
+                .on_press(MealListMessage::ApplyMealPresetFormMeal(None).into())
+        ]
+        .spacing(10),
+    )
+    .width(500)
+    .padding(30)
+    .style(container::rounded_box)
+    .into()
+}
@@ -0,0 +1,225 @@
+use chomp_services::{CreateMeasurementType, Measurement, MeasurementType, ServiceError};
+use iced::{
+    widget::{button, column, row, Button, Container, Scrollable, Text},
+    Alignment, Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{sidebar::sidebar, style::TableRowStyle, InputFormField, InputFormFieldError, Widget};
+
+type MeasurementId = usize;
+type MeasurementTypeId = usize;
+
+#[derive(Debug, Clone)]
+pub enum MeasurementListMessage {
+    RedirectToCreate,
+    DeleteMeasurement(MeasurementId),
+    UpdateTypeName(String),
+    UpdateTypeUnit(String),
+    SubmitType,
+    DeleteType(MeasurementTypeId),
+}
+
+impl From<MeasurementListMessage> for Message {
+    fn from(value: MeasurementListMessage) -> Self {
+        Message::MeasurementList(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct MeasurementList {
+    measurements: Vec<Measurement>,
+    types: Vec<MeasurementType>,
+    type_name: InputFormField<String>,
+    type_unit: InputFormField<String>,
+}
+
+impl MeasurementList {
+    pub fn new(measurements: Vec<Measurement>, types: Vec<MeasurementType>) -> Self {
+        MeasurementList {
+            measurements,
+            types,
+            type_name: InputFormField::new("Name*", "Neck"),
+            type_unit: InputFormField::new("Unit*", "cm"),
+        }
+    }
+
+    fn refresh(&mut self, ctx: &Context) {
+        self.measurements = ctx.services.measurement.list().unwrap_or_default();
+        self.types = ctx.services.measurement.list_types().unwrap_or_default();
+    }
+
+    fn submit_type(&mut self, ctx: &Context) {
+        self.type_name.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                Ok(input.to_string())
+            }
+        });
+        self.type_unit.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                Ok(input.to_string())
+            }
+        });
+
+        let (Some(name), Some(unit)) = (self.type_name.value.clone(), self.type_unit.value.clone())
+        else {
+            return;
+        };
+
+        match ctx
+            .services
+            .measurement
+            .create_type(CreateMeasurementType { name, unit })
+        {
+            Ok(_) => {
+                self.type_name = InputFormField::new("Name*", "Neck");
+                self.type_unit = InputFormField::new("Unit*", "cm");
+                self.refresh(ctx);
+            }
+            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                self.type_name.error = Some(InputFormFieldError::Custom(
+                    "Measurement with this name already exists".to_string(),
+                ));
+            }
+            Err(err) => {
+                tracing::error!("Failed to create measurement type: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+impl Widget for MeasurementList {
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row()];
+        for (i, measurement) in self.measurements.iter().enumerate() {
+            table = table.push(list_row(measurement, i % 2 == 0))
+        }
+
+        let mut types = column![].spacing(5);
+        for t in self.types.iter() {
+            types = types.push(
+                row![
+                    Text::new(t.to_string()).width(Length::Fill),
+                    Button::new("Delete")
+                        .style(button::danger)
+                        .on_press(MeasurementListMessage::DeleteType(t.id).into())
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let type_form = column![
+            Text::new("Measurement types").size(20),
+            types,
+            self.type_name
+                .view(|n| MeasurementListMessage::UpdateTypeName(n).into()),
+            self.type_unit.view_with_submit(
+                |u| MeasurementListMessage::UpdateTypeUnit(u).into(),
+                MeasurementListMessage::SubmitType.into()
+            ),
+            Button::new("Add type").on_press(MeasurementListMessage::SubmitType.into())
+        ]
+        .spacing(10)
+        .width(300);
+
+        let content = column![
+            row![
+                Text::new("Measurements").size(40),
+                Button::new("+").on_press(MeasurementListMessage::RedirectToCreate.into())
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            row![Scrollable::new(table).width(Length::Fill), type_form].spacing(20)
+        ]
+        .spacing(10);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::MeasurementList(msg) = msg {
+            match msg {
+                MeasurementListMessage::RedirectToCreate => {
+                    ctx.next_widget = Some(NextWidget::CreateMeasurement);
+                }
+                MeasurementListMessage::DeleteMeasurement(id) => {
+                    if let Err(err) = ctx.services.measurement.delete(id) {
+                        tracing::error!("Failed to delete measurement: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+                MeasurementListMessage::UpdateTypeName(raw_name) => {
+                    self.type_name.raw_input = raw_name;
+                }
+                MeasurementListMessage::UpdateTypeUnit(raw_unit) => {
+                    self.type_unit.raw_input = raw_unit;
+                }
+                MeasurementListMessage::SubmitType => self.submit_type(ctx),
+                MeasurementListMessage::DeleteType(type_id) => {
+                    if let Err(err) = ctx.services.measurement.delete_type(type_id) {
+                        tracing::error!("Failed to delete measurement type: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
+
+fn list_header_row() -> Element<'static, Message> {
+    let row = row![
+        Text::new("Day").width(Length::Fill),
+        Text::new("Measurement").width(Length::Fill),
+        Text::new("Value").width(Length::Fill),
+        Text::new("Actions").width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row).width(Length::Fill).into()
+}
+
+fn list_row(m: &Measurement, even: bool) -> Element<'_, Message> {
+    let row = row![
+        Text::new(format!("{}", m.day.format("%Y-%m-%d"))).width(Length::Fill),
+        Text::new(&m.name).width(Length::Fill),
+        Text::new(format!("{:.1} {}", m.value, m.unit)).width(Length::Fill),
+        row![
+            Button::new("Update")
+                .on_press(Message::ChangeWidget(NextWidget::UpdateMeasurement(m.id))),
+            Button::new("Delete")
+                .style(button::danger)
+                .on_press(MeasurementListMessage::DeleteMeasurement(m.id).into())
+        ]
+        .spacing(10)
+        .width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row)
+        .width(Length::Fill)
+        .style(move |t| {
+            if even {
+                TableRowStyle::Even.style(t)
+            } else {
+                TableRowStyle::Odd.style(t)
+            }
+        })
+        .into()
+}
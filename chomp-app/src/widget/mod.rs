@@ -2,31 +2,41 @@ use iced::{Element, Task};
 
 use crate::app::{Context, Message};
 
+mod create_measurement;
 mod create_nutrition_target;
 mod create_product;
 mod create_product_portion;
+mod create_recipe;
 mod create_weight;
 mod dashboard;
 mod date_picker;
 mod form_field;
 mod line_chart;
 mod meal_list;
+mod measurement_list;
 mod modal;
 mod nutrition_target_list;
 mod product_list;
 mod product_portion_list;
+mod recipe_list;
+mod reports;
+mod settings;
 mod sidebar;
 mod style;
 mod tools;
+mod update_measurement;
 mod update_nutrition_target;
 mod update_product;
 mod update_product_portion;
+mod update_recipe;
 mod update_weight;
 mod weight_list;
 
+pub use create_measurement::*;
 pub use create_nutrition_target::*;
 pub use create_product::*;
 pub use create_product_portion::*;
+pub use create_recipe::*;
 pub use create_weight::*;
 pub use dashboard::*;
 pub use date_picker::CalendarMonth;
@@ -34,19 +44,25 @@ use date_picker::*;
 use form_field::*;
 use line_chart::*;
 pub use meal_list::*;
+pub use measurement_list::*;
 use modal::*;
 pub use nutrition_target_list::*;
 pub use product_list::*;
 pub use product_portion_list::*;
+pub use recipe_list::*;
+pub use reports::*;
+pub use settings::*;
 use sidebar::*;
 pub use tools::*;
+pub use update_measurement::*;
 pub use update_nutrition_target::*;
 pub use update_product::*;
 pub use update_product_portion::*;
+pub use update_recipe::*;
 pub use update_weight::*;
 pub use weight_list::*;
 
 pub trait Widget {
-    fn view(&self) -> Element<Message>;
+    fn view(&self) -> Element<'_, Message>;
     fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message>;
 }
@@ -1,4 +1,4 @@
-use chomp_services::NutritionTarget;
+use chomp_services::{EnergyUnit, NutritionTarget, Units};
 use chrono::NaiveDate;
 use iced::{
     widget::{button, column, row, Button, Container, Scrollable, Text},
@@ -24,11 +24,12 @@ impl From<NutritionTargetListMessage> for Message {
 #[derive(Debug)]
 pub struct NutritionTargetList {
     targets: Vec<NutritionTarget>,
+    units: Units,
 }
 
 impl NutritionTargetList {
-    pub fn new(targets: Vec<NutritionTarget>) -> Self {
-        NutritionTargetList { targets }
+    pub fn new(targets: Vec<NutritionTarget>, units: Units) -> Self {
+        NutritionTargetList { targets, units }
     }
 
     fn refresh(&mut self, ctx: &Context) {
@@ -37,10 +38,10 @@ impl NutritionTargetList {
 }
 
 impl Widget for NutritionTargetList {
-    fn view(&self) -> Element<Message> {
-        let mut table = column![list_header_row()];
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row(self.units.energy)];
         for (i, target) in self.targets.iter().enumerate() {
-            table = table.push(list_row(target, i % 2 == 0))
+            table = table.push(list_row(target, self.units.energy, i % 2 == 0))
         }
 
         let content = column![
@@ -81,10 +82,10 @@ impl Widget for NutritionTargetList {
     }
 }
 
-fn list_header_row() -> Element<'static, Message> {
+fn list_header_row(energy: EnergyUnit) -> Element<'static, Message> {
     let row = row![
         Text::new("Day").width(Length::Fill),
-        Text::new("Calories (kcal/day)").width(Length::Fill),
+        Text::new(format!("Calories ({energy}/day)")).width(Length::Fill),
         Text::new("Fats (g/day)").width(Length::Fill),
         Text::new("Proteins (g/day)").width(Length::Fill),
         Text::new("Carbohydrates (g/day)").width(Length::Fill),
@@ -96,10 +97,10 @@ fn list_header_row() -> Element<'static, Message> {
     Container::new(row).width(Length::Fill).into()
 }
 
-fn list_row(t: &NutritionTarget, even: bool) -> Element<Message> {
+fn list_row(t: &NutritionTarget, energy: EnergyUnit, even: bool) -> Element<'_, Message> {
     let row = row![
         Text::new(format!("{}", t.day.format("%Y-%m-%d")),).width(Length::Fill),
-        Text::new(format!("{:.1}", t.calories)).width(Length::Fill),
+        Text::new(format!("{:.1}", energy.from_canonical(t.calories))).width(Length::Fill),
         Text::new(format!("{:.1}", t.fats)).width(Length::Fill),
         Text::new(format!("{:.1}", t.proteins)).width(Length::Fill),
         Text::new(format!("{:.1}", t.carbohydrates)).width(Length::Fill),
@@ -1,6 +1,6 @@
-use chomp_services::Product;
+use chomp_services::{EnergyUnit, Product, Units};
 use iced::{
-    widget::{button, column, row, Button, Container, Scrollable, Text},
+    widget::{button, column, horizontal_space, row, Button, Container, Scrollable, Text},
     Alignment, Element, Length, Task,
 };
 
@@ -13,6 +13,8 @@ pub enum ProductListMessage {
     RedirectToCreate,
     ProductSearch(String),
     DeleteProduct(usize),
+    ToggleArchived,
+    RestoreProduct(usize),
 }
 
 impl From<ProductListMessage> for Message {
@@ -26,52 +28,74 @@ pub struct ProductList {
     name_filter: InputFormField<String>,
     products: Vec<Product>,
     filtered_products: Vec<Product>,
+    show_archived: bool,
+    units: Units,
 }
 
 impl ProductList {
-    pub fn new(products: Vec<Product>) -> Self {
+    pub fn new(products: Vec<Product>, units: Units) -> Self {
         ProductList {
             name_filter: InputFormField::new("Product search", "Chicken"),
             products: products.clone(),
             filtered_products: products,
+            show_archived: false,
+            units,
         }
     }
 
     fn refresh(&mut self, ctx: &Context) {
-        self.products = ctx.services.product.list().unwrap_or_default();
-        self.filter();
+        self.products = if self.show_archived {
+            ctx.services.product.list_archived().unwrap_or_default()
+        } else {
+            ctx.services.product.list().unwrap_or_default()
+        };
+        self.filter(ctx);
     }
 
-    fn filter(&mut self) {
-        let name_filter = self.name_filter.raw_input.clone();
+    fn filter(&mut self, ctx: &Context) {
+        let name_filter = self.name_filter.raw_input.trim();
         if name_filter.is_empty() {
             self.filtered_products = self.products.clone();
-        } else {
+        } else if self.show_archived {
+            // Search index skips archived products, there are few of them to filter by name.
+            let name_filter = name_filter.to_lowercase();
             self.filtered_products = self
                 .products
                 .iter()
-                .filter(|p| {
-                    p.name
-                        .to_lowercase()
-                        .contains(name_filter.to_lowercase().as_str())
-                })
+                .filter(|p| p.to_string().to_lowercase().contains(&name_filter))
                 .cloned()
                 .collect();
+        } else {
+            self.filtered_products = match ctx.services.product.search(name_filter) {
+                Ok(p) => p,
+                Err(err) => {
+                    tracing::error!("Failed to search products: {}", err);
+                    std::process::exit(1);
+                }
+            };
         }
     }
 }
 
 impl Widget for ProductList {
-    fn view(&self) -> Element<Message> {
-        let mut table = column![list_header_row()];
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row(self.units.energy)];
         for (i, product) in self.filtered_products.iter().enumerate() {
-            table = table.push(list_row(product, i % 2 == 0))
+            table = table.push(list_row(product, self.units.energy, i % 2 == 0))
         }
 
+        let (title, toggle) = if self.show_archived {
+            ("Archived Products", "Show Products")
+        } else {
+            ("Products", "Show Archived")
+        };
+
         let content = column![
             row![
-                Text::new("Products").size(40),
-                Button::new("+").on_press(ProductListMessage::RedirectToCreate.into())
+                Text::new(title).size(40),
+                Button::new("+").on_press(ProductListMessage::RedirectToCreate.into()),
+                horizontal_space(),
+                Button::new(toggle).on_press(ProductListMessage::ToggleArchived.into())
             ]
             .spacing(10)
             .align_y(Alignment::Center),
@@ -96,11 +120,22 @@ impl Widget for ProductList {
                 }
                 ProductListMessage::ProductSearch(s) => {
                     self.name_filter.raw_input = s;
-                    self.filter();
+                    self.filter(ctx);
                 }
                 ProductListMessage::DeleteProduct(product_id) => {
                     if let Err(err) = ctx.services.product.delete(product_id) {
-                        tracing::error!("Failed to delete product: {}", err);
+                        tracing::error!("Failed to archive product: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+                ProductListMessage::ToggleArchived => {
+                    self.show_archived = !self.show_archived;
+                    self.refresh(ctx);
+                }
+                ProductListMessage::RestoreProduct(product_id) => {
+                    if let Err(err) = ctx.services.product.restore(product_id) {
+                        tracing::error!("Failed to restore product: {}", err);
                         std::process::exit(1);
                     }
                     self.refresh(ctx);
@@ -112,12 +147,12 @@ impl Widget for ProductList {
     }
 }
 
-fn list_header_row() -> Element<'static, Message> {
+fn list_header_row(energy: EnergyUnit) -> Element<'static, Message> {
     let row = row![
         Text::new("Name").width(Length::Fill),
         Text::new("Company").width(Length::Fill),
-        Text::new("Calories (kcal/100g)").width(Length::Fill),
-        Text::new("Fats (g/100kcal)").width(Length::Fill),
+        Text::new(format!("Calories ({energy}/100g)")).width(Length::Fill),
+        Text::new("Fats (g/100g)").width(Length::Fill),
         Text::new("Proteins (g/100g)").width(Length::Fill),
         Text::new("Carbohydrates (g/100g)").width(Length::Fill),
         Text::new("Actions").width(Length::Fill)
@@ -128,24 +163,37 @@ fn list_header_row() -> Element<'static, Message> {
     Container::new(row).width(Length::Fill).into()
 }
 
-fn list_row(p: &Product, even: bool) -> Element<Message> {
+fn actions(p: &Product) -> Element<'_, Message> {
+    if p.archived {
+        return row![
+            Button::new("Restore").on_press(ProductListMessage::RestoreProduct(p.id).into())
+        ]
+        .width(Length::Fill)
+        .into();
+    }
+
+    row![
+        Button::new("Update").on_press(Message::ChangeWidget(NextWidget::UpdateProduct(p.id))),
+        Button::new("Portions")
+            .on_press(Message::ChangeWidget(NextWidget::ProductPortionList(p.id))),
+        Button::new("Archive")
+            .style(button::danger)
+            .on_press(ProductListMessage::DeleteProduct(p.id).into())
+    ]
+    .spacing(10)
+    .width(Length::Fill)
+    .into()
+}
+
+fn list_row(p: &Product, energy: EnergyUnit, even: bool) -> Element<'_, Message> {
     let row = row![
         Text::new(&p.name).width(Length::Fill),
         Text::new(p.company.as_deref().unwrap_or("-")).width(Length::Fill),
-        Text::new(format!("{:.1}", p.calories)).width(Length::Fill),
+        Text::new(format!("{:.1}", energy.from_canonical(p.calories))).width(Length::Fill),
         Text::new(format!("{:.1}", p.fats)).width(Length::Fill),
         Text::new(format!("{:.1}", p.proteins)).width(Length::Fill),
         Text::new(format!("{:.1}", p.carbohydrates)).width(Length::Fill),
-        row![
-            Button::new("Update").on_press(Message::ChangeWidget(NextWidget::UpdateProduct(p.id))),
-            Button::new("Portions")
-                .on_press(Message::ChangeWidget(NextWidget::ProductPortionList(p.id))),
-            Button::new("Delete")
-                .style(button::danger)
-                .on_press(ProductListMessage::DeleteProduct(p.id).into())
-        ]
-        .spacing(10)
-        .width(Length::Fill)
+        actions(p)
     ]
     .padding(10)
     .width(Length::Fill);
@@ -1,4 +1,4 @@
-use chomp_services::{Product, ProductPortion};
+use chomp_services::{Product, ProductPortion, Units};
 use iced::{
     widget::{button, column, row, Button, Container, Scrollable, Text},
     Alignment, Element, Length, Task,
@@ -26,11 +26,16 @@ impl From<ProductPortionListMessage> for Message {
 pub struct ProductPortionList {
     product: Product,
     portions: Vec<ProductPortion>,
+    units: Units,
 }
 
 impl ProductPortionList {
-    pub fn new(product: Product, portions: Vec<ProductPortion>) -> Self {
-        ProductPortionList { product, portions }
+    pub fn new(product: Product, portions: Vec<ProductPortion>, units: Units) -> Self {
+        ProductPortionList {
+            product,
+            portions,
+            units,
+        }
     }
 
     fn refresh(&mut self, ctx: &Context) {
@@ -43,10 +48,10 @@ impl ProductPortionList {
 }
 
 impl Widget for ProductPortionList {
-    fn view(&self) -> Element<Message> {
-        let mut table = column![list_header_row()];
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row(self.units)];
         for (i, portion) in self.portions.iter().enumerate() {
-            table = table.push(list_row(&self.product, portion, i % 2 == 0))
+            table = table.push(list_row(&self.product, portion, self.units, i % 2 == 0))
         }
 
         let content = column![
@@ -87,10 +92,10 @@ impl Widget for ProductPortionList {
     }
 }
 
-fn list_header_row() -> Element<'static, Message> {
+fn list_header_row(units: Units) -> Element<'static, Message> {
     let row = row![
         Text::new("Name").width(Length::Fill),
-        Text::new("Weight (g)").width(Length::Fill),
+        Text::new(format!("Weight ({})", units.food_weight)).width(Length::Fill),
         Text::new("Actions").width(Length::Fill)
     ]
     .padding(10)
@@ -102,11 +107,16 @@ fn list_header_row() -> Element<'static, Message> {
 fn list_row<'a>(
     product: &'a Product,
     portion: &'a ProductPortion,
+    units: Units,
     even: bool,
 ) -> Element<'a, Message> {
     let row = row![
         Text::new(&portion.name).width(Length::Fill),
-        Text::new(format!("{:.1}", portion.weight)).width(Length::Fill),
+        Text::new(format!(
+            "{:.1}",
+            units.food_weight.from_canonical(portion.weight)
+        ))
+        .width(Length::Fill),
         row![
             Button::new("Update").on_press(Message::ChangeWidget(
                 NextWidget::UpdateProductPortion(product.id, portion.id)
@@ -0,0 +1,134 @@
+use chomp_services::{Recipe, Units};
+use iced::{
+    widget::{button, column, row, Button, Container, Scrollable, Text},
+    Alignment, Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{sidebar, style::TableRowStyle, Widget};
+
+type RecipeId = usize;
+
+#[derive(Debug, Clone)]
+pub enum RecipeListMessage {
+    RedirectToCreate,
+    DeleteRecipe(RecipeId),
+}
+
+impl From<RecipeListMessage> for Message {
+    fn from(value: RecipeListMessage) -> Self {
+        Message::RecipeList(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct RecipeList {
+    recipes: Vec<Recipe>,
+    units: Units,
+}
+
+impl RecipeList {
+    pub fn new(recipes: Vec<Recipe>, units: Units) -> Self {
+        RecipeList { recipes, units }
+    }
+
+    fn refresh(&mut self, ctx: &Context) {
+        self.recipes = ctx.services.recipe.list().unwrap_or_default();
+    }
+}
+
+impl Widget for RecipeList {
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row(self.units)];
+        for (i, recipe) in self.recipes.iter().enumerate() {
+            table = table.push(list_row(recipe, self.units, i % 2 == 0))
+        }
+
+        let content = column![
+            row![
+                Text::new("Recipes").size(40),
+                Button::new("+").on_press(RecipeListMessage::RedirectToCreate.into())
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            Scrollable::new(table)
+        ]
+        .spacing(10);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::RecipeList(msg) = msg {
+            match msg {
+                RecipeListMessage::RedirectToCreate => {
+                    ctx.next_widget = Some(NextWidget::CreateRecipe);
+                }
+                RecipeListMessage::DeleteRecipe(recipe_id) => {
+                    if let Err(err) = ctx.services.recipe.delete(recipe_id) {
+                        tracing::error!("Failed to archive recipe: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
+
+fn list_header_row(units: Units) -> Element<'static, Message> {
+    let row = row![
+        Text::new("Name").width(Length::Fill),
+        Text::new("Ingredients").width(Length::Fill),
+        Text::new(format!("Weight ({})", units.food_weight)).width(Length::Fill),
+        Text::new("Calories (kcal/100g)").width(Length::Fill),
+        Text::new("Fats (g/100g)").width(Length::Fill),
+        Text::new("Proteins (g/100g)").width(Length::Fill),
+        Text::new("Carbohydrates (g/100g)").width(Length::Fill),
+        Text::new("Actions").width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row).width(Length::Fill).into()
+}
+
+fn list_row(r: &Recipe, units: Units, even: bool) -> Element<'_, Message> {
+    let row = row![
+        Text::new(&r.name).width(Length::Fill),
+        Text::new(r.ingredients.len()).width(Length::Fill),
+        Text::new(format!("{:.1}", units.food_weight.from_canonical(r.weight))).width(Length::Fill),
+        Text::new(format!("{:.1}", r.calories)).width(Length::Fill),
+        Text::new(format!("{:.1}", r.fats)).width(Length::Fill),
+        Text::new(format!("{:.1}", r.proteins)).width(Length::Fill),
+        Text::new(format!("{:.1}", r.carbohydrates)).width(Length::Fill),
+        row![
+            Button::new("Update").on_press(Message::ChangeWidget(NextWidget::UpdateRecipe(r.id))),
+            Button::new("Archive")
+                .style(button::danger)
+                .on_press(RecipeListMessage::DeleteRecipe(r.id).into())
+        ]
+        .spacing(10)
+        .width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row)
+        .width(Length::Fill)
+        .style(move |t| {
+            if even {
+                TableRowStyle::Even.style(t)
+            } else {
+                TableRowStyle::Odd.style(t)
+            }
+        })
+        .into()
+}
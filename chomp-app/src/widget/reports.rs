@@ -0,0 +1,337 @@
+use std::fmt;
+
+use chomp_services::{Adherence, DayTotals, Macros, PeriodSummary, Report, ReportPeriod};
+use chrono::{Days, Months, NaiveDate, Weekday};
+use iced::{
+    widget::{column, horizontal_space, pick_list, row, Container, Scrollable, Text},
+    Alignment, Element, Length, Task,
+};
+
+use crate::app::{Context, Message};
+
+use super::{
+    modal, sidebar, style::TableRowStyle, DatePicker, InputFormField, InputFormFieldError, Widget,
+};
+
+pub const DEFAULT_REPORT_TOLERANCE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportRange {
+    FourWeeks,
+    ThreeMonths,
+    SixMonths,
+    Year,
+}
+
+impl ReportRange {
+    const ALL: [ReportRange; 4] = [
+        ReportRange::FourWeeks,
+        ReportRange::ThreeMonths,
+        ReportRange::SixMonths,
+        ReportRange::Year,
+    ];
+
+    /// First day of the range ending on `end`.
+    pub fn start(&self, end: NaiveDate) -> NaiveDate {
+        let start = match self {
+            ReportRange::FourWeeks => end.checked_sub_days(Days::new(28)),
+            ReportRange::ThreeMonths => end.checked_sub_months(Months::new(3)),
+            ReportRange::SixMonths => end.checked_sub_months(Months::new(6)),
+            ReportRange::Year => end.checked_sub_months(Months::new(12)),
+        };
+
+        start.and_then(|d| d.succ_opt()).unwrap_or(end)
+    }
+}
+
+impl fmt::Display for ReportRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportRange::FourWeeks => write!(f, "Last 4 weeks"),
+            ReportRange::ThreeMonths => write!(f, "Last 3 months"),
+            ReportRange::SixMonths => write!(f, "Last 6 months"),
+            ReportRange::Year => write!(f, "Last year"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReportsMessage {
+    ChangeRange(ReportRange),
+    ChangePeriod(ReportPeriod),
+    UpdateTolerance(String),
+}
+
+impl From<ReportsMessage> for Message {
+    fn from(value: ReportsMessage) -> Self {
+        Message::Reports(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct Reports {
+    end: DatePicker,
+    range: ReportRange,
+    period: ReportPeriod,
+    tolerance: InputFormField<f32>,
+    report: Report,
+}
+
+impl Reports {
+    pub fn new(
+        end: NaiveDate,
+        range: ReportRange,
+        period: ReportPeriod,
+        report: Report,
+        week_start: Weekday,
+    ) -> Self {
+        Reports {
+            end: DatePicker::new_with_value("To", &end, week_start),
+            range,
+            period,
+            tolerance: InputFormField::new_with_raw_value(
+                "Tolerance (%)",
+                "10",
+                &DEFAULT_REPORT_TOLERANCE.to_string(),
+            ),
+            report,
+        }
+    }
+
+    fn refresh(&mut self, ctx: &Context) {
+        self.tolerance.validate(|input| match input.parse::<f32>() {
+            Err(_) => Err(InputFormFieldError::InvalidNumber),
+            Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+            Ok(val) => Ok(val),
+        });
+        let Some(tolerance) = self
+            .tolerance
+            .value
+            .filter(|_| self.tolerance.error.is_none())
+        else {
+            return;
+        };
+
+        let end = self.end.value();
+        self.report = match ctx.services.report.report(
+            self.range.start(end),
+            end,
+            self.period,
+            tolerance / 100.0,
+        ) {
+            Ok(r) => r,
+            Err(err) => {
+                tracing::error!("Failed to create report: {}", err);
+                std::process::exit(1);
+            }
+        };
+    }
+}
+
+impl Widget for Reports {
+    fn view(&self) -> Element<'_, Message> {
+        let filters = row![
+            column![
+                Text::new("Range"),
+                pick_list(ReportRange::ALL, Some(self.range), |r| {
+                    ReportsMessage::ChangeRange(r).into()
+                })
+            ]
+            .spacing(2),
+            column![
+                Text::new("Averages"),
+                pick_list(ReportPeriod::ALL, Some(self.period), |p| {
+                    ReportsMessage::ChangePeriod(p).into()
+                })
+            ]
+            .spacing(2),
+            self.tolerance
+                .view(|t| ReportsMessage::UpdateTolerance(t).into()),
+            self.end.view(),
+        ]
+        .spacing(20)
+        .align_y(Alignment::End);
+
+        let mut periods = column![header_row("Period", "Calories on target")];
+        for (i, period) in self.report.periods.iter().rev().enumerate() {
+            periods = periods.push(period_row(period, i % 2 == 0));
+        }
+
+        let mut days = column![header_row("Day", "Calorie target")];
+        for (i, day) in self.report.days.iter().rev().enumerate() {
+            days = days.push(day_row(day, i % 2 == 0));
+        }
+
+        let content = column![
+            row![Text::new("Reports").size(40), horizontal_space(), filters]
+                .align_y(Alignment::Center),
+            summary(&self.report),
+            Scrollable::new(
+                column![
+                    Text::new(format!("{} averages", self.period)).size(20),
+                    periods,
+                    Text::new("Days").size(20),
+                    days
+                ]
+                .spacing(10)
+            )
+        ]
+        .spacing(20);
+
+        modal(
+            row![sidebar(), content]
+                .height(Length::Fill)
+                .padding(20)
+                .spacing(20)
+                .into(),
+            self.end.view_modal(),
+            Message::CloseDatePicker,
+            self.end.calendar_open(),
+        )
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        self.end.handle_message(msg.clone());
+
+        match msg {
+            Message::DatePickerDateChange(_) => self.refresh(ctx),
+            Message::Reports(msg) => {
+                match msg {
+                    ReportsMessage::ChangeRange(range) => self.range = range,
+                    ReportsMessage::ChangePeriod(period) => self.period = period,
+                    ReportsMessage::UpdateTolerance(raw_tolerance) => {
+                        self.tolerance.raw_input = raw_tolerance
+                    }
+                }
+                self.refresh(ctx);
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+}
+
+fn summary(report: &Report) -> Element<'_, Message> {
+    let logged = Text::new(format!(
+        "{} days logged between {} and {}",
+        report.days.len(),
+        report.from.format("%Y-%m-%d"),
+        report.to.format("%Y-%m-%d")
+    ));
+
+    if report.days.is_empty() {
+        return logged.into();
+    }
+
+    column![
+        logged,
+        Text::new(format!(
+            "Average per day: {}",
+            format_macros(&report.averages)
+        )),
+        Text::new(format_adherence(&report.adherence, report.tolerance)),
+    ]
+    .spacing(5)
+    .into()
+}
+
+fn format_macros(m: &Macros) -> String {
+    format!(
+        "{:.0} kcal, {:.1}g fats, {:.1}g proteins, {:.1}g carbohydrates",
+        m.calories, m.fats, m.proteins, m.carbohydrates
+    )
+}
+
+fn format_adherence(a: &Adherence, tolerance: f32) -> String {
+    if a.days_with_target == 0 {
+        return "No nutrition target set for these days".to_string();
+    }
+
+    format!(
+        "Within {:.0}% of target on {} days: calories {:.0}%, fats {:.0}%, proteins {:.0}%, carbohydrates {:.0}%",
+        tolerance * 100.0,
+        a.days_with_target,
+        a.calories,
+        a.fats,
+        a.proteins,
+        a.carbohydrates
+    )
+}
+
+fn header_row<'a>(first_column: &'a str, last_column: &'a str) -> Element<'a, Message> {
+    let row = row![
+        Text::new(first_column).width(Length::Fill),
+        Text::new("Calories (kcal)").width(Length::Fill),
+        Text::new("Fats (g)").width(Length::Fill),
+        Text::new("Proteins (g)").width(Length::Fill),
+        Text::new("Carbohydrates (g)").width(Length::Fill),
+        Text::new(last_column).width(Length::Fill),
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row).width(Length::Fill).into()
+}
+
+fn period_row(period: &PeriodSummary, even: bool) -> Element<'_, Message> {
+    let on_target = if period.adherence.days_with_target == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.0}%", period.adherence.calories)
+    };
+
+    table_row(
+        format!(
+            "{} ({} days)",
+            period.start.format("%Y-%m-%d"),
+            period.days_logged
+        ),
+        &period.averages,
+        on_target,
+        even,
+    )
+}
+
+fn day_row(day: &DayTotals, even: bool) -> Element<'_, Message> {
+    let on_target = match &day.target {
+        Some(t) => format!("{:.0}", t.calories),
+        None => "-".to_string(),
+    };
+
+    table_row(
+        day.day.format("%Y-%m-%d").to_string(),
+        &day.totals,
+        on_target,
+        even,
+    )
+}
+
+fn table_row(
+    label: String,
+    macros: &Macros,
+    on_target: String,
+    even: bool,
+) -> Element<'static, Message> {
+    let row = row![
+        Text::new(label).width(Length::Fill),
+        Text::new(format!("{:.1}", macros.calories)).width(Length::Fill),
+        Text::new(format!("{:.1}", macros.fats)).width(Length::Fill),
+        Text::new(format!("{:.1}", macros.proteins)).width(Length::Fill),
+        Text::new(format!("{:.1}", macros.carbohydrates)).width(Length::Fill),
+        Text::new(on_target).width(Length::Fill),
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row)
+        .width(Length::Fill)
+        .style(move |t| {
+            if even {
+                TableRowStyle::Even.style(t)
+            } else {
+                TableRowStyle::Odd.style(t)
+            }
+        })
+        .into()
+}
@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+use chomp_services::{BodyWeightUnit, EnergyUnit, FoodWeightUnit, Macros, MealTemplate};
+use chrono::Weekday;
+use iced::{
+    widget::{column, pick_list, row, Button, Scrollable, Text},
+    Alignment, Element, Length, Task, Theme,
+};
+
+use crate::app::{Context, Message};
+
+use super::{sidebar, ChartRange, InputFormField, InputFormFieldError, Widget};
+
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    SelectTheme(Theme),
+    SelectBodyWeightUnit(BodyWeightUnit),
+    SelectFoodWeightUnit(FoodWeightUnit),
+    SelectEnergyUnit(EnergyUnit),
+    SelectWeekStart(Weekday),
+    SelectDashboardRange(ChartRange),
+    UpdateTargetCalories(String),
+    UpdateTargetFats(String),
+    UpdateTargetProteins(String),
+    UpdateTargetCarbohydrates(String),
+    SubmitTarget,
+    UpdateMeals(String),
+    SubmitMeals,
+}
+
+impl From<SettingsMessage> for Message {
+    fn from(value: SettingsMessage) -> Self {
+        Message::Settings(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct Settings {
+    settings: chomp_services::Settings,
+    theme: Theme,
+    dashboard_range: ChartRange,
+    calories: InputFormField<f32>,
+    fats: InputFormField<f32>,
+    proteins: InputFormField<f32>,
+    carbohydrates: InputFormField<f32>,
+    meals: InputFormField<Vec<String>>,
+}
+
+impl Settings {
+    pub fn new(
+        settings: chomp_services::Settings,
+        theme: Theme,
+        dashboard_range: ChartRange,
+        meals: Vec<MealTemplate>,
+    ) -> Self {
+        let meals = meals
+            .into_iter()
+            .map(|m| m.name)
+            .collect::<Vec<String>>()
+            .join(", ");
+        let (calories, fats, proteins, carbohydrates) = target_fields(&settings);
+
+        Settings {
+            settings,
+            theme,
+            dashboard_range,
+            calories,
+            fats,
+            proteins,
+            carbohydrates,
+            meals: InputFormField::new_with_raw_value(
+                "Meals (comma separated)",
+                "Breakfast, Lunch, Dinner",
+                &meals,
+            ),
+        }
+    }
+
+    fn save(&self, ctx: &Context) {
+        if let Err(err) = ctx.services.settings.save(&self.settings) {
+            tracing::error!("Failed to save settings: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    fn parse_target(&mut self) -> Result<Macros, String> {
+        for field in [
+            &mut self.calories,
+            &mut self.fats,
+            &mut self.proteins,
+            &mut self.carbohydrates,
+        ] {
+            field.validate(|input| {
+                if input.is_empty() {
+                    Err(InputFormFieldError::MissingRequiredValue)
+                } else {
+                    match input.parse::<f32>() {
+                        Err(_) => Err(InputFormFieldError::InvalidNumber),
+                        Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                        Ok(val) => Ok(val),
+                    }
+                }
+            });
+        }
+
+        Ok(Macros {
+            calories: self
+                .settings
+                .units
+                .energy
+                .to_canonical(self.calories.value.ok_or("validation failed")?),
+            fats: self.fats.value.ok_or("validation failed")?,
+            proteins: self.proteins.value.ok_or("validation failed")?,
+            carbohydrates: self.carbohydrates.value.ok_or("validation failed")?,
+        })
+    }
+
+    fn parse_meals(&mut self) -> Result<Vec<String>, String> {
+        self.meals.validate(|input| {
+            let names: Vec<String> = input
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+            let unique: HashSet<&String> = names.iter().collect();
+
+            if names.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else if unique.len() != names.len() {
+                Err(InputFormFieldError::Custom(
+                    "Meal names must be unique".to_string(),
+                ))
+            } else {
+                Ok(names)
+            }
+        });
+
+        Ok(self.meals.value.clone().ok_or("validation failed")?)
+    }
+}
+
+impl Widget for Settings {
+    fn view(&self) -> Element<'_, Message> {
+        let units = self.settings.units;
+
+        let general = column![
+            Text::new("General").size(20),
+            setting(
+                "Theme",
+                pick_list(Theme::ALL, Some(self.theme.clone()), |t| {
+                    SettingsMessage::SelectTheme(t).into()
+                })
+            ),
+            setting(
+                "First day of the week",
+                pick_list(WEEKDAYS, Some(self.settings.week_start), |d| {
+                    SettingsMessage::SelectWeekStart(d).into()
+                })
+            ),
+            setting(
+                "Dashboard range",
+                pick_list(ChartRange::ALL, Some(self.dashboard_range), |r| {
+                    SettingsMessage::SelectDashboardRange(r).into()
+                })
+            ),
+        ]
+        .spacing(10);
+
+        let unit_settings = column![
+            Text::new("Units").size(20),
+            setting(
+                "Body weight",
+                pick_list(BodyWeightUnit::ALL, Some(units.body_weight), |u| {
+                    SettingsMessage::SelectBodyWeightUnit(u).into()
+                })
+            ),
+            setting(
+                "Food weight",
+                pick_list(FoodWeightUnit::ALL, Some(units.food_weight), |u| {
+                    SettingsMessage::SelectFoodWeightUnit(u).into()
+                })
+            ),
+            setting(
+                "Energy",
+                pick_list(EnergyUnit::ALL, Some(units.energy), |u| {
+                    SettingsMessage::SelectEnergyUnit(u).into()
+                })
+            ),
+        ]
+        .spacing(10);
+
+        let target = column![
+            Text::new("Default nutrition target").size(20),
+            Text::new("Used for the first day when no nutrition target exists yet"),
+            self.calories
+                .view(|c| SettingsMessage::UpdateTargetCalories(c).into()),
+            self.fats
+                .view(|f| SettingsMessage::UpdateTargetFats(f).into()),
+            self.proteins
+                .view(|p| SettingsMessage::UpdateTargetProteins(p).into()),
+            self.carbohydrates.view_with_submit(
+                |c| SettingsMessage::UpdateTargetCarbohydrates(c).into(),
+                SettingsMessage::SubmitTarget.into()
+            ),
+            Button::new("Save Target").on_press(SettingsMessage::SubmitTarget.into())
+        ]
+        .spacing(10);
+
+        let meals = column![
+            Text::new("Default meals").size(20),
+            Text::new("Meals created for new days, replaces the layout of every weekday"),
+            self.meals.view_with_submit(
+                |m| SettingsMessage::UpdateMeals(m).into(),
+                SettingsMessage::SubmitMeals.into()
+            ),
+            Button::new("Use For Every Day").on_press(SettingsMessage::SubmitMeals.into())
+        ]
+        .spacing(10);
+
+        let content = Scrollable::new(
+            column![
+                Text::new("Settings").size(40),
+                general,
+                unit_settings,
+                target,
+                meals
+            ]
+            .spacing(20)
+            .width(500),
+        )
+        .height(Length::Fill);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::Settings(msg) = msg {
+            match msg {
+                SettingsMessage::SelectTheme(theme) => {
+                    self.settings.theme = Some(theme.to_string());
+                    self.theme = theme;
+                    self.save(ctx);
+                }
+                SettingsMessage::SelectBodyWeightUnit(unit) => {
+                    self.settings.units.body_weight = unit;
+                    self.save(ctx);
+                }
+                SettingsMessage::SelectFoodWeightUnit(unit) => {
+                    self.settings.units.food_weight = unit;
+                    self.save(ctx);
+                }
+                SettingsMessage::SelectEnergyUnit(unit) => {
+                    self.settings.units.energy = unit;
+                    self.save(ctx);
+                    (self.calories, self.fats, self.proteins, self.carbohydrates) =
+                        target_fields(&self.settings);
+                }
+                SettingsMessage::SelectWeekStart(weekday) => {
+                    self.settings.week_start = weekday;
+                    self.save(ctx);
+                }
+                SettingsMessage::SelectDashboardRange(range) => {
+                    self.settings.dashboard_range = Some(range.to_string());
+                    self.dashboard_range = range;
+                    self.save(ctx);
+                }
+                SettingsMessage::UpdateTargetCalories(raw_calories) => {
+                    self.calories.raw_input = raw_calories;
+                }
+                SettingsMessage::UpdateTargetFats(raw_fats) => {
+                    self.fats.raw_input = raw_fats;
+                }
+                SettingsMessage::UpdateTargetProteins(raw_proteins) => {
+                    self.proteins.raw_input = raw_proteins;
+                }
+                SettingsMessage::UpdateTargetCarbohydrates(raw_carbohydrates) => {
+                    self.carbohydrates.raw_input = raw_carbohydrates;
+                }
+                SettingsMessage::SubmitTarget => {
+                    if let Ok(target) = self.parse_target() {
+                        self.settings.default_target = target;
+                        self.save(ctx);
+                    }
+                }
+                SettingsMessage::UpdateMeals(raw_meals) => {
+                    self.meals.raw_input = raw_meals;
+                }
+                SettingsMessage::SubmitMeals => {
+                    if let Ok(names) = self.parse_meals() {
+                        for weekday in WEEKDAYS {
+                            if let Err(err) = ctx.services.meal_template.replace(weekday, &names) {
+                                tracing::error!("Failed to save meal template: {}", err);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
+
+fn setting<'a>(label: &'a str, input: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    row![Text::new(label).width(Length::Fixed(200.0)), input.into()]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+fn target_fields(
+    settings: &chomp_services::Settings,
+) -> (
+    InputFormField<f32>,
+    InputFormField<f32>,
+    InputFormField<f32>,
+    InputFormField<f32>,
+) {
+    let energy = settings.units.energy;
+    let target = settings.default_target;
+
+    (
+        InputFormField::new_with_raw_value(
+            &format!("Calories* ({energy}/day)"),
+            "2500.0",
+            &format!("{:.0}", energy.from_canonical(target.calories)),
+        ),
+        InputFormField::new_with_raw_value("Fats* (g/day)", "80.0", &target.fats.to_string()),
+        InputFormField::new_with_raw_value(
+            "Proteins* (g/day)",
+            "200.0",
+            &target.proteins.to_string(),
+        ),
+        InputFormField::new_with_raw_value(
+            "Carbohydrates* (g/day)",
+            "245.0",
+            &target.carbohydrates.to_string(),
+        ),
+    )
+}
@@ -12,6 +12,10 @@ pub fn sidebar() -> Element<'static, Message> {
     ));
     let product_icon =
         svg::Handle::from_path(format!("{}/resources/beef.svg", env!("CARGO_MANIFEST_DIR")));
+    let recipe_icon = svg::Handle::from_path(format!(
+        "{}/resources/cooking-pot.svg",
+        env!("CARGO_MANIFEST_DIR")
+    ));
     let meal_icon = svg::Handle::from_path(format!(
         "{}/resources/salad.svg",
         env!("CARGO_MANIFEST_DIR")
@@ -20,14 +24,26 @@ pub fn sidebar() -> Element<'static, Message> {
         "{}/resources/weight.svg",
         env!("CARGO_MANIFEST_DIR")
     ));
+    let measurement_icon = svg::Handle::from_path(format!(
+        "{}/resources/ruler.svg",
+        env!("CARGO_MANIFEST_DIR")
+    ));
     let target_icon = svg::Handle::from_path(format!(
         "{}/resources/target.svg",
         env!("CARGO_MANIFEST_DIR")
     ));
+    let report_icon = svg::Handle::from_path(format!(
+        "{}/resources/chart-line.svg",
+        env!("CARGO_MANIFEST_DIR")
+    ));
     let tool_icon = svg::Handle::from_path(format!(
         "{}/resources/wrench.svg",
         env!("CARGO_MANIFEST_DIR")
     ));
+    let settings_icon = svg::Handle::from_path(format!(
+        "{}/resources/settings.svg",
+        env!("CARGO_MANIFEST_DIR")
+    ));
 
     let buttons = vec![
         (
@@ -45,21 +61,41 @@ pub fn sidebar() -> Element<'static, Message> {
             "Products",
             Message::ChangeWidget(NextWidget::ProductList),
         ),
+        (
+            recipe_icon.clone(),
+            "Recipes",
+            Message::ChangeWidget(NextWidget::RecipeList),
+        ),
         (
             weight_icon.clone(),
             "Weights",
             Message::ChangeWidget(NextWidget::WeightList),
         ),
+        (
+            measurement_icon.clone(),
+            "Measurements",
+            Message::ChangeWidget(NextWidget::MeasurementList),
+        ),
         (
             target_icon.clone(),
             "Nutrition Targets",
             Message::ChangeWidget(NextWidget::NutritionTargetList),
         ),
+        (
+            report_icon.clone(),
+            "Reports",
+            Message::ChangeWidget(NextWidget::Reports),
+        ),
         (
             tool_icon.clone(),
             "Tools",
             Message::ChangeWidget(NextWidget::Tools),
         ),
+        (
+            settings_icon.clone(),
+            "Settings",
+            Message::ChangeWidget(NextWidget::Settings),
+        ),
     ];
 
     let navigation = buttons
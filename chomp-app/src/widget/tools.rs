@@ -1,4 +1,7 @@
-use chomp_services::{CreateUpdateProduct, ServiceError, Weight};
+use chomp_services::{
+    Archive, BodyWeightUnit, CreateMeasurement, CreateUpdateProduct, ImportConflict, ServiceError,
+    Weight,
+};
 use std::{
     fs::File,
     path::{Path, PathBuf},
@@ -7,15 +10,18 @@ use std::{
 use chrono::NaiveDate;
 use csv::Reader;
 use iced::{
-    widget::{column, row, Button, Text},
-    Element,
+    widget::{checkbox, column, row, text_input, Button, Column, Scrollable, Text},
+    Alignment, Element,
     Length::{self},
     Task,
 };
 use rfd::AsyncFileDialog;
 use serde::Deserialize;
 
-use crate::app::{Context, Message};
+use crate::{
+    app::{Context, Message, NextWidget},
+    open_food_facts::{self, alternative_names, OffFilter},
+};
 
 use super::{sidebar::sidebar, Widget};
 
@@ -27,14 +33,27 @@ struct FitnotesRecord {
     measurement: String,
     #[serde(rename = "Value")]
     value: f32,
+    #[serde(rename = "Unit", default)]
+    unit: String,
 }
 
 #[derive(Debug, Clone)]
 pub enum ToolsMessage {
-    PickFitnotesWeightsDataFile,
-    LoadFitnotesWeightsData(Option<PathBuf>),
+    PickFitnotesDataFile,
+    LoadFitnotesData(Option<PathBuf>),
     PickProductsJSONDataFile,
     LoadProductsJSONData(Option<PathBuf>),
+    UpdateOpenFoodFactsCountry(String),
+    UpdateOpenFoodFactsLanguage(String),
+    PickOpenFoodFactsDataFile,
+    LoadOpenFoodFactsData(Option<PathBuf>),
+    PickArchiveExportFile,
+    ExportArchive(Option<PathBuf>),
+    PickArchiveImportFile,
+    ImportArchive(Option<PathBuf>),
+    ToggleOverwriteOnImport(bool),
+    CreateBackup,
+    RestoreBackup(PathBuf),
 }
 
 impl From<ToolsMessage> for Message {
@@ -44,20 +63,30 @@ impl From<ToolsMessage> for Message {
 }
 
 #[derive(Debug)]
-pub struct Tools {}
+pub struct Tools {
+    open_food_facts_country: String,
+    open_food_facts_language: String,
+    overwrite_on_import: bool,
+    backups: Vec<PathBuf>,
+}
 
 impl Tools {
-    pub fn new() -> Self {
-        Tools {}
+    pub fn new(backups: Vec<PathBuf>) -> Self {
+        Tools {
+            open_food_facts_country: String::new(),
+            open_food_facts_language: String::new(),
+            overwrite_on_import: false,
+            backups,
+        }
     }
 }
 
 impl Widget for Tools {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let fitnotes = column![
             Text::new("Fitnotes"),
-            Button::new("Load Bodyweights From CSV File")
-                .on_press(ToolsMessage::PickFitnotesWeightsDataFile.into())
+            Button::new("Load Measurements From CSV File")
+                .on_press(ToolsMessage::PickFitnotesDataFile.into())
         ]
         .spacing(2);
 
@@ -68,7 +97,68 @@ impl Widget for Tools {
         ]
         .spacing(2);
 
-        let content = column![Text::new("Tools").size(40), fitnotes, json_products].spacing(10);
+        let open_food_facts = column![
+            Text::new("Open Food Facts"),
+            row![
+                text_input("Country, e.g. poland", &self.open_food_facts_country)
+                    .on_input(|c| ToolsMessage::UpdateOpenFoodFactsCountry(c).into())
+                    .width(Length::Fixed(200.0)),
+                text_input("Language, e.g. pl", &self.open_food_facts_language)
+                    .on_input(|l| ToolsMessage::UpdateOpenFoodFactsLanguage(l).into())
+                    .width(Length::Fixed(200.0)),
+            ]
+            .spacing(10),
+            Button::new("Load Products From Open Food Facts Export (CSV or JSONL)")
+                .on_press(ToolsMessage::PickOpenFoodFactsDataFile.into())
+        ]
+        .spacing(2);
+
+        let archive = column![
+            Text::new("Database"),
+            Button::new("Export Everything To JSON File")
+                .on_press(ToolsMessage::PickArchiveExportFile.into()),
+            Button::new("Import Everything From JSON File")
+                .on_press(ToolsMessage::PickArchiveImportFile.into()),
+            checkbox(
+                "Overwrite existing entries on import",
+                self.overwrite_on_import
+            )
+            .on_toggle(|checked| ToolsMessage::ToggleOverwriteOnImport(checked).into())
+        ]
+        .spacing(2);
+
+        let backup_rows = self.backups.iter().map(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            row![
+                Text::new(name).width(Length::Fixed(250.0)),
+                Button::new("Restore").on_press(ToolsMessage::RestoreBackup(path.clone()).into())
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into()
+        });
+        let backups = column![
+            Text::new("Backups"),
+            Button::new("Create Backup Now").on_press(ToolsMessage::CreateBackup.into()),
+            Column::with_children(backup_rows).spacing(2)
+        ]
+        .spacing(2);
+
+        let content = Scrollable::new(
+            column![
+                Text::new("Tools").size(40),
+                fitnotes,
+                json_products,
+                open_food_facts,
+                archive,
+                backups
+            ]
+            .spacing(10),
+        )
+        .height(Length::Fill);
 
         row![sidebar(), content]
             .height(Length::Fill)
@@ -80,14 +170,14 @@ impl Widget for Tools {
     fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
         if let Message::Tools(msg) = msg {
             match msg {
-                ToolsMessage::PickFitnotesWeightsDataFile => {
-                    return Task::perform(pick_fitnotes_weights_data_file(), |file_path| {
-                        ToolsMessage::LoadFitnotesWeightsData(file_path).into()
+                ToolsMessage::PickFitnotesDataFile => {
+                    return Task::perform(pick_fitnotes_data_file(), |file_path| {
+                        ToolsMessage::LoadFitnotesData(file_path).into()
                     });
                 }
-                ToolsMessage::LoadFitnotesWeightsData(file_path) => {
+                ToolsMessage::LoadFitnotesData(file_path) => {
                     if let Some(path) = file_path {
-                        import_fitnotes_weights_data(&path, ctx);
+                        import_fitnotes_data(&path, ctx);
                     }
                 }
                 ToolsMessage::PickProductsJSONDataFile => {
@@ -100,6 +190,71 @@ impl Widget for Tools {
                         import_products_data(&path, ctx);
                     }
                 }
+                ToolsMessage::UpdateOpenFoodFactsCountry(country) => {
+                    self.open_food_facts_country = country;
+                }
+                ToolsMessage::UpdateOpenFoodFactsLanguage(language) => {
+                    self.open_food_facts_language = language;
+                }
+                ToolsMessage::PickOpenFoodFactsDataFile => {
+                    return Task::perform(pick_open_food_facts_data_file(), |file_path| {
+                        ToolsMessage::LoadOpenFoodFactsData(file_path).into()
+                    });
+                }
+                ToolsMessage::LoadOpenFoodFactsData(file_path) => {
+                    if let Some(path) = file_path {
+                        let filter = OffFilter::new(
+                            &self.open_food_facts_country,
+                            &self.open_food_facts_language,
+                        );
+                        import_open_food_facts_data(&path, &filter, ctx);
+                    }
+                }
+                ToolsMessage::PickArchiveExportFile => {
+                    return Task::perform(pick_archive_export_file(), |file_path| {
+                        ToolsMessage::ExportArchive(file_path).into()
+                    });
+                }
+                ToolsMessage::ExportArchive(file_path) => {
+                    if let Some(path) = file_path {
+                        export_archive(&path, ctx);
+                    }
+                }
+                ToolsMessage::PickArchiveImportFile => {
+                    return Task::perform(pick_archive_import_file(), |file_path| {
+                        ToolsMessage::ImportArchive(file_path).into()
+                    });
+                }
+                ToolsMessage::ImportArchive(file_path) => {
+                    if let Some(path) = file_path {
+                        let conflict = if self.overwrite_on_import {
+                            ImportConflict::Overwrite
+                        } else {
+                            ImportConflict::Skip
+                        };
+                        import_archive(&path, conflict, ctx);
+                    }
+                }
+                ToolsMessage::ToggleOverwriteOnImport(checked) => {
+                    self.overwrite_on_import = checked;
+                }
+
+                ToolsMessage::CreateBackup => {
+                    match ctx.services.backup.create() {
+                        Ok(path) => tracing::info!("Created database backup {}", path.display()),
+                        Err(err) => tracing::error!("Failed to create database backup: {}", err),
+                    }
+                    ctx.next_widget = Some(NextWidget::Tools);
+                }
+                ToolsMessage::RestoreBackup(path) => {
+                    // Current state is backed up before restoring, so this can be undone by
+                    // restoring the newest backup.
+                    match ctx.services.backup.restore(&path) {
+                        Ok(_) => tracing::info!("Restored database backup {}", path.display()),
+                        Err(err) => tracing::error!("Failed to restore database backup: {}", err),
+                    }
+                    ctx.next_widget = Some(NextWidget::Tools);
+                }
             }
         };
 
@@ -107,6 +262,179 @@ impl Widget for Tools {
     }
 }
 
+async fn pick_open_food_facts_data_file() -> Option<PathBuf> {
+    AsyncFileDialog::new()
+        .set_title("Select Open Food Facts data export...")
+        .add_filter(
+            "Open Food Facts exports",
+            &["csv", "tsv", "jsonl", "ndjson", "json"],
+        )
+        .pick_file()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+fn import_open_food_facts_data(path: &Path, filter: &OffFilter, ctx: &mut Context) {
+    let mut created = 0;
+    let mut renamed = 0;
+    let mut skipped = 0;
+    let mut filtered_out = 0;
+    let mut incomplete = 0;
+    let mut malformed = 0;
+    let mut failed_to_create = 0;
+
+    // All products are created in one transaction, one by one would be slow and each of them
+    // would end up in undo history.
+    let result = ctx.services.product.import_many(|importer| {
+        open_food_facts::read_products(path, |record| {
+            let product = match record {
+                Ok(p) => p,
+                Err(err) => {
+                    malformed += 1;
+                    tracing::debug!("Skipping malformed Open Food Facts record: {}", err);
+                    return;
+                }
+            };
+
+            if !filter.matches(&product) {
+                filtered_out += 1;
+                return;
+            }
+
+            let Some(mut product) = product.into_create_product(filter.language.as_deref()) else {
+                incomplete += 1;
+                return;
+            };
+
+            // Taken name doesn't mean it's the same product, it's only skipped when barcode
+            // already exists or none of alternative names is free.
+            let mut alternative_names = alternative_names(&product).into_iter();
+            let mut is_renamed = false;
+            loop {
+                match importer.create(&product) {
+                    Ok(_) => {
+                        created += 1;
+                        if is_renamed {
+                            renamed += 1;
+                        }
+                        break;
+                    }
+                    Err(ServiceError::UniqueConstraintViolation(unique_field))
+                        if unique_field == "products.name" =>
+                    {
+                        match alternative_names.next() {
+                            Some(name) => {
+                                product.name = name;
+                                is_renamed = true;
+                            }
+                            None => {
+                                skipped += 1;
+                                break;
+                            }
+                        }
+                    }
+                    Err(ServiceError::UniqueConstraintViolation(_)) => {
+                        skipped += 1;
+                        break;
+                    }
+                    Err(err) => {
+                        failed_to_create += 1;
+                        tracing::error!("Failed to create product: {}", err);
+                        break;
+                    }
+                }
+            }
+        })
+    });
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => tracing::error!("Failed to read Open Food Facts export: {}", err),
+        Err(err) => tracing::error!("Failed to import Open Food Facts products: {}", err),
+    }
+
+    tracing::info!(
+        "Open Food Facts products: created {} (renamed {}), skipped (exists) {}, filtered out {}, incomplete {}, malformed {}, failed {}",
+        created,
+        renamed,
+        skipped,
+        filtered_out,
+        incomplete,
+        malformed,
+        failed_to_create,
+    );
+}
+
+async fn pick_archive_export_file() -> Option<PathBuf> {
+    AsyncFileDialog::new()
+        .set_title("Select where to save exported data...")
+        .set_file_name("chomp-export.json")
+        .add_filter("JSON files", &["json"])
+        .save_file()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+fn export_archive(path: &Path, ctx: &mut Context) {
+    let archive = match ctx.services.archive.export() {
+        Ok(a) => a,
+        Err(err) => {
+            tracing::error!("Failed to export data: {}", err);
+            return;
+        }
+    };
+
+    let file = match File::create(path) {
+        Ok(f) => f,
+        Err(err) => {
+            tracing::error!("Failed to create export file: {}", err);
+            return;
+        }
+    };
+
+    match serde_json::to_writer_pretty(file, &archive) {
+        Ok(_) => tracing::info!("Exported data to {}", path.display()),
+        Err(err) => tracing::error!("Failed to write export file: {}", err),
+    }
+}
+
+async fn pick_archive_import_file() -> Option<PathBuf> {
+    AsyncFileDialog::new()
+        .set_title("Select file with exported data...")
+        .add_filter("JSON files", &["json"])
+        .pick_file()
+        .await
+        .map(|handle| handle.path().to_path_buf())
+}
+
+fn import_archive(path: &Path, conflict: ImportConflict, ctx: &mut Context) {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(err) => {
+            tracing::error!("Failed to open export file: {}", err);
+            return;
+        }
+    };
+
+    let archive: Archive = match serde_json::from_reader(file) {
+        Ok(a) => a,
+        Err(err) => {
+            tracing::error!("Failed to parse export file: {}", err);
+            return;
+        }
+    };
+
+    match ctx.services.archive.import(&archive, conflict) {
+        Ok(summary) => tracing::info!(
+            "Imported data: created {}, updated {}, skipped (exists) {}",
+            summary.created,
+            summary.updated,
+            summary.skipped,
+        ),
+        Err(err) => tracing::error!("Failed to import data: {}", err),
+    }
+}
+
 async fn pick_products_data_file() -> Option<PathBuf> {
     AsyncFileDialog::new()
         .set_title("Select file with products in JSON format...")
@@ -166,16 +494,18 @@ fn import_products_data(path: &Path, ctx: &mut Context) {
     );
 }
 
-async fn pick_fitnotes_weights_data_file() -> Option<PathBuf> {
+async fn pick_fitnotes_data_file() -> Option<PathBuf> {
     AsyncFileDialog::new()
-        .set_title("Select fitnotes weights data...")
+        .set_title("Select fitnotes measurements data...")
         .add_filter("CSV files", &["csv"])
         .pick_file()
         .await
         .map(|handle| handle.path().to_path_buf())
 }
 
-fn import_fitnotes_weights_data(path: &Path, ctx: &mut Context) {
+/// Imports "Bodyweight" records as weights and everything else as measurements, types that
+/// don't exist yet are created with the unit of the record.
+fn import_fitnotes_data(path: &Path, ctx: &mut Context) {
     let file = match File::open(path) {
         Ok(f) => f,
         Err(err) => {
@@ -187,10 +517,10 @@ fn import_fitnotes_weights_data(path: &Path, ctx: &mut Context) {
     let mut rdr = Reader::from_reader(file);
 
     let mut found_records = 0;
-    let mut added_records = 0;
+    let mut added_weights = 0;
+    let mut added_measurements = 0;
     let mut skipped_adding_because_of_existing = 0;
     let mut failed_to_add_because_of_unexpected_error = 0;
-    let mut ignored_records = 0;
     let mut malformed_records = 0;
 
     for result in rdr.deserialize::<FitnotesRecord>() {
@@ -203,39 +533,52 @@ fn import_fitnotes_weights_data(path: &Path, ctx: &mut Context) {
             }
         };
 
-        if record.measurement != "Bodyweight" {
-            ignored_records += 1;
-            continue;
-        }
-
         found_records += 1;
-        let weight = Weight::new(record.date, record.value);
 
-        match ctx.services.weight.create(weight.clone()) {
-            Ok(_) => {
-                added_records += 1;
+        let result = if record.measurement == "Bodyweight" {
+            // Fitnotes exports body weight in whatever unit the user had set, kgs or lbs.
+            let weight = if record.unit.starts_with("lb") {
+                BodyWeightUnit::Pound.to_canonical(record.value)
+            } else {
+                record.value
+            };
+            ctx.services
+                .weight
+                .create(Weight::new(record.date, weight))
+                .map(|_| added_weights += 1)
+        } else {
+            ctx.services
+                .measurement
+                .find_or_create_type(&record.measurement, &record.unit)
+                .and_then(|type_id| {
+                    ctx.services.measurement.create(CreateMeasurement {
+                        type_id,
+                        day: record.date,
+                        value: record.value,
+                    })
+                })
+                .map(|_| added_measurements += 1)
+        };
+
+        match result {
+            Ok(_) => {}
+            Err(ServiceError::UniqueConstraintViolation(_)) => {
+                skipped_adding_because_of_existing += 1;
+            }
+            Err(err) => {
+                failed_to_add_because_of_unexpected_error += 1;
+                tracing::error!("Failed to create {}: {}", record.measurement, err);
             }
-            Err(err) => match err {
-                ServiceError::UniqueConstraintViolation(unique_field)
-                    if unique_field == "weights.day" =>
-                {
-                    skipped_adding_because_of_existing += 1;
-                }
-                _ => {
-                    failed_to_add_because_of_unexpected_error += 1;
-                    tracing::error!("Failed to create weight: {}", err);
-                }
-            },
         }
     }
 
     tracing::info!(
-        "Processed {} records: added {}, skipped (exists) {}, failed {}, ignored {}, malformed {}",
+        "Processed {} records: added {} weights and {} measurements, skipped (exists) {}, failed {}, malformed {}",
         found_records,
-        added_records,
+        added_weights,
+        added_measurements,
         skipped_adding_because_of_existing,
         failed_to_add_because_of_unexpected_error,
-        ignored_records,
         malformed_records
     );
 }
@@ -0,0 +1,106 @@
+use chomp_services::Measurement;
+use iced::{
+    widget::{column, row, Button, Text},
+    Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{sidebar::sidebar, InputFormField, InputFormFieldError, Widget};
+
+#[derive(Debug, Clone)]
+pub enum UpdateMeasurementMessage {
+    UpdateValue(String),
+    Submit,
+}
+
+impl From<UpdateMeasurementMessage> for Message {
+    fn from(value: UpdateMeasurementMessage) -> Self {
+        Message::UpdateMeasurement(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateMeasurement {
+    measurement: Measurement,
+    value: InputFormField<f32>,
+}
+
+impl UpdateMeasurement {
+    pub fn new(m: Measurement) -> Self {
+        UpdateMeasurement {
+            value: InputFormField::new_with_raw_value(
+                &format!("Value* ({})", m.unit),
+                "85.5",
+                m.value.to_string().as_str(),
+            ),
+            measurement: m,
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<f32, String> {
+        self.value.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(val),
+                }
+            }
+        });
+
+        Ok(self.value.value.ok_or("validation failed")?)
+    }
+}
+
+impl Widget for UpdateMeasurement {
+    fn view(&self) -> Element<'_, Message> {
+        let form = column![self
+            .value
+            .view(|v| { UpdateMeasurementMessage::UpdateValue(v).into() }),]
+        .spacing(10);
+
+        let content = column![
+            Text::new(format!(
+                "Update {} for {}",
+                self.measurement.name.to_lowercase(),
+                self.measurement.day.format("%Y-%m-%d")
+            ))
+            .size(40),
+            form,
+            Button::new("Update").on_press(UpdateMeasurementMessage::Submit.into())
+        ]
+        .spacing(10);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::UpdateMeasurement(msg) = msg {
+            match msg {
+                UpdateMeasurementMessage::UpdateValue(raw_value) => {
+                    self.value.raw_input = raw_value;
+                }
+                UpdateMeasurementMessage::Submit => {
+                    if let Ok(value) = self.parse() {
+                        if let Err(err) =
+                            ctx.services.measurement.update(self.measurement.id, value)
+                        {
+                            tracing::error!("Failed to update measurement: {}", err);
+                            std::process::exit(1);
+                        }
+                        ctx.next_widget = Some(NextWidget::MeasurementList);
+                    };
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
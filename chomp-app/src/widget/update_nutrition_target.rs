@@ -1,13 +1,13 @@
-use chomp_services::NutritionTarget;
-use chrono::NaiveDate;
+use chomp_services::{MealBudget, NutritionTarget, ServiceError, Units, WeekdayTarget};
+use chrono::{NaiveDate, Weekday};
 use iced::{
-    widget::{column, row, Button, Text},
-    Element, Length, Task,
+    widget::{button, column, pick_list, row, Button, Scrollable, Text},
+    Alignment, Element, Length, Task,
 };
 
 use crate::app::{Context, Message, NextWidget};
 
-use super::{sidebar::sidebar, InputFormField, InputFormFieldError, Widget};
+use super::{settings::WEEKDAYS, sidebar::sidebar, InputFormField, InputFormFieldError, Widget};
 
 #[derive(Debug, Clone)]
 pub enum UpdateNutritionTargetMessage {
@@ -16,6 +16,19 @@ pub enum UpdateNutritionTargetMessage {
     UpdateProteins(String),
     UpdateCarbohydrates(String),
     Submit,
+
+    SelectWeekday(Weekday),
+    UpdateWeekdayCalories(String),
+    UpdateWeekdayFats(String),
+    UpdateWeekdayProteins(String),
+    UpdateWeekdayCarbohydrates(String),
+    SubmitWeekday,
+    DeleteWeekday(u32),
+
+    UpdateMealName(String),
+    UpdateMealShare(String),
+    SubmitMealBudget,
+    DeleteMealBudget(String),
 }
 
 impl From<UpdateNutritionTargetMessage> for Message {
@@ -31,16 +44,33 @@ pub struct UpdateNutritionTarget {
     fats: InputFormField<f32>,
     proteins: InputFormField<f32>,
     carbohydrates: InputFormField<f32>,
+    units: Units,
+
+    weekdays: Vec<WeekdayTarget>,
+    weekday: Weekday,
+    weekday_calories: InputFormField<f32>,
+    weekday_fats: InputFormField<f32>,
+    weekday_proteins: InputFormField<f32>,
+    weekday_carbohydrates: InputFormField<f32>,
+
+    budgets: Vec<MealBudget>,
+    meal_name: InputFormField<String>,
+    meal_share: InputFormField<f32>,
 }
 
 impl UpdateNutritionTarget {
-    pub fn new(t: NutritionTarget) -> Self {
+    pub fn new(
+        t: NutritionTarget,
+        weekdays: Vec<WeekdayTarget>,
+        budgets: Vec<MealBudget>,
+        units: Units,
+    ) -> Self {
         UpdateNutritionTarget {
             day: t.day,
             calories: InputFormField::new_with_raw_value(
-                "Calories* (kcal/day)",
+                &format!("Calories* ({}/day)", units.energy),
                 "2500.0",
-                t.calories.to_string().as_str(),
+                format!("{:.1}", units.energy.from_canonical(t.calories)).as_str(),
             ),
             fats: InputFormField::new_with_raw_value(
                 "Fats* (g/day)",
@@ -57,7 +87,170 @@ impl UpdateNutritionTarget {
                 "245.0",
                 t.carbohydrates.to_string().as_str(),
             ),
+            units,
+            weekdays,
+            weekday: Weekday::Mon,
+            weekday_calories: InputFormField::new(
+                &format!("Calories* ({}/day)", units.energy),
+                "2500.0",
+            ),
+            weekday_fats: InputFormField::new("Fats* (g/day)", "80.0"),
+            weekday_proteins: InputFormField::new("Proteins* (g/day)", "200.0"),
+            weekday_carbohydrates: InputFormField::new("Carbohydrates* (g/day)", "245.0"),
+            budgets,
+            meal_name: InputFormField::new("Meal*", "Breakfast"),
+            meal_share: InputFormField::new("Share* (% of the day)", "25"),
+        }
+    }
+
+    fn parse_weekday(&mut self) -> Result<WeekdayTarget, String> {
+        for field in [
+            &mut self.weekday_calories,
+            &mut self.weekday_fats,
+            &mut self.weekday_proteins,
+            &mut self.weekday_carbohydrates,
+        ] {
+            field.validate(non_negative);
+        }
+
+        Ok(WeekdayTarget {
+            day: self.day,
+            weekday: self.weekday.num_days_from_monday(),
+            calories: self
+                .units
+                .energy
+                .to_canonical(self.weekday_calories.value.ok_or("validation failed")?),
+            fats: self.weekday_fats.value.ok_or("validation failed")?,
+            proteins: self.weekday_proteins.value.ok_or("validation failed")?,
+            carbohydrates: self
+                .weekday_carbohydrates
+                .value
+                .ok_or("validation failed")?,
+        })
+    }
+
+    fn parse_meal_budget(&mut self) -> Result<MealBudget, String> {
+        self.meal_name.validate(|input| {
+            let name = input.trim();
+            if name.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                Ok(name.to_string())
+            }
+        });
+        self.meal_share.validate(|input| match non_negative(input) {
+            Ok(val) if val > 100.0 => Err(InputFormFieldError::Custom(
+                "Share can't be more than 100%".to_string(),
+            )),
+            result => result,
+        });
+
+        Ok(MealBudget {
+            day: self.day,
+            meal: self.meal_name.value.clone().ok_or("validation failed")?,
+            share: self.meal_share.value.ok_or("validation failed")?,
+        })
+    }
+
+    fn reload_schedule(&mut self, ctx: &Context) {
+        self.weekdays = match ctx.services.nutrition_target.list_weekdays(self.day) {
+            Ok(w) => w,
+            Err(err) => {
+                tracing::error!("Failed to get weekday targets: {}", err);
+                std::process::exit(1);
+            }
+        };
+        self.budgets = match ctx.services.nutrition_target.list_meal_budgets(self.day) {
+            Ok(b) => b,
+            Err(err) => {
+                tracing::error!("Failed to get meal budgets: {}", err);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    fn schedule_view(&self) -> Element<'_, Message> {
+        let energy = self.units.energy;
+
+        let mut weekdays = column![
+            Text::new("Weekday targets").size(20),
+            Text::new("Replace the target on chosen weekdays, for example on training days"),
+        ]
+        .spacing(10);
+        for w in &self.weekdays {
+            weekdays = weekdays.push(
+                row![
+                    Text::new(WEEKDAYS[w.weekday as usize].to_string()).width(50),
+                    Text::new(format!(
+                        "{:.0}{energy}, {:.1}g fats, {:.1}g proteins, {:.1}g carbohydrates",
+                        energy.from_canonical(w.calories),
+                        w.fats,
+                        w.proteins,
+                        w.carbohydrates
+                    ))
+                    .width(Length::Fill),
+                    Button::new("Delete")
+                        .style(button::danger)
+                        .on_press(UpdateNutritionTargetMessage::DeleteWeekday(w.weekday).into()),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
         }
+        weekdays = weekdays.push(
+            column![
+                pick_list(WEEKDAYS, Some(self.weekday), |d| {
+                    UpdateNutritionTargetMessage::SelectWeekday(d).into()
+                }),
+                self.weekday_calories
+                    .view(|c| UpdateNutritionTargetMessage::UpdateWeekdayCalories(c).into()),
+                self.weekday_fats
+                    .view(|f| UpdateNutritionTargetMessage::UpdateWeekdayFats(f).into()),
+                self.weekday_proteins
+                    .view(|p| UpdateNutritionTargetMessage::UpdateWeekdayProteins(p).into()),
+                self.weekday_carbohydrates.view_with_submit(
+                    |c| UpdateNutritionTargetMessage::UpdateWeekdayCarbohydrates(c).into(),
+                    UpdateNutritionTargetMessage::SubmitWeekday.into()
+                ),
+                Button::new("Set Weekday Target")
+                    .on_press(UpdateNutritionTargetMessage::SubmitWeekday.into()),
+            ]
+            .spacing(10),
+        );
+
+        let mut budgets = column![
+            Text::new("Meal budgets").size(20),
+            Text::new("Split the day target between meals, shares can add up to at most 100%"),
+        ]
+        .spacing(10);
+        for b in &self.budgets {
+            budgets = budgets.push(
+                row![
+                    Text::new(&b.meal).width(Length::Fill),
+                    Text::new(format!("{:.1}%", b.share)).width(Length::Fill),
+                    Button::new("Delete").style(button::danger).on_press(
+                        UpdateNutritionTargetMessage::DeleteMealBudget(b.meal.clone()).into()
+                    ),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            );
+        }
+        budgets = budgets.push(
+            column![
+                self.meal_name
+                    .view(|n| UpdateNutritionTargetMessage::UpdateMealName(n).into()),
+                self.meal_share.view_with_submit(
+                    |s| UpdateNutritionTargetMessage::UpdateMealShare(s).into(),
+                    UpdateNutritionTargetMessage::SubmitMealBudget.into()
+                ),
+                Button::new("Set Meal Budget")
+                    .on_press(UpdateNutritionTargetMessage::SubmitMealBudget.into()),
+            ]
+            .spacing(10),
+        );
+
+        column![weekdays, budgets].spacing(20).into()
     }
 
     pub fn parse(&mut self) -> Result<NutritionTarget, String> {
@@ -111,7 +304,10 @@ impl UpdateNutritionTarget {
 
         Ok(NutritionTarget {
             day: self.day,
-            calories: self.calories.value.ok_or("validation failed")?,
+            calories: self
+                .units
+                .energy
+                .to_canonical(self.calories.value.ok_or("validation failed")?),
             fats: self.fats.value.ok_or("validation failed")?,
             proteins: self.proteins.value.ok_or("validation failed")?,
             carbohydrates: self.carbohydrates.value.ok_or("validation failed")?,
@@ -120,7 +316,7 @@ impl UpdateNutritionTarget {
 }
 
 impl Widget for UpdateNutritionTarget {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![
             self.calories
                 .view(|c| { UpdateNutritionTargetMessage::UpdateCalories(c).into() }),
@@ -140,9 +336,12 @@ impl Widget for UpdateNutritionTarget {
             ))
             .size(40),
             form,
-            Button::new("Update").on_press(UpdateNutritionTargetMessage::Submit.into())
+            Button::new("Update").on_press(UpdateNutritionTargetMessage::Submit.into()),
+            self.schedule_view(),
         ]
-        .spacing(10);
+        .spacing(10)
+        .width(600);
+        let content = Scrollable::new(content).height(Length::Fill);
 
         row![sidebar(), content]
             .height(Length::Fill)
@@ -175,9 +374,91 @@ impl Widget for UpdateNutritionTarget {
                         ctx.next_widget = Some(NextWidget::NutritionTargetList);
                     };
                 }
+                UpdateNutritionTargetMessage::SelectWeekday(weekday) => {
+                    self.weekday = weekday;
+                }
+                UpdateNutritionTargetMessage::UpdateWeekdayCalories(raw_calories) => {
+                    self.weekday_calories.raw_input = raw_calories;
+                }
+                UpdateNutritionTargetMessage::UpdateWeekdayFats(raw_fats) => {
+                    self.weekday_fats.raw_input = raw_fats;
+                }
+                UpdateNutritionTargetMessage::UpdateWeekdayProteins(raw_proteins) => {
+                    self.weekday_proteins.raw_input = raw_proteins;
+                }
+                UpdateNutritionTargetMessage::UpdateWeekdayCarbohydrates(raw_carbohydrates) => {
+                    self.weekday_carbohydrates.raw_input = raw_carbohydrates;
+                }
+                UpdateNutritionTargetMessage::SubmitWeekday => {
+                    if let Ok(weekday) = self.parse_weekday() {
+                        if let Err(err) = ctx.services.nutrition_target.set_weekday(weekday) {
+                            tracing::error!("Failed to set weekday target: {}", err);
+                            std::process::exit(1);
+                        }
+                        self.reload_schedule(ctx);
+                    }
+                }
+                UpdateNutritionTargetMessage::DeleteWeekday(weekday) => {
+                    if let Err(err) = ctx
+                        .services
+                        .nutrition_target
+                        .delete_weekday(self.day, weekday)
+                    {
+                        tracing::error!("Failed to delete weekday target: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.reload_schedule(ctx);
+                }
+                UpdateNutritionTargetMessage::UpdateMealName(raw_name) => {
+                    self.meal_name.raw_input = raw_name;
+                }
+                UpdateNutritionTargetMessage::UpdateMealShare(raw_share) => {
+                    self.meal_share.raw_input = raw_share;
+                }
+                UpdateNutritionTargetMessage::SubmitMealBudget => {
+                    if let Ok(budget) = self.parse_meal_budget() {
+                        match ctx.services.nutrition_target.set_meal_budget(budget) {
+                            Ok(()) => {
+                                self.meal_name.raw_input.clear();
+                                self.meal_share.raw_input.clear();
+                                self.reload_schedule(ctx);
+                            }
+                            Err(ServiceError::Custom(msg)) => {
+                                self.meal_share.error = Some(InputFormFieldError::Custom(msg));
+                            }
+                            Err(err) => {
+                                tracing::error!("Failed to set meal budget: {}", err);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
+                UpdateNutritionTargetMessage::DeleteMealBudget(meal) => {
+                    if let Err(err) = ctx
+                        .services
+                        .nutrition_target
+                        .delete_meal_budget(self.day, &meal)
+                    {
+                        tracing::error!("Failed to delete meal budget: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.reload_schedule(ctx);
+                }
             }
         };
 
         Task::none()
     }
 }
+
+fn non_negative(input: &str) -> Result<f32, InputFormFieldError> {
+    if input.is_empty() {
+        Err(InputFormFieldError::MissingRequiredValue)
+    } else {
+        match input.parse::<f32>() {
+            Err(_) => Err(InputFormFieldError::InvalidNumber),
+            Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+            Ok(val) => Ok(val),
+        }
+    }
+}
@@ -1,21 +1,35 @@
-use chomp_services::{CreateUpdateProduct, Product, ServiceError};
+use chomp_services::{
+    is_valid_barcode, CreateUpdateProduct, Nutrient, NutrientAmount, Product, ServiceError,
+    SetProductNutrient, Units,
+};
 use iced::{
-    widget::{column, row, Button, Text},
+    widget::{column, row, Button, Scrollable, Text},
     Element, Length, Task,
 };
 
 use crate::app::{Context, Message, NextWidget};
 
-use super::{sidebar::sidebar, InputFormField, InputFormFieldError, Widget};
+use super::{
+    sidebar::sidebar, taken_product_name_error, InputFormField, InputFormFieldError, Widget,
+};
+
+type NutrientId = usize;
 
 #[derive(Debug, Clone)]
 pub enum UpdateProductMessage {
     UpdateName(String),
     UpdateCompany(String),
+    UpdateBarcode(String),
+    UpdateAliases(String),
     UpdateCalories(String),
     UpdateFats(String),
     UpdateProteins(String),
     UpdateCarbohydrates(String),
+    UpdateFiber(String),
+    UpdateSugar(String),
+    UpdateSaturatedFats(String),
+    UpdateSalt(String),
+    UpdateNutrient(NutrientId, String),
     Submit,
 }
 
@@ -30,14 +44,28 @@ pub struct UpdateProduct {
     product_id: usize,
     name: InputFormField<String>,
     company: InputFormField<Option<String>>,
+    barcode: InputFormField<Option<String>>,
+    aliases: InputFormField<Vec<String>>,
     calories: InputFormField<f32>,
     fats: InputFormField<f32>,
     proteins: InputFormField<f32>,
     carbohydrates: InputFormField<f32>,
+    fiber: InputFormField<Option<f32>>,
+    sugar: InputFormField<Option<f32>>,
+    saturated_fats: InputFormField<Option<f32>>,
+    salt: InputFormField<Option<f32>>,
+    nutrients: Vec<(Nutrient, InputFormField<Option<f32>>)>,
+    units: Units,
 }
 
 impl UpdateProduct {
-    pub fn new(p: Product) -> Self {
+    pub fn new(
+        p: Product,
+        nutrients: Vec<Nutrient>,
+        product_nutrients: Vec<NutrientAmount>,
+        aliases: Vec<String>,
+        units: Units,
+    ) -> Self {
         UpdateProduct {
             product_id: p.id,
             name: InputFormField::new_with_raw_value("Name*", "Chicken", &p.name),
@@ -46,10 +74,20 @@ impl UpdateProduct {
                 "Chicken Inc.",
                 &p.company.clone().unwrap_or("".to_string()),
             ),
+            barcode: InputFormField::new_with_raw_value(
+                "Barcode (EAN/UPC)",
+                "5901234123457",
+                &p.barcode.clone().unwrap_or("".to_string()),
+            ),
+            aliases: InputFormField::new_with_raw_value(
+                "Aliases (comma separated)",
+                "PB, peanut paste",
+                &aliases.join(", "),
+            ),
             calories: InputFormField::new_with_raw_value(
-                "Calories* (kcal)",
+                &format!("Calories* ({})", units.energy),
                 "100.0",
-                &p.calories.to_string(),
+                &format!("{:.1}", units.energy.from_canonical(p.calories)),
             ),
             fats: InputFormField::new_with_raw_value("Fats* (g)", "2.0", &p.fats.to_string()),
             proteins: InputFormField::new_with_raw_value(
@@ -62,6 +100,43 @@ impl UpdateProduct {
                 "1.0",
                 &p.carbohydrates.to_string(),
             ),
+            fiber: InputFormField::new_with_raw_value(
+                "Fiber (g)",
+                "0.5",
+                &p.fiber.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            sugar: InputFormField::new_with_raw_value(
+                "Sugar (g)",
+                "0.5",
+                &p.sugar.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            saturated_fats: InputFormField::new_with_raw_value(
+                "Saturated fats (g)",
+                "0.5",
+                &p.saturated_fats.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            salt: InputFormField::new_with_raw_value(
+                "Salt (g)",
+                "0.1",
+                &p.salt.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            nutrients: nutrients
+                .into_iter()
+                .map(|n| {
+                    let amount = product_nutrients
+                        .iter()
+                        .find(|pn| pn.nutrient_id == n.id)
+                        .map(|pn| pn.amount.to_string())
+                        .unwrap_or_default();
+                    let field = InputFormField::new_with_raw_value(
+                        &format!("{} ({})", n.name, n.unit),
+                        "0.0",
+                        &amount,
+                    );
+                    (n, field)
+                })
+                .collect(),
+            units,
         }
     }
 
@@ -84,6 +159,27 @@ impl UpdateProduct {
             }
         });
 
+        self.barcode.validate(|input| {
+            let input = input.trim();
+            if input.is_empty() {
+                Ok(None)
+            } else if !is_valid_barcode(input) {
+                Err(InputFormFieldError::Custom(
+                    "Invalid barcode, expected EAN-8, UPC-A, EAN-13 or GTIN-14".to_string(),
+                ))
+            } else {
+                Ok(Some(input.to_string()))
+            }
+        });
+
+        self.aliases.validate(|input| {
+            Ok(input
+                .split(',')
+                .map(|alias| alias.trim().to_string())
+                .filter(|alias| !alias.is_empty())
+                .collect())
+        });
+
         self.calories.validate(|input| {
             if input.is_empty() {
                 Err(InputFormFieldError::MissingRequiredValue)
@@ -132,24 +228,109 @@ impl UpdateProduct {
             }
         });
 
+        self.fiber.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.sugar.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.saturated_fats.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        self.salt.validate(|input| {
+            if input.is_empty() {
+                Ok(None)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(Some(val)),
+                }
+            }
+        });
+
+        for (_, field) in self.nutrients.iter_mut() {
+            field.validate(|input| {
+                if input.is_empty() {
+                    Ok(None)
+                } else {
+                    match input.parse::<f32>() {
+                        Err(_) => Err(InputFormFieldError::InvalidNumber),
+                        Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                        Ok(val) => Ok(Some(val)),
+                    }
+                }
+            });
+        }
+
+        let mut nutrients = Vec::new();
+        for (nutrient, field) in self.nutrients.iter() {
+            if let Some(amount) = field.value.ok_or("validation failed")? {
+                nutrients.push(SetProductNutrient {
+                    nutrient_id: nutrient.id,
+                    amount,
+                });
+            }
+        }
+
         Ok(CreateUpdateProduct {
             name: self.name.value.clone().ok_or("validation failed")?,
             company: self.company.value.clone().ok_or("validation failed")?,
-            calories: self.calories.value.ok_or("validation failed")?,
+            calories: self
+                .units
+                .energy
+                .to_canonical(self.calories.value.ok_or("validation failed")?),
             fats: self.fats.value.ok_or("validation failed")?,
             proteins: self.proteins.value.ok_or("validation failed")?,
             carbohydrates: self.carbohydrates.value.ok_or("validation failed")?,
+            fiber: self.fiber.value.ok_or("validation failed")?,
+            sugar: self.sugar.value.ok_or("validation failed")?,
+            saturated_fats: self.saturated_fats.value.ok_or("validation failed")?,
+            salt: self.salt.value.ok_or("validation failed")?,
+            barcode: self.barcode.value.clone().ok_or("validation failed")?,
+            nutrients,
         })
     }
 }
 
 impl Widget for UpdateProduct {
-    fn view(&self) -> Element<Message> {
-        let form = column![
+    fn view(&self) -> Element<'_, Message> {
+        let mut form = column![
             self.name
                 .view(|n| { UpdateProductMessage::UpdateName(n).into() }),
             self.company
                 .view(|c| { UpdateProductMessage::UpdateCompany(c).into() }),
+            self.barcode
+                .view(|b| { UpdateProductMessage::UpdateBarcode(b).into() }),
+            self.aliases
+                .view(|a| { UpdateProductMessage::UpdateAliases(a).into() }),
             self.calories
                 .view(|c| { UpdateProductMessage::UpdateCalories(c).into() }),
             self.fats
@@ -158,12 +339,27 @@ impl Widget for UpdateProduct {
                 .view(|p| { UpdateProductMessage::UpdateProteins(p).into() }),
             self.carbohydrates
                 .view(|c| { UpdateProductMessage::UpdateCarbohydrates(c).into() }),
+            self.fiber
+                .view(|f| { UpdateProductMessage::UpdateFiber(f).into() }),
+            self.sugar
+                .view(|s| { UpdateProductMessage::UpdateSugar(s).into() }),
+            self.saturated_fats
+                .view(|s| { UpdateProductMessage::UpdateSaturatedFats(s).into() }),
+            self.salt
+                .view(|s| { UpdateProductMessage::UpdateSalt(s).into() }),
         ]
         .spacing(10);
 
+        for (nutrient, field) in self.nutrients.iter() {
+            let nutrient_id = nutrient.id;
+            form = form.push(
+                field.view(move |a| UpdateProductMessage::UpdateNutrient(nutrient_id, a).into()),
+            );
+        }
+
         let content = column![
             Text::new("Update product").size(40),
-            form,
+            Scrollable::new(form).height(Length::Fill),
             Button::new("Update").on_press(UpdateProductMessage::Submit.into())
         ]
         .spacing(10);
@@ -184,6 +380,12 @@ impl Widget for UpdateProduct {
                 UpdateProductMessage::UpdateCompany(company) => {
                     self.company.raw_input = company;
                 }
+                UpdateProductMessage::UpdateBarcode(barcode) => {
+                    self.barcode.raw_input = barcode;
+                }
+                UpdateProductMessage::UpdateAliases(aliases) => {
+                    self.aliases.raw_input = aliases;
+                }
                 UpdateProductMessage::UpdateCalories(raw_calories) => {
                     self.calories.raw_input = raw_calories;
                 }
@@ -196,6 +398,25 @@ impl Widget for UpdateProduct {
                 UpdateProductMessage::UpdateCarbohydrates(raw_carbohydrates) => {
                     self.carbohydrates.raw_input = raw_carbohydrates;
                 }
+                UpdateProductMessage::UpdateFiber(raw_fiber) => {
+                    self.fiber.raw_input = raw_fiber;
+                }
+                UpdateProductMessage::UpdateSugar(raw_sugar) => {
+                    self.sugar.raw_input = raw_sugar;
+                }
+                UpdateProductMessage::UpdateSaturatedFats(raw_saturated_fats) => {
+                    self.saturated_fats.raw_input = raw_saturated_fats;
+                }
+                UpdateProductMessage::UpdateSalt(raw_salt) => {
+                    self.salt.raw_input = raw_salt;
+                }
+                UpdateProductMessage::UpdateNutrient(nutrient_id, raw_amount) => {
+                    if let Some((_, field)) =
+                        self.nutrients.iter_mut().find(|(n, _)| n.id == nutrient_id)
+                    {
+                        field.raw_input = raw_amount;
+                    }
+                }
                 UpdateProductMessage::Submit => {
                     if let Ok(product) = self.parse() {
                         if let Some(err) =
@@ -205,8 +426,16 @@ impl Widget for UpdateProduct {
                                 ServiceError::UniqueConstraintViolation(unique_field)
                                     if unique_field == "products.name" =>
                                 {
-                                    self.name.error = Some(InputFormFieldError::Custom(
-                                        "Product with this name already exists".to_string(),
+                                    self.name.error = Some(taken_product_name_error(
+                                        &ctx.services,
+                                        &self.name.raw_input,
+                                    ))
+                                }
+                                ServiceError::UniqueConstraintViolation(unique_field)
+                                    if unique_field == "products.barcode" =>
+                                {
+                                    self.barcode.error = Some(InputFormFieldError::Custom(
+                                        "Product with this barcode already exists".to_string(),
                                     ))
                                 }
                                 _ => {
@@ -214,6 +443,13 @@ impl Widget for UpdateProduct {
                                 }
                             }
                         } else {
+                            let aliases = self.aliases.value.clone().unwrap_or_default();
+                            if let Err(err) =
+                                ctx.services.product.set_aliases(self.product_id, &aliases)
+                            {
+                                tracing::error!("Failed to save product aliases: {}", err);
+                                std::process::exit(1);
+                            }
                             ctx.next_widget = Some(NextWidget::ProductList);
                         }
                     };
@@ -1,4 +1,4 @@
-use chomp_services::{Product, ProductPortion, ServiceError};
+use chomp_services::{Product, ProductPortion, ServiceError, Units};
 use iced::{
     widget::{column, row, Button, Text},
     Element, Length, Task,
@@ -27,19 +27,23 @@ pub struct UpdateProductPortion {
     product: Product,
     name: InputFormField<String>,
     weight: InputFormField<f32>,
+    units: Units,
 }
 
 impl UpdateProductPortion {
-    pub fn new(product: &Product, product_portion: &ProductPortion) -> Self {
+    pub fn new(product: &Product, product_portion: &ProductPortion, units: Units) -> Self {
+        let weight = units.food_weight.from_canonical(product_portion.weight);
+
         UpdateProductPortion {
             product_portion_id: product_portion.id,
             product: product.to_owned(),
             name: InputFormField::new_with_raw_value("Name*", "One package", &product_portion.name),
             weight: InputFormField::new_with_raw_value(
-                "Weight (g)*",
+                &format!("Weight ({})*", units.food_weight),
                 "100.0",
-                &product_portion.weight.to_string(),
+                &format!("{weight:.1}"),
             ),
+            units,
         }
     }
 
@@ -70,13 +74,16 @@ impl UpdateProductPortion {
             id: self.product_portion_id,
             product_id: self.product.id,
             name: self.name.value.clone().ok_or("validation failed")?,
-            weight: self.weight.value.ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
 impl Widget for UpdateProductPortion {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![
             self.name
                 .view(|n| { UpdateProductPortionMessage::UpdateName(n).into() }),
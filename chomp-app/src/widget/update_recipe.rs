@@ -0,0 +1,355 @@
+use std::collections::HashSet;
+
+use chomp_services::{
+    AddRecipeIngredient, CreateUpdateRecipe, Product, Recipe, RecipeIngredient, ServiceError, Units,
+};
+use iced::{
+    widget::{button, column, combo_box, row, Button, Container, Scrollable, Text},
+    Alignment, Color, Element, Length, Task,
+};
+
+use crate::app::{Context, Message, NextWidget};
+
+use super::{
+    sidebar::sidebar, style::TableRowStyle, taken_product_name_error, InputFormField,
+    InputFormFieldError, Widget,
+};
+
+type ProductId = usize;
+type RecipeIngredientId = usize;
+
+#[derive(Debug, Clone)]
+pub enum UpdateRecipeMessage {
+    UpdateName(String),
+    UpdateWeight(String),
+    Submit,
+    IngredientFormProduct(ProductId),
+    IngredientFormWeight(String),
+    SubmitIngredientForm,
+    DeleteIngredient(RecipeIngredientId),
+}
+
+impl From<UpdateRecipeMessage> for Message {
+    fn from(value: UpdateRecipeMessage) -> Self {
+        Message::UpdateRecipe(value)
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateRecipe {
+    recipe: Recipe,
+    name: InputFormField<String>,
+    weight: InputFormField<f32>,
+    ingredient_form: IngredientForm,
+    units: Units,
+}
+
+impl UpdateRecipe {
+    pub fn new(recipe: Recipe, products: Vec<Product>, units: Units) -> Self {
+        let weight = units.food_weight.from_canonical(recipe.weight);
+
+        UpdateRecipe {
+            name: InputFormField::new_with_raw_value("Name*", "Chili con carne", &recipe.name),
+            weight: InputFormField::new_with_raw_value(
+                &format!("Weight after cooking* ({})", units.food_weight),
+                "2500.0",
+                &format!("{weight:.1}"),
+            ),
+            ingredient_form: IngredientForm::new(products, &recipe, units),
+            recipe,
+            units,
+        }
+    }
+
+    fn refresh(&mut self, ctx: &Context) {
+        self.recipe = match ctx.services.recipe.read(self.recipe.id) {
+            Ok(r) => r,
+            Err(err) => {
+                tracing::error!("Failed to get recipe: {}", err);
+                std::process::exit(1);
+            }
+        };
+        let products = ctx.services.product.list().unwrap_or_default();
+        self.ingredient_form = IngredientForm::new(products, &self.recipe, self.units);
+    }
+
+    pub fn parse(&mut self) -> Result<CreateUpdateRecipe, String> {
+        self.name.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else if input.len() < 3 {
+                Err(InputFormFieldError::TooShort(3))
+            } else {
+                Ok(input.to_string())
+            }
+        });
+
+        self.weight.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val <= 0.0 => Err(InputFormFieldError::Custom(
+                        "Invalid number. Must be greater than zero".to_string(),
+                    )),
+                    Ok(val) => Ok(val),
+                }
+            }
+        });
+
+        Ok(CreateUpdateRecipe {
+            name: self.name.value.clone().ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
+        })
+    }
+}
+
+impl Widget for UpdateRecipe {
+    fn view(&self) -> Element<'_, Message> {
+        let form = column![
+            self.name
+                .view(|n| { UpdateRecipeMessage::UpdateName(n).into() }),
+            self.weight
+                .view(|w| { UpdateRecipeMessage::UpdateWeight(w).into() }),
+        ]
+        .spacing(10);
+
+        let mut table = column![list_header_row(self.units)];
+        for (i, ingredient) in self.recipe.ingredients.iter().enumerate() {
+            table = table.push(list_row(ingredient, self.units, i % 2 == 0))
+        }
+
+        let content = column![
+            Text::new(format!("Update recipe {}", self.recipe.name)).size(40),
+            form,
+            Button::new("Update").on_press(UpdateRecipeMessage::Submit.into()),
+            Text::new(format!(
+                "Ingredients (per 100g: {:.1} kcal, {:.1}g fats, {:.1}g proteins, {:.1}g carbohydrates)",
+                self.recipe.calories,
+                self.recipe.fats,
+                self.recipe.proteins,
+                self.recipe.carbohydrates
+            ))
+            .size(20),
+            render_ingredient_form(&self.ingredient_form),
+            Scrollable::new(table)
+        ]
+        .spacing(10);
+
+        row![sidebar(), content]
+            .height(Length::Fill)
+            .padding(20)
+            .spacing(20)
+            .into()
+    }
+
+    fn update(&mut self, ctx: &mut Context, msg: Message) -> Task<Message> {
+        if let Message::UpdateRecipe(msg) = msg {
+            match msg {
+                UpdateRecipeMessage::UpdateName(name) => {
+                    self.name.raw_input = name;
+                }
+                UpdateRecipeMessage::UpdateWeight(raw_weight) => {
+                    self.weight.raw_input = raw_weight;
+                }
+                UpdateRecipeMessage::Submit => {
+                    if let Ok(recipe) = self.parse() {
+                        if let Some(err) = ctx.services.recipe.update(self.recipe.id, recipe).err()
+                        {
+                            match err {
+                                ServiceError::UniqueConstraintViolation(unique_field)
+                                    if unique_field == "products.name" =>
+                                {
+                                    self.name.error = Some(taken_product_name_error(
+                                        &ctx.services,
+                                        &self.name.raw_input,
+                                    ))
+                                }
+                                _ => {
+                                    eprintln!("Error: {err:?}");
+                                }
+                            }
+                        } else {
+                            ctx.next_widget = Some(NextWidget::RecipeList);
+                        }
+                    };
+                }
+                UpdateRecipeMessage::IngredientFormProduct(product_id) => {
+                    self.ingredient_form.product_id = Some(product_id);
+                }
+                UpdateRecipeMessage::IngredientFormWeight(raw_weight) => {
+                    self.ingredient_form.weight.raw_input = raw_weight;
+                }
+                UpdateRecipeMessage::SubmitIngredientForm => {
+                    match self.ingredient_form.parse(self.recipe.id) {
+                        Ok(ingredient) => match ctx.services.recipe.add_ingredient(ingredient) {
+                            Ok(_) => self.refresh(ctx),
+                            Err(ServiceError::Custom(err)) => {
+                                self.ingredient_form.combo_box_error =
+                                    Some(InputFormFieldError::Custom(err));
+                            }
+                            Err(err) => {
+                                tracing::error!("Failed to add recipe ingredient: {}", err);
+                                std::process::exit(1);
+                            }
+                        },
+                        Err(err) => {
+                            tracing::warn!("Failed to parse recipe ingredient form: {}", err)
+                        }
+                    }
+                }
+                UpdateRecipeMessage::DeleteIngredient(ingredient_id) => {
+                    if let Err(err) = ctx.services.recipe.delete_ingredient(ingredient_id) {
+                        tracing::error!("Failed to delete recipe ingredient: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.refresh(ctx);
+                }
+            }
+        };
+
+        Task::none()
+    }
+}
+
+fn list_header_row(units: Units) -> Element<'static, Message> {
+    let row = row![
+        Text::new("Name").width(Length::Fill),
+        Text::new(format!("Weight ({})", units.food_weight)).width(Length::Fill),
+        Text::new("Calories (kcal)").width(Length::Fill),
+        Text::new("Fats (g)").width(Length::Fill),
+        Text::new("Proteins (g)").width(Length::Fill),
+        Text::new("Carbohydrates (g)").width(Length::Fill),
+        Text::new("Actions").width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row).width(Length::Fill).into()
+}
+
+fn list_row(i: &RecipeIngredient, units: Units, even: bool) -> Element<'_, Message> {
+    let row = row![
+        Text::new(&i.name).width(Length::Fill),
+        Text::new(format!("{:.1}", units.food_weight.from_canonical(i.weight))).width(Length::Fill),
+        Text::new(format!("{:.1}", i.calories)).width(Length::Fill),
+        Text::new(format!("{:.1}", i.fats)).width(Length::Fill),
+        Text::new(format!("{:.1}", i.proteins)).width(Length::Fill),
+        Text::new(format!("{:.1}", i.carbohydrates)).width(Length::Fill),
+        row![Button::new("Delete")
+            .style(button::danger)
+            .on_press(UpdateRecipeMessage::DeleteIngredient(i.id).into())]
+        .spacing(10)
+        .width(Length::Fill)
+    ]
+    .padding(10)
+    .width(Length::Fill);
+
+    Container::new(row)
+        .width(Length::Fill)
+        .style(move |t| {
+            if even {
+                TableRowStyle::Even.style(t)
+            } else {
+                TableRowStyle::Odd.style(t)
+            }
+        })
+        .into()
+}
+
+#[derive(Debug)]
+pub struct IngredientForm {
+    pub combo_box_state: combo_box::State<Product>,
+    pub combo_box_error: Option<InputFormFieldError>,
+    pub weight: InputFormField<f32>,
+    pub product_id: Option<usize>,
+    pub units: Units,
+}
+
+impl IngredientForm {
+    pub fn new(products: Vec<Product>, recipe: &Recipe, units: Units) -> Self {
+        let ingredient_ids: HashSet<usize> =
+            recipe.ingredients.iter().map(|i| i.product_id).collect();
+
+        let mut available_products: Vec<Product> = products
+            .into_iter()
+            .filter(|p| p.id != recipe.product_id && !ingredient_ids.contains(&p.id))
+            .collect();
+
+        available_products.sort_by_key(|p| p.name.to_lowercase());
+
+        IngredientForm {
+            combo_box_state: combo_box::State::new(available_products),
+            combo_box_error: None,
+            weight: InputFormField::new(&format!("Weight ({})", units.food_weight), "100.0"),
+            product_id: None,
+            units,
+        }
+    }
+
+    pub fn parse(&mut self, recipe_id: usize) -> Result<AddRecipeIngredient, String> {
+        self.weight.validate(|input| {
+            if input.is_empty() {
+                Err(InputFormFieldError::MissingRequiredValue)
+            } else {
+                match input.parse::<f32>() {
+                    Err(_) => Err(InputFormFieldError::InvalidNumber),
+                    Ok(val) if val < 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                    Ok(val) => Ok(val),
+                }
+            }
+        });
+
+        self.combo_box_error = None;
+        if self.product_id.is_none() {
+            self.combo_box_error = Some(InputFormFieldError::MissingRequiredValue);
+        }
+
+        Ok(AddRecipeIngredient {
+            recipe_id,
+            product_id: self.product_id.ok_or("validation failed")?,
+            weight: self
+                .units
+                .food_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
+        })
+    }
+}
+
+fn render_ingredient_form(form: &IngredientForm) -> Element<'_, Message> {
+    let selected_product = match &form.product_id {
+        Some(id) => form.combo_box_state.options().iter().find(|p| p.id == *id),
+        None => None,
+    };
+
+    let mut product_column = column![
+        Text::new("Product"),
+        combo_box(
+            &form.combo_box_state,
+            "Search product...",
+            selected_product,
+            |p| UpdateRecipeMessage::IngredientFormProduct(p.id).into(),
+        )
+    ]
+    .spacing(2)
+    .width(Length::Fill);
+
+    if let Some(err) = &form.combo_box_error {
+        product_column =
+            product_column.push(Text::new(err.to_string()).color(Color::from_rgb(1.0, 0.0, 0.0)));
+    }
+
+    row![
+        product_column,
+        form.weight
+            .view(|w| UpdateRecipeMessage::IngredientFormWeight(w).into()),
+        Button::new("Add Ingredient").on_press(UpdateRecipeMessage::SubmitIngredientForm.into())
+    ]
+    .spacing(10)
+    .align_y(Alignment::End)
+    .into()
+}
@@ -1,4 +1,4 @@
-use chomp_services::Weight;
+use chomp_services::{Units, Weight};
 use chrono::NaiveDate;
 use iced::{
     widget::{column, row, Button, Text},
@@ -25,17 +25,21 @@ impl From<UpdateWeightMessage> for Message {
 pub struct UpdateWeight {
     day: NaiveDate,
     weight: InputFormField<f32>,
+    units: Units,
 }
 
 impl UpdateWeight {
-    pub fn new(w: Weight) -> Self {
+    pub fn new(w: Weight, units: Units) -> Self {
+        let weight = units.body_weight.from_canonical(w.weight);
+
         UpdateWeight {
             day: w.day,
             weight: InputFormField::new_with_raw_value(
-                "Weight* (g)",
+                &format!("Weight* ({})", units.body_weight),
                 "80.1",
-                w.weight.to_string().as_str(),
+                format!("{weight:.1}").as_str(),
             ),
+            units,
         }
     }
 
@@ -54,13 +58,16 @@ impl UpdateWeight {
 
         Ok(Weight {
             day: self.day,
-            weight: self.weight.value.ok_or("validation failed")?,
+            weight: self
+                .units
+                .body_weight
+                .to_canonical(self.weight.value.ok_or("validation failed")?),
         })
     }
 }
 
 impl Widget for UpdateWeight {
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let form = column![self
             .weight
             .view(|w| { UpdateWeightMessage::UpdateWeight(w).into() }),]
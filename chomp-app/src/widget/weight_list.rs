@@ -1,4 +1,4 @@
-use chomp_services::Weight;
+use chomp_services::{BodyWeightUnit, Units, WeightAnalysis, WeightTrend};
 use chrono::NaiveDate;
 use iced::{
     widget::{button, column, row, Button, Container, Scrollable, Text},
@@ -7,12 +7,15 @@ use iced::{
 
 use crate::app::{Context, Message, NextWidget};
 
-use super::{sidebar::sidebar, style::TableRowStyle, Widget};
+use super::{sidebar::sidebar, style::TableRowStyle, InputFormField, InputFormFieldError, Widget};
 
 #[derive(Debug, Clone)]
 pub enum WeightListMessage {
     RedirectToCreate,
     DeleteWeight(NaiveDate),
+    UpdateGoal(String),
+    SubmitGoal,
+    ClearGoal,
 }
 
 impl From<WeightListMessage> for Message {
@@ -23,24 +26,41 @@ impl From<WeightListMessage> for Message {
 
 #[derive(Debug)]
 pub struct WeightList {
-    weights: Vec<Weight>,
+    weights: WeightAnalysis,
+    goal: InputFormField<f32>,
+    units: Units,
 }
 
 impl WeightList {
-    pub fn new(weights: Vec<Weight>) -> Self {
-        WeightList { weights }
+    pub fn new(weights: WeightAnalysis, units: Units) -> Self {
+        let goal = weights
+            .goal
+            .map(|g| format!("{:.1}", units.body_weight.from_canonical(g)))
+            .unwrap_or_default();
+
+        WeightList {
+            weights,
+            goal: InputFormField::new_with_raw_value(&goal_label(units), "75.0", &goal),
+            units,
+        }
     }
 
     fn refresh(&mut self, ctx: &Context) {
-        self.weights = ctx.services.weight.list().unwrap_or_default();
+        self.weights = match ctx.services.weight.analysis() {
+            Ok(w) => w,
+            Err(err) => {
+                tracing::error!("Failed to analyse weights: {}", err);
+                std::process::exit(1);
+            }
+        };
     }
 }
 
 impl Widget for WeightList {
-    fn view(&self) -> Element<Message> {
-        let mut table = column![list_header_row()];
-        for (i, weight) in self.weights.iter().enumerate() {
-            table = table.push(list_row(weight, i % 2 == 0))
+    fn view(&self) -> Element<'_, Message> {
+        let mut table = column![list_header_row(self.units.body_weight)];
+        for (i, weight) in self.weights.trend.iter().rev().enumerate() {
+            table = table.push(list_row(weight, self.units.body_weight, i % 2 == 0))
         }
 
         let content = column![
@@ -50,6 +70,18 @@ impl Widget for WeightList {
             ]
             .spacing(10)
             .align_y(Alignment::Center),
+            row![
+                self.goal.view_with_submit(
+                    |g| WeightListMessage::UpdateGoal(g).into(),
+                    WeightListMessage::SubmitGoal.into()
+                ),
+                Button::new("Set").on_press(WeightListMessage::SubmitGoal.into()),
+                Button::new("Clear")
+                    .style(button::secondary)
+                    .on_press(WeightListMessage::ClearGoal.into())
+            ]
+            .spacing(10)
+            .align_y(Alignment::End),
             Scrollable::new(table)
         ]
         .spacing(10);
@@ -74,6 +106,30 @@ impl Widget for WeightList {
                     }
                     self.refresh(ctx);
                 }
+                WeightListMessage::UpdateGoal(raw_goal) => self.goal.raw_input = raw_goal,
+                WeightListMessage::SubmitGoal => {
+                    self.goal.validate(|input| match input.parse::<f32>() {
+                        Err(_) => Err(InputFormFieldError::InvalidNumber),
+                        Ok(val) if val <= 0.0 => Err(InputFormFieldError::SmallerThanZero),
+                        Ok(val) => Ok(val),
+                    });
+                    if let Some(goal) = self.goal.value.filter(|_| self.goal.error.is_none()) {
+                        let goal = self.units.body_weight.to_canonical(goal);
+                        if let Err(err) = ctx.services.weight.set_goal(goal) {
+                            tracing::error!("Failed to set weight goal: {}", err);
+                            std::process::exit(1);
+                        }
+                        self.refresh(ctx);
+                    }
+                }
+                WeightListMessage::ClearGoal => {
+                    if let Err(err) = ctx.services.weight.delete_goal() {
+                        tracing::error!("Failed to clear weight goal: {}", err);
+                        std::process::exit(1);
+                    }
+                    self.goal = InputFormField::new(&goal_label(self.units), "75.0");
+                    self.refresh(ctx);
+                }
             }
         };
 
@@ -81,10 +137,15 @@ impl Widget for WeightList {
     }
 }
 
-fn list_header_row() -> Element<'static, Message> {
+fn goal_label(units: Units) -> String {
+    format!("Goal ({})", units.body_weight)
+}
+
+fn list_header_row(unit: BodyWeightUnit) -> Element<'static, Message> {
     let row = row![
         Text::new("Day").width(Length::Fill),
-        Text::new("Weight (g)").width(Length::Fill),
+        Text::new(format!("Weight ({unit})")).width(Length::Fill),
+        Text::new(format!("Trend ({unit})")).width(Length::Fill),
         Text::new("Actions").width(Length::Fill)
     ]
     .padding(10)
@@ -93,10 +154,11 @@ fn list_header_row() -> Element<'static, Message> {
     Container::new(row).width(Length::Fill).into()
 }
 
-fn list_row(w: &Weight, even: bool) -> Element<Message> {
+fn list_row(w: &WeightTrend, unit: BodyWeightUnit, even: bool) -> Element<'_, Message> {
     let row = row![
         Text::new(format!("{}", w.day.format("%Y-%m-%d")),).width(Length::Fill),
-        Text::new(format!("{:.1}", w.weight)).width(Length::Fill),
+        Text::new(format!("{:.1}", unit.from_canonical(w.weight))).width(Length::Fill),
+        Text::new(format!("{:.1}", unit.from_canonical(w.trend))).width(Length::Fill),
         row![
             Button::new("Update").on_press(Message::ChangeWidget(NextWidget::UpdateWeight(w.day))),
             Button::new("Delete")
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use chomp_services::{is_valid_barcode, Product, ServiceError, Services};
+use chrono::{Local, NaiveDate};
+use pico_args::Arguments;
+
+pub fn subcommand(args: &mut Arguments) -> Result<String, String> {
+    args.subcommand()
+        .map_err(|err| err.to_string())?
+        .ok_or("missing subcommand, see --help".to_string())
+}
+
+pub fn required<T>(args: &mut Arguments, key: &'static str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    args.value_from_str(key)
+        .map_err(|err| format!("{key}: {err}"))
+}
+
+pub fn optional<T>(args: &mut Arguments, key: &'static str) -> Result<Option<T>, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    args.opt_value_from_str(key)
+        .map_err(|err| format!("{key}: {err}"))
+}
+
+pub fn free<T>(args: &mut Arguments, name: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    args.free_from_str()
+        .map_err(|err| format!("<{name}>: {err}"))
+}
+
+pub fn day(args: &mut Arguments) -> Result<NaiveDate, String> {
+    Ok(optional(args, "--day")?.unwrap_or(Local::now().date_naive()))
+}
+
+/// Makes sure that all arguments were consumed, so that typos in options are not silently
+/// ignored.
+pub fn finish(args: Arguments) -> Result<(), String> {
+    let remaining = args.finish();
+    if remaining.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("unexpected arguments: {remaining:?}"))
+    }
+}
+
+/// Finds product by barcode, id or case insensitive name. Archived products are not found,
+/// those can only be restored, see `find_archived_product`.
+pub fn find_product(services: &Services, reference: &str) -> Result<Product, String> {
+    if is_valid_barcode(reference) {
+        return services
+            .product
+            .read_by_barcode(reference)
+            .map_err(|err| match err {
+                ServiceError::NoRows => format!("no product with barcode {reference}"),
+                err => format!("failed to read product with barcode {reference}: {err}"),
+            });
+    }
+
+    if let Ok(id) = reference.parse::<usize>() {
+        let product = services
+            .product
+            .read(id)
+            .map_err(|err| format!("failed to read product {id}: {err}"))?;
+        if product.archived {
+            return Err(format!(
+                "product '{}' is archived, restore it first",
+                product.name
+            ));
+        }
+        return Ok(product);
+    }
+
+    let products = services.product.list().map_err(|err| err.to_string())?;
+    products
+        .into_iter()
+        .find(|p| p.name.to_lowercase() == reference.to_lowercase())
+        .ok_or(format!("product '{reference}' not found"))
+}
+
+/// Finds archived product by id or case insensitive name.
+pub fn find_archived_product(services: &Services, reference: &str) -> Result<Product, String> {
+    let products = services
+        .product
+        .list_archived()
+        .map_err(|err| err.to_string())?;
+    let id = reference.parse::<usize>().ok();
+    products
+        .into_iter()
+        .find(|p| Some(p.id) == id || p.name.to_lowercase() == reference.to_lowercase())
+        .ok_or(format!("archived product '{reference}' not found"))
+}
@@ -0,0 +1,74 @@
+use chomp_db::{
+    clear_migration_dirty_flag, list_profiles, migration_status, open_conn, DbLocation,
+    MigrationStatus,
+};
+use pico_args::Arguments;
+use serde::Serialize;
+
+use crate::{
+    args::{finish, subcommand},
+    output::Output,
+};
+
+#[derive(Serialize)]
+struct Status {
+    path: Option<String>,
+    version: usize,
+    latest: usize,
+    pending: usize,
+    dirty: bool,
+}
+
+impl Status {
+    fn new(path: Option<&str>, status: MigrationStatus) -> Self {
+        Status {
+            path: path.map(|p| p.to_string()),
+            version: status.version,
+            latest: status.latest,
+            pending: status.pending(),
+            dirty: status.dirty,
+        }
+    }
+}
+
+/// Database commands open connection without migrating it, so they work even when
+/// migrations fail and the regular commands can't start.
+pub fn run(mut args: Arguments, location: &DbLocation, output: Output) -> Result<(), String> {
+    let subcommand = subcommand(&mut args)?;
+    if subcommand == "profiles" {
+        finish(args)?;
+        let profiles = list_profiles().map_err(|err| err.to_string())?;
+        return output.print(&profiles, |profiles| {
+            profiles.iter().for_each(|p| println!("{p}"))
+        });
+    }
+
+    let conn = open_conn(location).map_err(|err| err.to_string())?;
+
+    match subcommand.as_str() {
+        "status" => {
+            finish(args)?;
+            let status = migration_status(&conn).map_err(|err| err.to_string())?;
+            let status = Status::new(conn.path(), status);
+            output.print(&status, |s| {
+                println!("Database: {}", s.path.as_deref().unwrap_or("-"));
+                println!("Schema version: {} of {}", s.version, s.latest);
+                println!("Pending migrations: {}", s.pending);
+                println!("Dirty: {}", if s.dirty { "yes" } else { "no" });
+            })
+        }
+        "repair" => {
+            finish(args)?;
+            let status = migration_status(&conn).map_err(|err| err.to_string())?;
+            if !status.dirty {
+                return output.done("Database is not dirty, nothing to repair");
+            }
+            clear_migration_dirty_flag(&conn).map_err(|err| err.to_string())?;
+            output.done(&format!(
+                "Cleared dirty flag, migrations will continue from version {} on next start",
+                status.version + 1
+            ))
+        }
+        other => Err(format!("unknown db subcommand '{other}'")),
+    }
+}
@@ -0,0 +1,65 @@
+use chomp_services::{ServiceError, Services, ENERGY_WINDOW_DAYS};
+use pico_args::Arguments;
+
+use crate::{
+    args::{day, finish, optional, subcommand},
+    output::Output,
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "show" => {
+            let window: u64 = optional(&mut args, "--window")?.unwrap_or(ENERGY_WINDOW_DAYS);
+            let day = day(&mut args)?;
+            finish(args)?;
+            let estimate = services
+                .energy
+                .estimate(day, window)
+                .map_err(|err| format!("failed to estimate maintenance calories: {err}"))?;
+            output.print(&estimate, |estimate| match estimate {
+                Some(e) => {
+                    println!("Maintenance: {:.0} kcal", e.maintenance);
+                    println!("Average intake: {:.0} kcal", e.average_intake);
+                    println!("Weight trend: {:+.2} kg per week", e.weekly_change);
+                    println!(
+                        "Days logged: {} between {} and {}",
+                        e.days_logged, e.from, e.to
+                    );
+                }
+                None => println!("Not enough meals or weights logged in the last {window} days"),
+            })
+        }
+        "target" => {
+            let adjustment: f32 = optional(&mut args, "--adjustment")?.unwrap_or(-500.0);
+            let window: u64 = optional(&mut args, "--window")?.unwrap_or(ENERGY_WINDOW_DAYS);
+            let day = day(&mut args)?;
+            finish(args)?;
+            let estimate = services
+                .energy
+                .estimate(day, window)
+                .map_err(|err| format!("failed to estimate maintenance calories: {err}"))?
+                .ok_or(format!(
+                    "not enough meals or weights logged in the last {window} days"
+                ))?;
+            let base = services
+                .nutrition_target
+                .read_last_or_create_default()
+                .map_err(|err| format!("failed to read nutrition target: {err}"))?;
+            let target = estimate.target(day, adjustment, &base);
+            match services.nutrition_target.create(target.clone()) {
+                Err(ServiceError::UniqueConstraintViolation(_)) => {
+                    services.nutrition_target.update(target.clone())
+                }
+                result => result,
+            }
+            .map_err(|err| format!("failed to save nutrition target: {err}"))?;
+            output.print(&target, |t| {
+                println!(
+                    "Saved nutrition target for {}: {:.0} kcal, {:.0}g fats, {:.0}g proteins, {:.0}g carbohydrates",
+                    t.day, t.calories, t.fats, t.proteins, t.carbohydrates
+                )
+            })
+        }
+        other => Err(format!("unknown energy subcommand '{other}'")),
+    }
+}
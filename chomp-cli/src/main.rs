@@ -0,0 +1,157 @@
+use chomp_db::{prepare_conn, DbLocation};
+use chomp_services::Services;
+use output::Output;
+use pico_args::Arguments;
+
+mod args;
+mod db;
+mod energy;
+mod meal;
+mod measurement;
+mod output;
+mod product;
+mod report;
+mod settings;
+mod target;
+mod weight;
+
+const USAGE: &str = "\
+Usage: chomp-cli [--json] [--db <path>] [--profile <name>] <command> <subcommand> [options]
+
+Commands:
+  product list
+  product show <product>
+  product search <query> [--limit <count, default 10>]
+  product aliases <product> [--set <comma separated aliases>]
+  product add --name <name> --calories <kcal> --fats <g> --proteins <g> --carbohydrates <g>
+              [--company <company>] [--fiber <g>] [--sugar <g>] [--saturated-fats <g>] [--salt <g>]
+              [--barcode <ean>]
+  product update <product> [same options as add]
+  product delete <product>
+  product archived
+  product restore <product>
+
+  meal list [--day <YYYY-MM-DD>]
+  meal add <product> --meal <meal name> [--weight <g, default last used>] [--day <YYYY-MM-DD>]
+  meal recent [--meal <meal name>] [--limit <count, default 10>] [--day <YYYY-MM-DD>]
+  meal update <meal product id> --weight <g>
+  meal remove <meal product id>
+
+  weight list [--from <YYYY-MM-DD>] [--to <YYYY-MM-DD>]
+  weight add --weight <kg> [--day <YYYY-MM-DD>]
+  weight delete [--day <YYYY-MM-DD>]
+  weight goal (--weight <kg> | --clear)
+  weight trend
+
+  measurement list
+  measurement types
+  measurement add <measurement> --value <value> [--unit <unit>] [--day <YYYY-MM-DD>]
+  measurement delete --id <measurement id>
+
+  target list
+  target show [--day <YYYY-MM-DD>]
+  target add --calories <kcal> --fats <g> --proteins <g> --carbohydrates <g> [--day <YYYY-MM-DD>]
+  target delete --day <YYYY-MM-DD>
+  target weekday --weekday <Mon..Sun> (--calories <kcal> --fats <g> --proteins <g> --carbohydrates <g> | --clear) [--day <target day>]
+  target meal --meal <name> (--share <percent of the day> | --clear) [--day <target day>]
+
+  energy show [--day <YYYY-MM-DD>] [--window <days>]
+  energy target [--adjustment <kcal over maintenance, default -500>] [--day <YYYY-MM-DD>]
+                [--window <days>]
+
+  report show [--from <YYYY-MM-DD>] [--to <YYYY-MM-DD>] [--period week|month]
+              [--tolerance <percent of target, default 10>]
+
+  settings show
+  settings set [--theme <name>] [--body-weight kg|lb] [--food-weight g|oz] [--energy kcal|kJ]
+               [--week-start <Mon..Sun>] [--dashboard-range 1W|1M|3M|1Y|All]
+               [--calories <kcal>] [--fats <g>] [--proteins <g>] [--carbohydrates <g>]
+
+  db status
+  db repair
+  db profiles
+
+Products can be referenced by barcode, id or name, values are per 100g. Deleted products are
+archived, past meals keep them and they can be restored.
+Days default to today.
+
+Database is looked up in this order: --db flag, CHOMP_DB environment variable, `db` key in
+chomp/config.toml in config directory, $XDG_DATA_HOME/chomp and data directory of the platform.
+Profile (--profile, CHOMP_PROFILE or `profile` key in config) selects separate database in
+profiles directory next to the default one.";
+
+fn main() {
+    let mut args = Arguments::from_env();
+
+    if args.contains(["-h", "--help"]) {
+        println!("{USAGE}");
+        return;
+    }
+
+    let output = if args.contains("--json") {
+        Output::Json
+    } else {
+        Output::Human
+    };
+
+    let location = match parse_location(&mut args) {
+        Ok(l) => l,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    let command = match args.subcommand() {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+        Err(err) => {
+            eprintln!("Error: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    if command == "db" {
+        if let Err(err) = db::run(args, &location, output) {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let db = match prepare_conn(&location) {
+        Ok(db) => db,
+        Err(err) => {
+            eprintln!("Error: failed to prepare database connection: {err}");
+            std::process::exit(1);
+        }
+    };
+    let services = Services::new(db);
+
+    let result = match command.as_str() {
+        "product" => product::run(args, &services, output),
+        "meal" => meal::run(args, &services, output),
+        "weight" => weight::run(args, &services, output),
+        "measurement" => measurement::run(args, &services, output),
+        "target" => target::run(args, &services, output),
+        "report" => report::run(args, &services, output),
+        "energy" => energy::run(args, &services, output),
+        "settings" => settings::run(args, &services, output),
+        _ => Err(format!("unknown command '{command}'\n\n{USAGE}")),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn parse_location(args: &mut Arguments) -> Result<DbLocation, String> {
+    Ok(DbLocation {
+        path: args::optional(args, "--db")?,
+        profile: args::optional(args, "--profile")?,
+    })
+}
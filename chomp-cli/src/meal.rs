@@ -0,0 +1,147 @@
+use chomp_services::{AddMealProduct, Meal, MealDayStats, Services, UpdateMealProductWeight};
+use pico_args::Arguments;
+use serde::Serialize;
+
+use crate::{
+    args::{day, find_product, finish, free, optional, required, subcommand},
+    output::Output,
+};
+
+#[derive(Serialize)]
+struct MealDay {
+    meals: Vec<Meal>,
+    stats: MealDayStats,
+}
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "list" => {
+            let day = day(&mut args)?;
+            finish(args)?;
+            let meals = services
+                .meal
+                .list_or_create_default(day)
+                .map_err(|err| format!("failed to list meals: {err}"))?;
+            let stats = services
+                .meal
+                .day_stats(day)
+                .map_err(|err| format!("failed to read meal stats: {err}"))?;
+            output.print(&MealDay { meals, stats }, print_meal_day)
+        }
+        "add" => {
+            let reference: String = free(&mut args, "product")?;
+            let meal_name: String = required(&mut args, "--meal")?;
+            let weight: Option<f32> = optional(&mut args, "--weight")?;
+            let day = day(&mut args)?;
+            finish(args)?;
+
+            let product = find_product(services, &reference)?;
+            let meals = services
+                .meal
+                .list_or_create_default(day)
+                .map_err(|err| format!("failed to list meals: {err}"))?;
+            let meal = meals
+                .iter()
+                .find(|m| m.name.to_lowercase() == meal_name.to_lowercase())
+                .ok_or(format!("meal '{meal_name}' not found on {day}"))?;
+            let weight = match weight {
+                Some(weight) => weight,
+                None => services
+                    .meal
+                    .product_usage(day, Some(&meal.name), None)
+                    .map_err(|err| format!("failed to read product usage: {err}"))?
+                    .into_iter()
+                    .find(|u| u.product.id == product.id)
+                    .map(|u| u.last_weight)
+                    .ok_or(format!(
+                        "{} was not eaten in the last year, --weight is required",
+                        product.name
+                    ))?,
+            };
+
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id: meal.id,
+                    product_id: product.id,
+                    weight,
+                })
+                .map_err(|err| format!("failed to add product to meal: {err}"))?;
+            output.done(&format!(
+                "Added {weight}g of {} to {} on {day}",
+                product.name, meal.name
+            ))
+        }
+        "recent" => {
+            let meal_name: Option<String> = optional(&mut args, "--meal")?;
+            let limit: Option<usize> = optional(&mut args, "--limit")?;
+            let day = day(&mut args)?;
+            finish(args)?;
+            let usage = services
+                .meal
+                .product_usage(day, meal_name.as_deref(), Some(limit.unwrap_or(10)))
+                .map_err(|err| format!("failed to read product usage: {err}"))?;
+            output.print(&usage, |usage| {
+                for u in usage {
+                    println!(
+                        "{:>5}  {:<30} {:>4}x  last {} ({:.1}g)",
+                        u.product.id, u.product.name, u.uses, u.last_used, u.last_weight
+                    );
+                }
+            })
+        }
+        "update" => {
+            let meal_product_id: usize = free(&mut args, "meal product id")?;
+            let weight: f32 = required(&mut args, "--weight")?;
+            finish(args)?;
+            services
+                .meal
+                .update_product_weight(UpdateMealProductWeight {
+                    meal_product_id,
+                    weight,
+                })
+                .map_err(|err| format!("failed to update meal product: {err}"))?;
+            output.done(&format!("Updated meal product {meal_product_id}"))
+        }
+        "remove" => {
+            let meal_product_id: usize = free(&mut args, "meal product id")?;
+            finish(args)?;
+            services
+                .meal
+                .delete_product(meal_product_id)
+                .map_err(|err| format!("failed to remove meal product: {err}"))?;
+            output.done(&format!("Removed meal product {meal_product_id}"))
+        }
+        other => Err(format!("unknown meal subcommand '{other}'")),
+    }
+}
+
+fn print_meal_day(meal_day: &MealDay) {
+    for meal in &meal_day.meals {
+        println!("{}", meal.name);
+        for p in &meal.products {
+            println!(
+                "  [{:>5}] {:<30} {:>7.1}g {:>8.1} kcal  F {:>6.1}  P {:>6.1}  C {:>6.1}",
+                p.id, p.name, p.weight, p.calories, p.fats, p.proteins, p.carbohydrates
+            );
+        }
+    }
+
+    let stats = &meal_day.stats;
+    println!();
+    println!(
+        "Total: {:.1} kcal  F {:.1}  P {:.1}  C {:.1}",
+        stats.calories, stats.fats, stats.proteins, stats.carbohydrates
+    );
+    println!(
+        "Fiber {:.1}  Sugar {:.1}  Saturated fats {:.1}  Salt {:.2} (sodium {:.2})",
+        stats.fiber,
+        stats.sugar,
+        stats.saturated_fats,
+        stats.salt,
+        stats.sodium()
+    );
+    for n in &stats.nutrients {
+        println!("{} {:.1}{}", n.name, n.amount, n.unit);
+    }
+}
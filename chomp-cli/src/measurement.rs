@@ -0,0 +1,86 @@
+use chomp_services::{CreateMeasurement, ServiceError, Services};
+use pico_args::Arguments;
+
+use crate::{
+    args::{day, finish, free, optional, required, subcommand},
+    output::Output,
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "list" => {
+            finish(args)?;
+            let measurements = services
+                .measurement
+                .list()
+                .map_err(|err| format!("failed to list measurements: {err}"))?;
+            output.print(&measurements, |measurements| {
+                for m in measurements {
+                    println!(
+                        "{:>5}  {}  {}  {:.1} {}",
+                        m.id, m.day, m.name, m.value, m.unit
+                    );
+                }
+            })
+        }
+        "types" => {
+            finish(args)?;
+            let types = services
+                .measurement
+                .list_types()
+                .map_err(|err| format!("failed to list measurement types: {err}"))?;
+            output.print(&types, |types| {
+                for t in types {
+                    println!("{:>5}  {} ({})", t.id, t.name, t.unit);
+                }
+            })
+        }
+        "add" => {
+            let name: String = free(&mut args, "measurement")?;
+            let value: f32 = required(&mut args, "--value")?;
+            let unit: Option<String> = optional(&mut args, "--unit")?;
+            let day = day(&mut args)?;
+            finish(args)?;
+            let types = services
+                .measurement
+                .list_types()
+                .map_err(|err| format!("failed to list measurement types: {err}"))?;
+            let type_id = match (
+                types.iter().find(|t| t.name.eq_ignore_ascii_case(&name)),
+                unit,
+            ) {
+                (Some(t), _) => t.id,
+                (None, Some(unit)) => services
+                    .measurement
+                    .find_or_create_type(&name, &unit)
+                    .map_err(|err| format!("failed to create measurement type: {err}"))?,
+                (None, None) => {
+                    return Err(format!(
+                        "unknown measurement '{name}', pass --unit to create it"
+                    ))
+                }
+            };
+            match services.measurement.create(CreateMeasurement {
+                type_id,
+                day,
+                value,
+            }) {
+                Err(ServiceError::UniqueConstraintViolation(_)) => {
+                    Err(format!("{name} for {day} already exists"))
+                }
+                result => result.map_err(|err| format!("failed to save measurement: {err}")),
+            }?;
+            output.done(&format!("Saved {name} {value:.1} for {day}"))
+        }
+        "delete" => {
+            let id: usize = required(&mut args, "--id")?;
+            finish(args)?;
+            services
+                .measurement
+                .delete(id)
+                .map_err(|err| format!("failed to delete measurement: {err}"))?;
+            output.done(&format!("Deleted measurement {id}"))
+        }
+        other => Err(format!("unknown measurement subcommand '{other}'")),
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Output {
+    Human,
+    Json,
+}
+
+impl Output {
+    /// Prints value as JSON or, for human readable output, calls given function that is
+    /// responsible for printing it.
+    pub fn print<T, F>(&self, value: &T, human: F) -> Result<(), String>
+    where
+        T: Serialize,
+        F: FnOnce(&T),
+    {
+        match self {
+            Output::Json => {
+                let json = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+                println!("{json}");
+            }
+            Output::Human => human(value),
+        }
+
+        Ok(())
+    }
+
+    /// Prints confirmation of successful operation.
+    pub fn done(&self, message: &str) -> Result<(), String> {
+        #[derive(Serialize)]
+        struct Done<'a> {
+            message: &'a str,
+        }
+
+        self.print(&Done { message }, |d| println!("{}", d.message))
+    }
+}
+
+pub fn opt(value: Option<f32>) -> String {
+    value.map(|v| format!("{v:.1}")).unwrap_or("-".to_string())
+}
@@ -0,0 +1,193 @@
+use chomp_services::{CreateUpdateProduct, Product, ServiceError, Services, SetProductNutrient};
+use pico_args::Arguments;
+
+use crate::{
+    args::{find_archived_product, find_product, finish, free, optional, required, subcommand},
+    output::{opt, Output},
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "list" => {
+            finish(args)?;
+            let products = services.product.list().map_err(|err| err.to_string())?;
+            output.print(&products, |products| {
+                print_header();
+                products.iter().for_each(print_row);
+            })
+        }
+        "search" => {
+            let query: String = free(&mut args, "query")?;
+            let limit: Option<usize> = optional(&mut args, "--limit")?;
+            finish(args)?;
+            let mut products = services
+                .product
+                .search(&query)
+                .map_err(|err| format!("failed to search products: {err}"))?;
+            products.truncate(limit.unwrap_or(10));
+            output.print(&products, |products| {
+                print_header();
+                products.iter().for_each(print_row);
+            })
+        }
+        "aliases" => {
+            let reference: String = free(&mut args, "product")?;
+            let set: Option<String> = optional(&mut args, "--set")?;
+            finish(args)?;
+            let product = find_product(services, &reference)?;
+            if let Some(set) = set {
+                let aliases: Vec<String> = set.split(',').map(str::to_string).collect();
+                services
+                    .product
+                    .set_aliases(product.id, &aliases)
+                    .map_err(|err| format!("failed to save aliases: {err}"))?;
+            }
+            let aliases = services
+                .product
+                .list_aliases(product.id)
+                .map_err(|err| format!("failed to read aliases: {err}"))?;
+            output.print(&aliases, |aliases| {
+                aliases.iter().for_each(|a| println!("{a}"))
+            })
+        }
+        "show" => {
+            let reference: String = free(&mut args, "product")?;
+            finish(args)?;
+            let product = find_product(services, &reference)?;
+            output.print(&product, |p| {
+                print_header();
+                print_row(p);
+            })
+        }
+        "add" => {
+            let product = CreateUpdateProduct {
+                name: required(&mut args, "--name")?,
+                company: optional(&mut args, "--company")?,
+                calories: required(&mut args, "--calories")?,
+                fats: required(&mut args, "--fats")?,
+                proteins: required(&mut args, "--proteins")?,
+                carbohydrates: required(&mut args, "--carbohydrates")?,
+                fiber: optional(&mut args, "--fiber")?,
+                sugar: optional(&mut args, "--sugar")?,
+                saturated_fats: optional(&mut args, "--saturated-fats")?,
+                salt: optional(&mut args, "--salt")?,
+                barcode: optional(&mut args, "--barcode")?,
+                nutrients: Vec::new(),
+            };
+            finish(args)?;
+            let name = product.name.clone();
+            services.product.create(product).map_err(|err| match err {
+                ServiceError::UniqueConstraintViolation(field)
+                    if field == "products.name"
+                        && services.product.is_name_archived(&name).unwrap_or(false) =>
+                {
+                    format!("archived product '{name}' exists, restore it with `product restore`")
+                }
+                err => format!("failed to create product: {err}"),
+            })?;
+            output.done(&format!("Created product {name}"))
+        }
+        "update" => {
+            let reference: String = free(&mut args, "product")?;
+            let current = find_product(services, &reference)?;
+            let nutrients = services
+                .nutrient
+                .list_for_product(current.id)
+                .map_err(|err| err.to_string())?;
+            let product = CreateUpdateProduct {
+                name: optional(&mut args, "--name")?.unwrap_or(current.name),
+                company: optional(&mut args, "--company")?.or(current.company),
+                calories: optional(&mut args, "--calories")?.unwrap_or(current.calories),
+                fats: optional(&mut args, "--fats")?.unwrap_or(current.fats),
+                proteins: optional(&mut args, "--proteins")?.unwrap_or(current.proteins),
+                carbohydrates: optional(&mut args, "--carbohydrates")?
+                    .unwrap_or(current.carbohydrates),
+                fiber: optional(&mut args, "--fiber")?.or(current.fiber),
+                sugar: optional(&mut args, "--sugar")?.or(current.sugar),
+                saturated_fats: optional(&mut args, "--saturated-fats")?.or(current.saturated_fats),
+                salt: optional(&mut args, "--salt")?.or(current.salt),
+                barcode: optional(&mut args, "--barcode")?.or(current.barcode),
+                nutrients: nutrients
+                    .into_iter()
+                    .map(|n| SetProductNutrient {
+                        nutrient_id: n.nutrient_id,
+                        amount: n.amount,
+                    })
+                    .collect(),
+            };
+            finish(args)?;
+            services
+                .product
+                .update(current.id, product)
+                .map_err(|err| format!("failed to update product: {err}"))?;
+            output.done(&format!("Updated product {}", current.id))
+        }
+        "delete" => {
+            let reference: String = free(&mut args, "product")?;
+            finish(args)?;
+            let product = find_product(services, &reference)?;
+            services
+                .product
+                .delete(product.id)
+                .map_err(|err| format!("failed to archive product: {err}"))?;
+            output.done(&format!("Archived product {}", product.name))
+        }
+        "archived" => {
+            finish(args)?;
+            let products = services
+                .product
+                .list_archived()
+                .map_err(|err| err.to_string())?;
+            output.print(&products, |products| {
+                print_header();
+                products.iter().for_each(print_row);
+            })
+        }
+        "restore" => {
+            let reference: String = free(&mut args, "product")?;
+            finish(args)?;
+            let product = find_archived_product(services, &reference)?;
+            services
+                .product
+                .restore(product.id)
+                .map_err(|err| format!("failed to restore product: {err}"))?;
+            output.done(&format!("Restored product {}", product.name))
+        }
+        other => Err(format!("unknown product subcommand '{other}'")),
+    }
+}
+
+fn print_header() {
+    println!(
+        "{:>5}  {:<30} {:<20} {:>9} {:>7} {:>9} {:>14} {:>7} {:>7} {:>9} {:>6}  barcode",
+        "id",
+        "name",
+        "company",
+        "calories",
+        "fats",
+        "proteins",
+        "carbohydrates",
+        "fiber",
+        "sugar",
+        "sat. fats",
+        "salt"
+    );
+}
+
+fn print_row(p: &Product) {
+    println!(
+        "{:>5}  {:<30} {:<20} {:>9.1} {:>7.1} {:>9.1} {:>14.1} {:>7} {:>7} {:>9} {:>6}  {}",
+        p.id,
+        p.name,
+        p.company.as_deref().unwrap_or("-"),
+        p.calories,
+        p.fats,
+        p.proteins,
+        p.carbohydrates,
+        opt(p.fiber),
+        opt(p.sugar),
+        opt(p.saturated_fats),
+        opt(p.salt),
+        p.barcode.as_deref().unwrap_or("-")
+    );
+}
@@ -0,0 +1,61 @@
+use chomp_services::{ReportPeriod, Services};
+use chrono::{Days, Local};
+use pico_args::Arguments;
+
+use crate::{
+    args::{finish, optional, subcommand},
+    output::Output,
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "show" => {
+            let to = optional(&mut args, "--to")?.unwrap_or(Local::now().date_naive());
+            let from = optional(&mut args, "--from")?
+                .unwrap_or(to.checked_sub_days(Days::new(27)).unwrap());
+            let period = match optional::<String>(&mut args, "--period")?.as_deref() {
+                None | Some("week") => ReportPeriod::Week,
+                Some("month") => ReportPeriod::Month,
+                Some(other) => return Err(format!("--period: unknown period '{other}'")),
+            };
+            let tolerance: f32 = optional(&mut args, "--tolerance")?.unwrap_or(10.0);
+            finish(args)?;
+
+            let report = services
+                .report
+                .report(from, to, period, tolerance / 100.0)
+                .map_err(|err| format!("failed to create report: {err}"))?;
+            output.print(&report, |report| {
+                println!(
+                    "{:<12} {:>5} {:>9} {:>7} {:>9} {:>14} {:>10}",
+                    "period", "days", "calories", "fats", "proteins", "carbohydrates", "on target"
+                );
+                for p in report.periods.iter() {
+                    let on_target = if p.adherence.days_with_target == 0 {
+                        "-".to_string()
+                    } else {
+                        format!("{:.0}%", p.adherence.calories)
+                    };
+                    println!(
+                        "{:<12} {:>5} {:>9.1} {:>7.1} {:>9.1} {:>14.1} {:>10}",
+                        p.start.to_string(),
+                        p.days_logged,
+                        p.averages.calories,
+                        p.averages.fats,
+                        p.averages.proteins,
+                        p.averages.carbohydrates,
+                        on_target
+                    );
+                }
+                println!(
+                    "\n{} days logged, average {:.0} kcal, calories within {}% of target on {:.0}% of days",
+                    report.days.len(),
+                    report.averages.calories,
+                    tolerance,
+                    report.adherence.calories
+                );
+            })
+        }
+        other => Err(format!("unknown report subcommand '{other}'")),
+    }
+}
@@ -0,0 +1,79 @@
+use chomp_services::Services;
+use pico_args::Arguments;
+
+use crate::{
+    args::{finish, optional, subcommand},
+    output::Output,
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    let mut settings = services
+        .settings
+        .read()
+        .map_err(|err| format!("failed to read settings: {err}"))?;
+
+    match subcommand(&mut args)?.as_str() {
+        "show" => {
+            finish(args)?;
+            output.print(&settings, |s| {
+                println!("Theme: {}", s.theme.as_deref().unwrap_or("default"));
+                println!(
+                    "Units: {}, {}, {}",
+                    s.units.body_weight, s.units.food_weight, s.units.energy
+                );
+                println!("First day of the week: {}", s.week_start);
+                println!(
+                    "Default target: {:.0} kcal, {:.0}g fats, {:.0}g proteins, {:.0}g carbohydrates",
+                    s.default_target.calories,
+                    s.default_target.fats,
+                    s.default_target.proteins,
+                    s.default_target.carbohydrates
+                );
+                println!(
+                    "Dashboard range: {}",
+                    s.dashboard_range.as_deref().unwrap_or("default")
+                );
+            })
+        }
+        "set" => {
+            if let Some(theme) = optional(&mut args, "--theme")? {
+                settings.theme = Some(theme);
+            }
+            if let Some(unit) = optional(&mut args, "--body-weight")? {
+                settings.units.body_weight = unit;
+            }
+            if let Some(unit) = optional(&mut args, "--food-weight")? {
+                settings.units.food_weight = unit;
+            }
+            if let Some(unit) = optional(&mut args, "--energy")? {
+                settings.units.energy = unit;
+            }
+            if let Some(weekday) = optional(&mut args, "--week-start")? {
+                settings.week_start = weekday;
+            }
+            if let Some(range) = optional(&mut args, "--dashboard-range")? {
+                settings.dashboard_range = Some(range);
+            }
+            let target = &mut settings.default_target;
+            if let Some(calories) = optional(&mut args, "--calories")? {
+                target.calories = calories;
+            }
+            if let Some(fats) = optional(&mut args, "--fats")? {
+                target.fats = fats;
+            }
+            if let Some(proteins) = optional(&mut args, "--proteins")? {
+                target.proteins = proteins;
+            }
+            if let Some(carbohydrates) = optional(&mut args, "--carbohydrates")? {
+                target.carbohydrates = carbohydrates;
+            }
+            finish(args)?;
+            services
+                .settings
+                .save(&settings)
+                .map_err(|err| format!("failed to save settings: {err}"))?;
+            output.done("Saved settings")
+        }
+        other => Err(format!("unknown settings subcommand '{other}'")),
+    }
+}
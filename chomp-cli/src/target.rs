@@ -0,0 +1,157 @@
+use chomp_services::{MealBudget, NutritionTarget, ServiceError, Services, WeekdayTarget};
+use chrono::Weekday;
+use pico_args::Arguments;
+use serde::Serialize;
+
+use crate::{
+    args::{day, finish, optional, required, subcommand},
+    output::Output,
+};
+
+#[derive(Serialize)]
+struct TargetDetails {
+    #[serde(flatten)]
+    target: NutritionTarget,
+    weekdays: Vec<WeekdayTarget>,
+    meals: Vec<MealBudget>,
+}
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "list" => {
+            finish(args)?;
+            let targets = services
+                .nutrition_target
+                .list()
+                .map_err(|err| format!("failed to list nutrition targets: {err}"))?;
+            output.print(&targets, |targets| targets.iter().for_each(print_target))
+        }
+        "show" => {
+            let day = day(&mut args)?;
+            finish(args)?;
+            let target = services
+                .nutrition_target
+                .read(day)
+                .map_err(|err| format!("failed to read nutrition target: {err}"))?;
+            let details = TargetDetails {
+                target,
+                weekdays: services
+                    .nutrition_target
+                    .list_weekdays(day)
+                    .map_err(|err| format!("failed to read weekday targets: {err}"))?,
+                meals: services
+                    .nutrition_target
+                    .list_meal_budgets(day)
+                    .map_err(|err| format!("failed to read meal budgets: {err}"))?,
+            };
+            output.print(&details, |d| {
+                print_target(&d.target);
+                for w in &d.weekdays {
+                    println!(
+                        "  {}  {:.0} kcal  F {:.1}  P {:.1}  C {:.1}",
+                        Weekday::try_from(w.weekday as u8).unwrap(),
+                        w.calories,
+                        w.fats,
+                        w.proteins,
+                        w.carbohydrates
+                    );
+                }
+                for m in &d.meals {
+                    println!("  {}  {:.1}%", m.meal, m.share);
+                }
+            })
+        }
+        "weekday" => {
+            let weekday: Weekday = required(&mut args, "--weekday")?;
+            let clear = args.contains("--clear");
+            let day = day(&mut args)?;
+            let weekday = weekday.num_days_from_monday();
+            if clear {
+                finish(args)?;
+                services
+                    .nutrition_target
+                    .delete_weekday(day, weekday)
+                    .map_err(|err| format!("failed to delete weekday target: {err}"))?;
+                return output.done("Deleted weekday target");
+            }
+            let target = WeekdayTarget {
+                day,
+                weekday,
+                calories: required(&mut args, "--calories")?,
+                fats: required(&mut args, "--fats")?,
+                proteins: required(&mut args, "--proteins")?,
+                carbohydrates: required(&mut args, "--carbohydrates")?,
+            };
+            finish(args)?;
+            services
+                .nutrition_target
+                .set_weekday(target)
+                .map_err(|err| match err {
+                    ServiceError::NoRows => format!("no nutrition target set on {day}"),
+                    err => format!("failed to save weekday target: {err}"),
+                })?;
+            output.done("Saved weekday target")
+        }
+        "meal" => {
+            let meal: String = required(&mut args, "--meal")?;
+            let share: Option<f32> = optional(&mut args, "--share")?;
+            let clear = args.contains("--clear");
+            let day = day(&mut args)?;
+            finish(args)?;
+            match (share, clear) {
+                (None, true) => {
+                    services
+                        .nutrition_target
+                        .delete_meal_budget(day, &meal)
+                        .map_err(|err| format!("failed to delete meal budget: {err}"))?;
+                    output.done(&format!("Deleted budget of {meal}"))
+                }
+                (Some(share), false) => {
+                    services
+                        .nutrition_target
+                        .set_meal_budget(MealBudget { day, meal, share })
+                        .map_err(|err| match err {
+                            ServiceError::NoRows => format!("no nutrition target set on {day}"),
+                            err => format!("failed to save meal budget: {err}"),
+                        })?;
+                    output.done("Saved meal budget")
+                }
+                _ => Err("either --share or --clear is required".to_string()),
+            }
+        }
+        "add" => {
+            let calories: f32 = required(&mut args, "--calories")?;
+            let fats: f32 = required(&mut args, "--fats")?;
+            let proteins: f32 = required(&mut args, "--proteins")?;
+            let carbohydrates: f32 = required(&mut args, "--carbohydrates")?;
+            let day = day(&mut args)?;
+            finish(args)?;
+            let target = NutritionTarget::new(day, calories, fats, proteins, carbohydrates);
+            match services.nutrition_target.create(target.clone()) {
+                Err(ServiceError::UniqueConstraintViolation(_)) => {
+                    services.nutrition_target.update(target)
+                }
+                result => result,
+            }
+            .map_err(|err| format!("failed to save nutrition target: {err}"))?;
+            output.done(&format!("Saved nutrition target for {day}"))
+        }
+        "delete" => {
+            let day = day(&mut args)?;
+            finish(args)?;
+            services
+                .nutrition_target
+                .delete(day)
+                .map_err(|err| format!("failed to delete nutrition target: {err}"))?;
+            output.done(&format!("Deleted nutrition target for {day}"))
+        }
+        other => Err(format!("unknown target subcommand '{other}'")),
+    }
+}
+
+fn print_target(t: &NutritionTarget) {
+    println!(
+        "{}  {:.0} kcal  F {:.1}  P {:.1}  C {:.1}",
+        t.day, t.calories, t.fats, t.proteins, t.carbohydrates
+    );
+}
@@ -0,0 +1,100 @@
+use chomp_services::{ServiceError, Services, Weight};
+use chrono::NaiveDate;
+use pico_args::Arguments;
+
+use crate::{
+    args::{day, finish, optional, required, subcommand},
+    output::Output,
+};
+
+pub fn run(mut args: Arguments, services: &Services, output: Output) -> Result<(), String> {
+    match subcommand(&mut args)?.as_str() {
+        "list" => {
+            let from = optional(&mut args, "--from")?;
+            let to = optional(&mut args, "--to")?;
+            finish(args)?;
+            let weights = match (from, to) {
+                (None, None) => services.weight.list(),
+                // Days are compared as text in the database, so the open ends are bound by
+                // dates that still format as plain `YYYY-MM-DD`.
+                (from, to) => services.weight.list_between(
+                    from.unwrap_or(NaiveDate::from_ymd_opt(1, 1, 1).unwrap()),
+                    to.unwrap_or(NaiveDate::from_ymd_opt(9999, 12, 31).unwrap()),
+                ),
+            }
+            .map_err(|err| format!("failed to list weights: {err}"))?;
+            output.print(&weights, |weights| {
+                for w in weights {
+                    println!("{}  {:.1} kg", w.day, w.weight);
+                }
+            })
+        }
+        "add" => {
+            let weight: f32 = required(&mut args, "--weight")?;
+            let day = day(&mut args)?;
+            finish(args)?;
+            match services.weight.create(Weight::new(day, weight)) {
+                Err(ServiceError::UniqueConstraintViolation(_)) => {
+                    services.weight.update(Weight::new(day, weight))
+                }
+                result => result,
+            }
+            .map_err(|err| format!("failed to save weight: {err}"))?;
+            output.done(&format!("Saved weight {weight:.1} kg for {day}"))
+        }
+        "delete" => {
+            let day = day(&mut args)?;
+            finish(args)?;
+            services
+                .weight
+                .delete(day)
+                .map_err(|err| format!("failed to delete weight: {err}"))?;
+            output.done(&format!("Deleted weight for {day}"))
+        }
+        "goal" => {
+            let clear = args.contains("--clear");
+            let weight: Option<f32> = optional(&mut args, "--weight")?;
+            finish(args)?;
+            match (weight, clear) {
+                (Some(weight), false) => {
+                    services
+                        .weight
+                        .set_goal(weight)
+                        .map_err(|err| format!("failed to set weight goal: {err}"))?;
+                    output.done(&format!("Set weight goal to {weight:.1} kg"))
+                }
+                (None, true) => {
+                    services
+                        .weight
+                        .delete_goal()
+                        .map_err(|err| format!("failed to clear weight goal: {err}"))?;
+                    output.done("Cleared weight goal")
+                }
+                _ => Err("expected either --weight <kg> or --clear".to_string()),
+            }
+        }
+        "trend" => {
+            finish(args)?;
+            let analysis = services
+                .weight
+                .analysis()
+                .map_err(|err| format!("failed to analyse weights: {err}"))?;
+            output.print(&analysis, |analysis| {
+                for t in analysis.trend.iter().rev() {
+                    println!("{}  {:.1} kg  trend {:.1} kg", t.day, t.weight, t.trend);
+                }
+                if let Some(change) = analysis.weekly_change {
+                    println!("Rate: {change:+.2} kg per week");
+                }
+                match (analysis.goal, analysis.goal_day) {
+                    (Some(goal), Some(day)) => println!("Goal {goal:.1} kg reached around {day}"),
+                    (Some(goal), None) => {
+                        println!("Goal {goal:.1} kg is not reached at current rate")
+                    }
+                    _ => {}
+                }
+            })
+        }
+        other => Err(format!("unknown weight subcommand '{other}'")),
+    }
+}
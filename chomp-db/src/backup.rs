@@ -0,0 +1,220 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use rusqlite::{Connection, MAIN_DB};
+
+use crate::{error::Error, run_migrations};
+
+/// Number of backups kept in backups directory, older ones are removed after creating new one.
+pub const BACKUP_COUNT: usize = 10;
+
+const BACKUP_FILE_PREFIX: &str = "data-";
+const BACKUP_FILE_EXTENSION: &str = "db";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+const BACKUP_DAY_FORMAT: &str = "%Y%m%d";
+
+/// Backups are kept next to the database file in `backups` directory.
+pub fn backup_dir(conn: &Connection) -> Result<PathBuf, Error> {
+    let db_path = match conn.path() {
+        Some(p) if !p.is_empty() => PathBuf::from(p),
+        _ => {
+            return Err(Error::Backup(
+                "in memory database can't be backed up".to_string(),
+            ))
+        }
+    };
+
+    match db_path.parent() {
+        Some(p) => Ok(p.join("backups")),
+        None => Err(Error::IO("failed to get parent of db path".to_string())),
+    }
+}
+
+/// Takes consistent snapshot of the database using SQLite online backup API and removes
+/// backups over `BACKUP_COUNT`.
+pub fn create_backup(conn: &Connection) -> Result<PathBuf, Error> {
+    let backup_path = snapshot(conn)?;
+    rotate_backups(&backup_dir(conn)?)?;
+
+    Ok(backup_path)
+}
+
+fn snapshot(conn: &Connection) -> Result<PathBuf, Error> {
+    let dir = backup_dir(conn)?;
+    if let Err(err) = fs::create_dir_all(&dir) {
+        return Err(Error::IO(format!(
+            "failed to create backups directory: {err}"
+        )));
+    }
+
+    let backup_path = dir.join(format!(
+        "{}{}.{}",
+        BACKUP_FILE_PREFIX,
+        Local::now().format(BACKUP_TIMESTAMP_FORMAT),
+        BACKUP_FILE_EXTENSION
+    ));
+
+    if let Err(err) = conn.backup(MAIN_DB, &backup_path, None) {
+        return Err(Error::Backup(format!("failed to create backup: {err}")));
+    }
+
+    Ok(backup_path)
+}
+
+/// Creates backup if there's none from today yet.
+pub fn create_backup_if_due(conn: &Connection) -> Result<Option<PathBuf>, Error> {
+    let today = format!(
+        "{}{}",
+        BACKUP_FILE_PREFIX,
+        Local::now().format(BACKUP_DAY_FORMAT)
+    );
+
+    let has_todays_backup = list_backups(conn)?.iter().any(|p| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(&today))
+    });
+    if has_todays_backup {
+        return Ok(None);
+    }
+
+    create_backup(conn).map(Some)
+}
+
+/// Lists existing backups, newest first.
+pub fn list_backups(conn: &Connection) -> Result<Vec<PathBuf>, Error> {
+    let dir = backup_dir(conn)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    list_backups_in(&dir)
+}
+
+/// Replaces content of the database with given backup and migrates it to the current schema.
+/// Current state is backed up first so that restoring is not a one way operation. Old backups
+/// are rotated only after restoring, otherwise the oldest one could be removed before it's read.
+pub fn restore_backup(conn: &mut Connection, backup_path: &Path) -> Result<(), Error> {
+    snapshot(conn)?;
+
+    if let Err(err) = conn.restore(MAIN_DB, backup_path, None::<fn(rusqlite::backup::Progress)>) {
+        return Err(Error::Backup(format!("failed to restore backup: {err}")));
+    }
+
+    if let Err(err) = run_migrations(conn) {
+        return Err(Error::Migration(format!(
+            "failed to perform database migration: {err}"
+        )));
+    }
+
+    rotate_backups(&backup_dir(conn)?)
+}
+
+fn list_backups_in(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(err) => {
+            return Err(Error::IO(format!(
+                "failed to read backups directory: {err}"
+            )))
+        }
+    };
+
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension().is_some_and(|e| e == BACKUP_FILE_EXTENSION)
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(BACKUP_FILE_PREFIX))
+        })
+        .collect();
+
+    // Timestamp format sorts the same way as dates do.
+    backups.sort();
+    backups.reverse();
+
+    Ok(backups)
+}
+
+fn rotate_backups(dir: &Path) -> Result<(), Error> {
+    for old_backup in list_backups_in(dir)?.iter().skip(BACKUP_COUNT) {
+        if let Err(err) = fs::remove_file(old_backup) {
+            return Err(Error::IO(format!(
+                "failed to remove old backup {}: {err}",
+                old_backup.display()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn restore_brings_back_backed_up_state() {
+        let dir = env::temp_dir().join(format!("chomp-backup-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut conn = Connection::open(dir.join("data.db")).unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO weights (day, weight) VALUES ('2025-01-31', 80.0)",
+            (),
+        )
+        .unwrap();
+        let backup_path = create_backup(&conn).unwrap();
+        conn.execute("DELETE FROM weights", ()).unwrap();
+
+        restore_backup(&mut conn, &backup_path).unwrap();
+
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM weights", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(!list_backups(&conn).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restore_oldest_of_full_backups() {
+        let dir = env::temp_dir().join(format!("chomp-backup-rotate-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut conn = Connection::open(dir.join("data.db")).unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO weights (day, weight) VALUES ('2025-01-31', 80.0)",
+            (),
+        )
+        .unwrap();
+        let oldest = create_backup(&conn).unwrap();
+        conn.execute("DELETE FROM weights", ()).unwrap();
+        for _ in 1..BACKUP_COUNT {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            create_backup(&conn).unwrap();
+        }
+        assert_eq!(list_backups(&conn).unwrap().len(), BACKUP_COUNT);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        restore_backup(&mut conn, &oldest).unwrap();
+
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM weights", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(list_backups(&conn).unwrap().len(), BACKUP_COUNT);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,20 @@
+use std::fmt;
+
 #[derive(Debug)]
 pub enum Error {
     IO(String),
     Connection(String),
     Migration(String),
+    Backup(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::IO(err) => write!(f, "io error: {err}"),
+            Error::Connection(err) => write!(f, "connection error: {err}"),
+            Error::Migration(err) => write!(f, "migration error: {err}"),
+            Error::Backup(err) => write!(f, "backup error: {err}"),
+        }
+    }
 }
@@ -1,36 +1,40 @@
-use std::{env, path::PathBuf};
-
-use migrate::migrate;
+use migrate::{clear_dirty, has_pending_migrations, migrate, status, Migration};
 use migrations::{
     CREATE_CALORIE_TARGETS_TABLE_QUERY_4, CREATE_MEALS_TABLE_QUERY_2,
-    CREATE_MEAL_PRODUCTS_TABLE_QUERY_3, CREATE_PRODUCTS_TABLE_QUERY_1,
-    CREATE_WEIGHTS_TABLE_QUERY_5,
+    CREATE_MEAL_PRESETS_TABLES_QUERY_20, CREATE_MEAL_PRODUCTS_TABLE_QUERY_3,
+    CREATE_MEAL_TEMPLATES_TABLE_QUERY_19, CREATE_MEASUREMENTS_TABLES_QUERY_22,
+    CREATE_NUTRITION_TARGET_SCHEDULE_TABLES_QUERY_24, CREATE_PRODUCTS_TABLE_QUERY_1,
+    CREATE_PRODUCT_SEARCH_INDEX_QUERY_25, CREATE_SETTINGS_TABLE_QUERY_23,
+    CREATE_WEIGHTS_TABLE_QUERY_5, CREATE_WEIGHT_GOAL_TABLE_QUERY_21,
 };
 use rusqlite::Connection;
 
-use crate::{
-    error::Error,
-    migrations::{
-        CREATE_PRODUCT_PORTIONS_TABLE_QUERY_7,
-        CREATE_PRODUCT_PORTIONS_UNIQUE_NAME_CONSTRAINT_QUERY_8,
-        RENAME_CALORIE_TARGETS_TO_NUTRITION_TARGETS_QUERY_6,
-    },
+pub use backup::{
+    backup_dir, create_backup, create_backup_if_due, list_backups, restore_backup, BACKUP_COUNT,
 };
+pub use error::Error;
+pub use location::{list_profiles, resolve_db_path, DbLocation};
+pub use migrate::MigrationStatus;
 
+use crate::migrations::{
+    ADD_MEAL_PRODUCTS_NUTRITION_SNAPSHOT_QUERY_27, ADD_PRODUCTS_ARCHIVED_COLUMN_QUERY_26,
+    ADD_PRODUCTS_BARCODE_COLUMN_QUERY_18, ADD_PRODUCTS_FIBER_COLUMN_QUERY_11,
+    ADD_PRODUCTS_SALT_COLUMN_QUERY_14, ADD_PRODUCTS_SATURATED_FATS_COLUMN_QUERY_13,
+    ADD_PRODUCTS_SUGAR_COLUMN_QUERY_12, CREATE_NUTRIENTS_TABLE_QUERY_15,
+    CREATE_PRODUCT_NUTRIENTS_TABLE_QUERY_16, CREATE_PRODUCT_PORTIONS_TABLE_QUERY_7,
+    CREATE_PRODUCT_PORTIONS_UNIQUE_NAME_CONSTRAINT_QUERY_8, CREATE_RECIPES_TABLE_QUERY_9,
+    CREATE_RECIPE_INGREDIENTS_TABLE_QUERY_10, INSERT_DEFAULT_NUTRIENTS_QUERY_17,
+    RENAME_CALORIE_TARGETS_TO_NUTRITION_TARGETS_QUERY_6,
+};
+
+mod backup;
 mod error;
+mod location;
 mod migrate;
 mod migrations;
 
-fn get_home_dir() -> Option<PathBuf> {
-    if cfg!(target_os = "windows") {
-        env::var("USERPROFILE").ok().map(PathBuf::from)
-    } else {
-        env::var("HOME").ok().map(PathBuf::from)
-    }
-}
-
-fn run_migrations(conn: &Connection) -> Result<(), String> {
-    let migrations = vec![
+fn migrations() -> Vec<Migration> {
+    vec![
         CREATE_PRODUCTS_TABLE_QUERY_1,
         CREATE_MEALS_TABLE_QUERY_2,
         CREATE_MEAL_PRODUCTS_TABLE_QUERY_3,
@@ -39,21 +43,36 @@ fn run_migrations(conn: &Connection) -> Result<(), String> {
         RENAME_CALORIE_TARGETS_TO_NUTRITION_TARGETS_QUERY_6,
         CREATE_PRODUCT_PORTIONS_TABLE_QUERY_7,
         CREATE_PRODUCT_PORTIONS_UNIQUE_NAME_CONSTRAINT_QUERY_8,
-    ];
+        CREATE_RECIPES_TABLE_QUERY_9,
+        CREATE_RECIPE_INGREDIENTS_TABLE_QUERY_10,
+        ADD_PRODUCTS_FIBER_COLUMN_QUERY_11,
+        ADD_PRODUCTS_SUGAR_COLUMN_QUERY_12,
+        ADD_PRODUCTS_SATURATED_FATS_COLUMN_QUERY_13,
+        ADD_PRODUCTS_SALT_COLUMN_QUERY_14,
+        CREATE_NUTRIENTS_TABLE_QUERY_15,
+        CREATE_PRODUCT_NUTRIENTS_TABLE_QUERY_16,
+        INSERT_DEFAULT_NUTRIENTS_QUERY_17,
+        ADD_PRODUCTS_BARCODE_COLUMN_QUERY_18,
+        CREATE_MEAL_TEMPLATES_TABLE_QUERY_19,
+        CREATE_MEAL_PRESETS_TABLES_QUERY_20,
+        CREATE_WEIGHT_GOAL_TABLE_QUERY_21,
+        CREATE_MEASUREMENTS_TABLES_QUERY_22,
+        CREATE_SETTINGS_TABLE_QUERY_23,
+        CREATE_NUTRITION_TARGET_SCHEDULE_TABLES_QUERY_24,
+        CREATE_PRODUCT_SEARCH_INDEX_QUERY_25,
+        ADD_PRODUCTS_ARCHIVED_COLUMN_QUERY_26,
+        ADD_MEAL_PRODUCTS_NUTRITION_SNAPSHOT_QUERY_27,
+    ]
+}
 
-    migrate(conn, migrations)
+fn run_migrations(conn: &Connection) -> Result<(), String> {
+    migrate(conn, migrations())
 }
 
-pub fn prepare_conn() -> Result<Connection, Error> {
-    let home = match get_home_dir() {
-        Some(d) => d,
-        None => return Err(Error::IO("failed to get home directory".to_string())),
-    };
-    let db_path: PathBuf = home
-        .join(".local")
-        .join("share")
-        .join("chomp")
-        .join("data.db");
+/// Opens database connection without running migrations, meant for inspecting and repairing
+/// database that can't be migrated.
+pub fn open_conn(location: &DbLocation) -> Result<Connection, Error> {
+    let db_path = resolve_db_path(location)?;
 
     let db_parent = match db_path.parent() {
         Some(p) => p,
@@ -73,6 +92,56 @@ pub fn prepare_conn() -> Result<Connection, Error> {
             )))
         }
     };
+    enable_foreign_keys(&conn)?;
+
+    Ok(conn)
+}
+
+pub fn prepare_conn(location: &DbLocation) -> Result<Connection, Error> {
+    let conn = open_conn(location)?;
+
+    match has_pending_migrations(&conn, &migrations()) {
+        Ok(true) => {
+            create_backup(&conn)?;
+        }
+        Ok(false) => {}
+        Err(err) => {
+            return Err(Error::Migration(format!(
+                "failed to check pending migrations: {err}"
+            )))
+        }
+    }
+
+    if let Err(err) = run_migrations(&conn) {
+        return Err(Error::Migration(format!(
+            "failed to perform database migration: {err}"
+        )));
+    }
+
+    Ok(conn)
+}
+
+pub fn migration_status(conn: &Connection) -> Result<MigrationStatus, Error> {
+    status(conn, &migrations())
+        .map_err(|err| Error::Migration(format!("failed to read migration status: {err}")))
+}
+
+/// Clears dirty flag left by failed migration. Should only be used after checking that
+/// database is not partially migrated, next start will retry pending migrations.
+pub fn clear_migration_dirty_flag(conn: &Connection) -> Result<(), Error> {
+    clear_dirty(conn).map_err(|err| Error::Migration(format!("failed to clear dirty flag: {err}")))
+}
+
+pub fn prepare_in_memory_conn() -> Result<Connection, Error> {
+    let conn = match Connection::open_in_memory() {
+        Ok(c) => c,
+        Err(err) => {
+            return Err(Error::Connection(format!(
+                "failed to open in memory database connection: {err}",
+            )))
+        }
+    };
+    enable_foreign_keys(&conn)?;
 
     if let Err(err) = run_migrations(&conn) {
         return Err(Error::Migration(format!(
@@ -83,6 +152,13 @@ pub fn prepare_conn() -> Result<Connection, Error> {
     Ok(conn)
 }
 
+/// Foreign keys are off in SQLite unless enabled for each connection, rows referencing deleted
+/// ones are only removed by `ON DELETE CASCADE` when they are on.
+fn enable_foreign_keys(conn: &Connection) -> Result<(), Error> {
+    conn.pragma_update(None, "foreign_keys", true)
+        .map_err(|err| Error::Connection(format!("failed to enable foreign keys: {err}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +169,30 @@ mod tests {
         run_migrations(&conn).unwrap();
         run_migrations(&conn).unwrap();
     }
+
+    #[test]
+    fn deleting_row_removes_rows_referencing_it() {
+        let conn = prepare_in_memory_conn().unwrap();
+        conn.execute(
+            "INSERT INTO products (id, name, calories, fats, proteins, carbohydrates)
+            VALUES (1, 'Kiwi', 60, 0.5, 1, 15)",
+            (),
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO product_nutrients (product_id, nutrient_id, amount)
+            SELECT 1, id, 90 FROM nutrients",
+            (),
+        )
+        .unwrap();
+
+        conn.execute("DELETE FROM nutrients", ()).unwrap();
+
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM product_nutrients", (), |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+    }
 }
@@ -0,0 +1,279 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Error;
+
+const DB_FILE_NAME: &str = "data.db";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Database location requested explicitly, usually through command line flags. Fields that
+/// are not set fall back to `CHOMP_DB`/`CHOMP_PROFILE` environment variables, then to config
+/// file and finally to data directory of the platform.
+#[derive(Debug, Default, Clone)]
+pub struct DbLocation {
+    pub path: Option<PathBuf>,
+    pub profile: Option<String>,
+}
+
+/// Values read from `chomp/config.toml` in config directory of the platform. Only top level
+/// string keys `db` and `profile` are supported.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Config {
+    db: Option<PathBuf>,
+    profile: Option<String>,
+}
+
+pub fn resolve_db_path(location: &DbLocation) -> Result<PathBuf, Error> {
+    let config = read_config()?;
+
+    resolve(
+        location,
+        non_empty_env("CHOMP_DB").map(PathBuf::from),
+        non_empty_env("CHOMP_PROFILE"),
+        config,
+        data_dir,
+    )
+}
+
+/// Lists profiles that already have a database, default profile is always included.
+pub fn list_profiles() -> Result<Vec<String>, Error> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+
+    let profiles_dir = data_dir()?.join("profiles");
+    if let Ok(entries) = fs::read_dir(profiles_dir) {
+        let mut named: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join(DB_FILE_NAME).exists())
+            .filter_map(|e| e.file_name().to_str().map(|n| n.to_string()))
+            .collect();
+        named.sort();
+        profiles.extend(named);
+    }
+
+    Ok(profiles)
+}
+
+fn resolve(
+    location: &DbLocation,
+    env_db: Option<PathBuf>,
+    env_profile: Option<String>,
+    config: Config,
+    data_dir: impl FnOnce() -> Result<PathBuf, Error>,
+) -> Result<PathBuf, Error> {
+    // Flags win over environment and environment over config file, path wins over profile
+    // only within the same source. Otherwise `--profile` would be silently ignored when
+    // `CHOMP_DB` is set and `CHOMP_PROFILE` when config file has `db`.
+    let sources = [
+        (location.path.clone(), location.profile.clone()),
+        (env_db, env_profile),
+        (config.db, config.profile),
+    ];
+    let mut profile = None;
+    for (path, source_profile) in sources {
+        if let Some(path) = path {
+            return Ok(path);
+        }
+        if source_profile.is_some() {
+            profile = source_profile;
+            break;
+        }
+    }
+
+    let dir = data_dir()?;
+
+    match profile.as_deref() {
+        None | Some(DEFAULT_PROFILE) => Ok(dir.join(DB_FILE_NAME)),
+        Some(name) => {
+            validate_profile_name(name)?;
+            Ok(dir.join("profiles").join(name).join(DB_FILE_NAME))
+        }
+    }
+}
+
+fn validate_profile_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::IO(format!(
+            "invalid profile name '{name}', only letters, digits, '-' and '_' are allowed"
+        )))
+    }
+}
+
+/// Directory with default database, `$XDG_DATA_HOME/chomp` when set and data directory of the
+/// platform otherwise. Databases created before this was configurable always lived in
+/// `~/.local/share/chomp`, that location is kept if it has a database and the new one doesn't.
+fn data_dir() -> Result<PathBuf, Error> {
+    let dir = match non_empty_env("XDG_DATA_HOME") {
+        Some(xdg) => PathBuf::from(xdg).join("chomp"),
+        None => match dirs::data_dir() {
+            Some(d) => d.join("chomp"),
+            None => return Err(Error::IO("failed to get data directory".to_string())),
+        },
+    };
+
+    let legacy = dirs::home_dir().map(|home| home.join(".local").join("share").join("chomp"));
+    match legacy {
+        Some(legacy) if !dir.join(DB_FILE_NAME).exists() && legacy.join(DB_FILE_NAME).exists() => {
+            Ok(legacy)
+        }
+        _ => Ok(dir),
+    }
+}
+
+fn read_config() -> Result<Config, Error> {
+    let Some(path) = dirs::config_dir().map(|d| d.join("chomp").join("config.toml")) else {
+        return Ok(Config::default());
+    };
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(content) => parse_config(&content, &path),
+        Err(err) => Err(Error::IO(format!(
+            "failed to read config file {}: {err}",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_config(content: &str, path: &Path) -> Result<Config, Error> {
+    let mut config = Config::default();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(Error::IO(format!(
+                "invalid line {} in config file {}",
+                i + 1,
+                path.display()
+            )));
+        };
+        let value = value.trim().trim_matches('"').to_string();
+
+        match key.trim() {
+            "db" => config.db = Some(PathBuf::from(value)),
+            "profile" => config.profile = Some(value),
+            _ => {}
+        }
+    }
+
+    Ok(config)
+}
+
+fn non_empty_env(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_dir() -> Result<PathBuf, Error> {
+        Ok(PathBuf::from("/data/chomp"))
+    }
+
+    #[test]
+    fn resolves_in_order_of_precedence() {
+        let flag = DbLocation {
+            path: Some(PathBuf::from("/flag.db")),
+            profile: None,
+        };
+        let config = Config {
+            db: Some(PathBuf::from("/config.db")),
+            profile: None,
+        };
+
+        let path = resolve(
+            &flag,
+            Some(PathBuf::from("/env.db")),
+            None,
+            config.clone(),
+            data_dir,
+        );
+        assert_eq!(path.unwrap(), PathBuf::from("/flag.db"));
+
+        let path = resolve(
+            &DbLocation::default(),
+            Some(PathBuf::from("/env.db")),
+            None,
+            config.clone(),
+            data_dir,
+        );
+        assert_eq!(path.unwrap(), PathBuf::from("/env.db"));
+
+        let path = resolve(&DbLocation::default(), None, None, config, data_dir);
+        assert_eq!(path.unwrap(), PathBuf::from("/config.db"));
+
+        let path = resolve(
+            &DbLocation::default(),
+            None,
+            None,
+            Config::default(),
+            data_dir,
+        );
+        assert_eq!(path.unwrap(), PathBuf::from("/data/chomp/data.db"));
+    }
+
+    #[test]
+    fn profiles_get_separate_databases() {
+        let location = DbLocation {
+            path: None,
+            profile: Some("anna".to_string()),
+        };
+
+        let path = resolve(
+            &location,
+            Some(PathBuf::from("/env.db")),
+            None,
+            Config::default(),
+            data_dir,
+        );
+        assert_eq!(
+            path.unwrap(),
+            PathBuf::from("/data/chomp/profiles/anna/data.db")
+        );
+
+        let config = parse_config("# comment\nprofile = \"default\"\n", Path::new("")).unwrap();
+        let path = resolve(&DbLocation::default(), None, None, config, data_dir);
+        assert_eq!(path.unwrap(), PathBuf::from("/data/chomp/data.db"));
+
+        let location = DbLocation {
+            path: None,
+            profile: Some("../anna".to_string()),
+        };
+        assert!(resolve(&location, None, None, Config::default(), data_dir).is_err());
+    }
+
+    #[test]
+    fn env_profile_wins_over_config_db() {
+        let config = Config {
+            db: Some(PathBuf::from("/config.db")),
+            profile: None,
+        };
+
+        let path = resolve(
+            &DbLocation::default(),
+            None,
+            Some("anna".to_string()),
+            config,
+            data_dir,
+        );
+        assert_eq!(
+            path.unwrap(),
+            PathBuf::from("/data/chomp/profiles/anna/data.db")
+        );
+    }
+}
@@ -14,6 +14,11 @@ const GET_SCHEMA_MIGRATIONS_STATE_QUERY: &str = "
     SELECT version, dirty
     FROM schema_migrations";
 
+const SCHEMA_MIGRATIONS_TABLE_EXISTS_QUERY: &str = "
+    SELECT COUNT(*)
+    FROM sqlite_master
+    WHERE type = 'table' AND name = 'schema_migrations'";
+
 const UPDATE_SCHEMA_MIGRATIONS_VERSION_QUERY: &str = "
     UPDATE schema_migrations
     SET version = ?1";
@@ -22,11 +27,33 @@ const MARK_DIRTY_QUERY: &str = "
     UPDATE schema_migrations
     SET dirty = 1";
 
+const CLEAR_DIRTY_QUERY: &str = "
+    UPDATE schema_migrations
+    SET dirty = 0";
+
+/// Migration query can contain multiple statements, all of them are executed in a single
+/// transaction together with version bump.
 pub struct Migration {
     pub query: &'static str,
     pub id: usize,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Id of the last applied migration.
+    pub version: usize,
+    /// Id of the newest known migration.
+    pub latest: usize,
+    /// Set when migration failed in a way that could leave database partially migrated.
+    pub dirty: bool,
+}
+
+impl MigrationStatus {
+    pub fn pending(&self) -> usize {
+        self.latest.saturating_sub(self.version)
+    }
+}
+
 pub fn migrate(conn: &Connection, migrations: Vec<Migration>) -> Result<(), String> {
     conn.execute(CREATE_SCHEMA_MIGRATIONS_TABLE_QUERY, ())
         .map_err(|err| err.to_string())?;
@@ -45,33 +72,163 @@ pub fn migrate(conn: &Connection, migrations: Vec<Migration>) -> Result<(), Stri
         };
 
     if dirty == 1 {
-        return Err(
-            "database schema_migrations table is dirty, fix issue manually, aborting".to_string(),
-        );
+        return Err(format!(
+            "database was left dirty by failed migration {}, check the database and clear the \
+             flag with `chomp-cli db repair`",
+            version + 1
+        ));
     }
 
     for migration in migrations {
         if migration.id > version {
-            apply_migration(conn, migration.query, migration.id).map_err(|err| err.to_string())?;
+            apply_migration(conn, migration.query, migration.id)
+                .map_err(|err| format!("migration {} failed: {err}", migration.id))?;
         }
     }
 
     Ok(())
 }
 
+/// Reads migration state without changing anything. Database without schema_migrations
+/// table is reported as not migrated at all.
+pub fn status(conn: &Connection, migrations: &[Migration]) -> Result<MigrationStatus, String> {
+    let latest = migrations.iter().map(|m| m.id).max().unwrap_or(0);
+
+    let tables: usize = conn
+        .query_row(SCHEMA_MIGRATIONS_TABLE_EXISTS_QUERY, (), |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    if tables == 0 {
+        return Ok(MigrationStatus {
+            version: 0,
+            latest,
+            dirty: false,
+        });
+    }
+
+    let (version, dirty): (usize, usize) =
+        match conn.query_row(GET_SCHEMA_MIGRATIONS_STATE_QUERY, (), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        }) {
+            Ok(data) => data,
+            Err(rusqlite::Error::QueryReturnedNoRows) => (0, 0),
+            Err(e) => return Err(e.to_string()),
+        };
+
+    Ok(MigrationStatus {
+        version,
+        latest,
+        dirty: dirty == 1,
+    })
+}
+
+/// Checks if existing database is behind given migrations. Fresh database without
+/// schema_migrations table has nothing worth protecting so it's not considered pending.
+pub fn has_pending_migrations(conn: &Connection, migrations: &[Migration]) -> Result<bool, String> {
+    let tables: usize = conn
+        .query_row(SCHEMA_MIGRATIONS_TABLE_EXISTS_QUERY, (), |row| row.get(0))
+        .map_err(|err| err.to_string())?;
+    if tables == 0 {
+        return Ok(false);
+    }
+
+    Ok(status(conn, migrations)?.pending() > 0)
+}
+
+/// Clears dirty flag so that migrations are attempted again, starting from the one after
+/// the last applied version.
+pub fn clear_dirty(conn: &Connection) -> Result<(), String> {
+    conn.execute(CLEAR_DIRTY_QUERY, ())
+        .map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+/// Runs migration and version bump in a transaction. Failed migration is rolled back and
+/// leaves database as it was, dirty flag is only set when rollback or commit itself fails
+/// and database state is unknown.
 fn apply_migration(
     conn: &Connection,
     query: &'static str,
     id: usize,
 ) -> Result<(), rusqlite::Error> {
-    match conn.execute(query, ()) {
-        Ok(_) => {
-            conn.execute(UPDATE_SCHEMA_MIGRATIONS_VERSION_QUERY, params![id])?;
-            Ok(())
-        }
+    let tx = conn.unchecked_transaction()?;
+
+    let result = tx
+        .execute_batch(query)
+        .and_then(|_| tx.execute(UPDATE_SCHEMA_MIGRATIONS_VERSION_QUERY, params![id]));
+
+    match result {
+        Ok(_) => tx.commit().inspect_err(|_| {
+            let _ = conn.execute(MARK_DIRTY_QUERY, ());
+        }),
         Err(e) => {
-            conn.execute(MARK_DIRTY_QUERY, ())?;
+            if tx.rollback().is_err() {
+                conn.execute(MARK_DIRTY_QUERY, ())?;
+            }
             Err(e)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_tables(conn: &Connection) -> usize {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name LIKE 't%'",
+            (),
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back_without_marking_dirty() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let result = migrate(
+            &conn,
+            vec![
+                Migration {
+                    query: "CREATE TABLE t1 (id INTEGER); CREATE TABLE t2 (id INTEGER);",
+                    id: 1,
+                },
+                Migration {
+                    query: "CREATE TABLE t3 (id INTEGER); CREATE TABLE t1 (id INTEGER);",
+                    id: 2,
+                },
+            ],
+        );
+
+        assert!(result.is_err());
+        assert_eq!(count_tables(&conn), 2);
+        assert_eq!(
+            status(&conn, &[]).unwrap(),
+            MigrationStatus {
+                version: 1,
+                latest: 0,
+                dirty: false
+            }
+        );
+    }
+
+    #[test]
+    fn cleared_dirty_flag_allows_migrating_again() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, Vec::new()).unwrap();
+        conn.execute(MARK_DIRTY_QUERY, ()).unwrap();
+
+        let migrations = || {
+            vec![Migration {
+                query: "CREATE TABLE t1 (id INTEGER);",
+                id: 1,
+            }]
+        };
+        assert!(migrate(&conn, migrations()).is_err());
+
+        clear_dirty(&conn).unwrap();
+        migrate(&conn, migrations()).unwrap();
+        assert_eq!(count_tables(&conn), 1);
+    }
+}
@@ -85,3 +85,327 @@ pub const CREATE_PRODUCT_PORTIONS_UNIQUE_NAME_CONSTRAINT_QUERY_8: Migration = Mi
         ON product_portions (name, product_id);",
     id: 8,
 };
+
+pub const CREATE_RECIPES_TABLE_QUERY_9: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS recipes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            product_id INTEGER UNIQUE NOT NULL,
+            weight REAL NOT NULL,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+        );",
+    id: 9,
+};
+
+pub const CREATE_RECIPE_INGREDIENTS_TABLE_QUERY_10: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS recipe_ingredients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            product_id INTEGER NOT NULL,
+            weight REAL NOT NULL,
+            UNIQUE (recipe_id, product_id),
+            FOREIGN KEY (recipe_id) REFERENCES recipes(id) ON DELETE CASCADE,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+        );",
+    id: 10,
+};
+
+pub const ADD_PRODUCTS_FIBER_COLUMN_QUERY_11: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN fiber REAL",
+    id: 11,
+};
+
+pub const ADD_PRODUCTS_SUGAR_COLUMN_QUERY_12: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN sugar REAL",
+    id: 12,
+};
+
+pub const ADD_PRODUCTS_SATURATED_FATS_COLUMN_QUERY_13: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN saturated_fats REAL",
+    id: 13,
+};
+
+pub const ADD_PRODUCTS_SALT_COLUMN_QUERY_14: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN salt REAL",
+    id: 14,
+};
+
+pub const CREATE_NUTRIENTS_TABLE_QUERY_15: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS nutrients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            name TEXT UNIQUE NOT NULL,
+            unit TEXT NOT NULL
+        );",
+    id: 15,
+};
+
+pub const CREATE_PRODUCT_NUTRIENTS_TABLE_QUERY_16: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS product_nutrients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            product_id INTEGER NOT NULL,
+            nutrient_id INTEGER NOT NULL,
+            amount REAL NOT NULL,
+            UNIQUE (product_id, nutrient_id),
+            FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE,
+            FOREIGN KEY (nutrient_id) REFERENCES nutrients(id) ON DELETE CASCADE
+        );",
+    id: 16,
+};
+
+pub const INSERT_DEFAULT_NUTRIENTS_QUERY_17: Migration = Migration {
+    query: "
+        INSERT OR IGNORE INTO nutrients (name, unit)
+        VALUES
+            ('Vitamin A', 'µg'),
+            ('Vitamin C', 'mg'),
+            ('Vitamin D', 'µg'),
+            ('Vitamin B12', 'µg'),
+            ('Calcium', 'mg'),
+            ('Iron', 'mg'),
+            ('Magnesium', 'mg'),
+            ('Potassium', 'mg'),
+            ('Zinc', 'mg');",
+    id: 17,
+};
+
+pub const ADD_PRODUCTS_BARCODE_COLUMN_QUERY_18: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN barcode TEXT;
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_products_barcode ON products (barcode);",
+    id: 18,
+};
+
+pub const CREATE_MEAL_TEMPLATES_TABLE_QUERY_19: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS meal_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            weekday INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            UNIQUE (weekday, name)
+        );
+        INSERT OR IGNORE INTO meal_templates (weekday, position, name)
+        SELECT weekdays.value, meals.position, meals.name
+        FROM (
+            SELECT 0 AS value UNION ALL SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3
+            UNION ALL SELECT 4 UNION ALL SELECT 5 UNION ALL SELECT 6
+        ) AS weekdays
+        CROSS JOIN (
+            SELECT 0 AS position, 'Breakfast' AS name UNION ALL SELECT 1, 'Snack'
+            UNION ALL SELECT 2, 'Lunch' UNION ALL SELECT 3, 'Dinner'
+        ) AS meals;",
+    id: 19,
+};
+
+pub const CREATE_MEAL_PRESETS_TABLES_QUERY_20: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS meal_presets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            name TEXT UNIQUE NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS meal_preset_products (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            preset_id INTEGER NOT NULL,
+            product_id INTEGER NOT NULL,
+            weight REAL NOT NULL,
+            UNIQUE (preset_id, product_id),
+            FOREIGN KEY (preset_id) REFERENCES meal_presets(id) ON DELETE CASCADE,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+        );",
+    id: 20,
+};
+
+pub const CREATE_WEIGHT_GOAL_TABLE_QUERY_21: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS weight_goal (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            weight REAL NOT NULL
+        );",
+    id: 21,
+};
+
+pub const CREATE_MEASUREMENTS_TABLES_QUERY_22: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS measurement_types (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            unit TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS measurements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            type_id INTEGER NOT NULL,
+            day DATE NOT NULL,
+            value REAL NOT NULL,
+            UNIQUE (type_id, day),
+            FOREIGN KEY (type_id) REFERENCES measurement_types(id) ON DELETE CASCADE
+        );
+
+        INSERT OR IGNORE INTO measurement_types (name, unit)
+        VALUES
+            ('Waist', 'cm'),
+            ('Hips', 'cm'),
+            ('Chest', 'cm'),
+            ('Body fat', '%');",
+    id: 22,
+};
+
+pub const CREATE_SETTINGS_TABLE_QUERY_23: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY NOT NULL,
+            value TEXT NOT NULL
+        );",
+    id: 23,
+};
+
+pub const CREATE_NUTRITION_TARGET_SCHEDULE_TABLES_QUERY_24: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS nutrition_target_weekdays (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            target_day TEXT NOT NULL,
+            weekday INTEGER NOT NULL CHECK (weekday BETWEEN 0 AND 6),
+            calories REAL NOT NULL,
+            fats REAL NOT NULL,
+            proteins REAL NOT NULL,
+            carbohydrates REAL NOT NULL,
+            UNIQUE (target_day, weekday)
+        );
+        CREATE TABLE IF NOT EXISTS nutrition_target_meals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            target_day TEXT NOT NULL,
+            meal TEXT NOT NULL COLLATE NOCASE,
+            share REAL NOT NULL CHECK (share >= 0),
+            UNIQUE (target_day, meal)
+        );",
+    id: 24,
+};
+
+pub const CREATE_PRODUCT_SEARCH_INDEX_QUERY_25: Migration = Migration {
+    query: "
+        CREATE TABLE IF NOT EXISTS product_aliases (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            product_id INTEGER NOT NULL,
+            alias TEXT NOT NULL COLLATE NOCASE,
+            UNIQUE (product_id, alias),
+            FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+        );
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS product_search USING fts5 (
+            name,
+            company,
+            aliases,
+            tokenize = 'trigram remove_diacritics 1'
+        );
+
+        INSERT INTO product_search (rowid, name, company, aliases)
+        SELECT id, name, COALESCE(company, ''), ''
+        FROM products;
+
+        CREATE TRIGGER IF NOT EXISTS product_search_insert AFTER INSERT ON products
+        BEGIN
+            INSERT INTO product_search (rowid, name, company, aliases)
+            VALUES (new.id, new.name, COALESCE(new.company, ''), '');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS product_search_update AFTER UPDATE OF name, company ON products
+        BEGIN
+            UPDATE product_search
+            SET name = new.name, company = COALESCE(new.company, '')
+            WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS product_search_delete AFTER DELETE ON products
+        BEGIN
+            DELETE FROM product_search WHERE rowid = old.id;
+            DELETE FROM product_aliases WHERE product_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS product_search_alias_insert AFTER INSERT ON product_aliases
+        BEGIN
+            UPDATE product_search
+            SET aliases = (
+                SELECT GROUP_CONCAT(alias, ' ') FROM product_aliases WHERE product_id = new.product_id
+            )
+            WHERE rowid = new.product_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS product_search_alias_delete AFTER DELETE ON product_aliases
+        BEGIN
+            UPDATE product_search
+            SET aliases = COALESCE((
+                SELECT GROUP_CONCAT(alias, ' ') FROM product_aliases WHERE product_id = old.product_id
+            ), '')
+            WHERE rowid = old.product_id;
+        END;",
+    id: 25,
+};
+
+pub const ADD_PRODUCTS_ARCHIVED_COLUMN_QUERY_26: Migration = Migration {
+    query: "
+        ALTER TABLE products ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+    id: 26,
+};
+
+pub const ADD_MEAL_PRODUCTS_NUTRITION_SNAPSHOT_QUERY_27: Migration = Migration {
+    query: "
+        ALTER TABLE meal_products ADD COLUMN frozen INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE meal_products ADD COLUMN calories REAL;
+        ALTER TABLE meal_products ADD COLUMN fats REAL;
+        ALTER TABLE meal_products ADD COLUMN proteins REAL;
+        ALTER TABLE meal_products ADD COLUMN carbohydrates REAL;
+        ALTER TABLE meal_products ADD COLUMN fiber REAL;
+        ALTER TABLE meal_products ADD COLUMN sugar REAL;
+        ALTER TABLE meal_products ADD COLUMN saturated_fats REAL;
+        ALTER TABLE meal_products ADD COLUMN salt REAL;
+
+        CREATE TABLE IF NOT EXISTS meal_product_nutrients (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            meal_product_id INTEGER NOT NULL,
+            nutrient_id INTEGER NOT NULL,
+            amount REAL NOT NULL,
+            UNIQUE (meal_product_id, nutrient_id),
+            FOREIGN KEY (meal_product_id) REFERENCES meal_products(id) ON DELETE CASCADE,
+            FOREIGN KEY (nutrient_id) REFERENCES nutrients(id) ON DELETE CASCADE
+        );
+
+        CREATE VIEW IF NOT EXISTS meal_product_nutrition AS
+        SELECT
+            meal_products.id,
+            CASE WHEN meal_products.frozen THEN meal_products.calories ELSE products.calories END
+                AS calories,
+            CASE WHEN meal_products.frozen THEN meal_products.fats ELSE products.fats END
+                AS fats,
+            CASE WHEN meal_products.frozen THEN meal_products.proteins ELSE products.proteins END
+                AS proteins,
+            CASE WHEN meal_products.frozen THEN meal_products.carbohydrates
+                ELSE products.carbohydrates END AS carbohydrates,
+            CASE WHEN meal_products.frozen THEN meal_products.fiber ELSE products.fiber END
+                AS fiber,
+            CASE WHEN meal_products.frozen THEN meal_products.sugar ELSE products.sugar END
+                AS sugar,
+            CASE WHEN meal_products.frozen THEN meal_products.saturated_fats
+                ELSE products.saturated_fats END AS saturated_fats,
+            CASE WHEN meal_products.frozen THEN meal_products.salt ELSE products.salt END
+                AS salt
+        FROM meal_products
+        INNER JOIN products ON meal_products.product_id = products.id;
+
+        CREATE VIEW IF NOT EXISTS meal_product_nutrient_amounts AS
+        SELECT meal_products.id AS meal_product_id, product_nutrients.nutrient_id,
+            product_nutrients.amount
+        FROM meal_products
+        INNER JOIN product_nutrients ON meal_products.product_id = product_nutrients.product_id
+        WHERE NOT meal_products.frozen
+        UNION ALL
+        SELECT meal_product_id, nutrient_id, amount
+        FROM meal_product_nutrients;",
+    id: 27,
+};
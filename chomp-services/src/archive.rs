@@ -0,0 +1,1569 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    history::History, meal_template::DEFAULT_MEAL_NAMES, meals::freeze_past_meal_products,
+    recipe::recalculate_recipe, MealBudget, MealTemplate, MeasurementType, Nutrient,
+    NutritionTarget, Product, ProductPortion, ServiceError, WeekdayTarget, Weight,
+};
+
+/// Version of the archive format, bumped whenever the layout changes in a way that older
+/// versions of the app can't read. Version 2 added frozen nutrition of meal products, meal
+/// products of version 1 archives follow their products.
+pub const ARCHIVE_VERSION: u32 = 2;
+
+/// First version with frozen nutrition of meal products.
+const FROZEN_NUTRITION_VERSION: u32 = 2;
+
+/// Snapshot of the whole database. Ids are the ones from exporting database, they are only
+/// used to connect rows with each other and new ids are assigned on import.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    #[serde(default)]
+    pub products: Vec<Product>,
+    #[serde(default)]
+    pub nutrients: Vec<Nutrient>,
+    #[serde(default)]
+    pub product_nutrients: Vec<ArchiveProductNutrient>,
+    #[serde(default)]
+    pub product_aliases: Vec<ArchiveProductAlias>,
+    #[serde(default)]
+    pub product_portions: Vec<ProductPortion>,
+    #[serde(default)]
+    pub recipes: Vec<ArchiveRecipe>,
+    #[serde(default)]
+    pub recipe_ingredients: Vec<ArchiveRecipeIngredient>,
+    #[serde(default)]
+    pub meals: Vec<ArchiveMeal>,
+    #[serde(default)]
+    pub meal_products: Vec<ArchiveMealProduct>,
+    #[serde(default)]
+    pub meal_templates: Vec<MealTemplate>,
+    #[serde(default)]
+    pub meal_presets: Vec<ArchiveMealPreset>,
+    #[serde(default)]
+    pub meal_preset_products: Vec<ArchiveMealPresetProduct>,
+    #[serde(default)]
+    pub nutrition_targets: Vec<NutritionTarget>,
+    #[serde(default)]
+    pub nutrition_target_weekdays: Vec<WeekdayTarget>,
+    #[serde(default)]
+    pub nutrition_target_meals: Vec<MealBudget>,
+    #[serde(default)]
+    pub weights: Vec<Weight>,
+    #[serde(default)]
+    pub weight_goal: Option<f32>,
+    #[serde(default)]
+    pub measurement_types: Vec<MeasurementType>,
+    #[serde(default)]
+    pub measurements: Vec<ArchiveMeasurement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProductNutrient {
+    pub product_id: usize,
+    pub nutrient_id: usize,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProductAlias {
+    pub product_id: usize,
+    pub alias: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRecipe {
+    pub id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRecipeIngredient {
+    pub recipe_id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMeal {
+    pub id: usize,
+    pub day: NaiveDate,
+    pub position: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMealProduct {
+    pub meal_id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+    /// Nutrition the product had when it was eaten, missing while meal product follows the
+    /// product.
+    #[serde(default)]
+    pub nutrition: Option<ArchiveMealProductNutrition>,
+}
+
+/// Nutrition per 100g frozen in meal product, see `freeze_past_meal_products`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMealProductNutrition {
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+    pub fiber: Option<f32>,
+    pub sugar: Option<f32>,
+    pub saturated_fats: Option<f32>,
+    pub salt: Option<f32>,
+    #[serde(default)]
+    pub nutrients: Vec<ArchiveNutrientAmount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveNutrientAmount {
+    pub nutrient_id: usize,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMealPreset {
+    pub id: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMealPresetProduct {
+    pub preset_id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveMeasurement {
+    pub type_id: usize,
+    pub day: NaiveDate,
+    pub value: f32,
+}
+
+/// What to do with archive rows that already exist in database (same product name, same
+/// day of weight and so on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportConflict {
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+impl ImportSummary {
+    /// Counts row based on number of rows inserted by `INSERT ... ON CONFLICT DO NOTHING` and
+    /// returns true if existing row should be overwritten.
+    fn record(&mut self, inserted: usize, conflict: ImportConflict) -> bool {
+        if inserted > 0 {
+            self.created += 1;
+            return false;
+        }
+
+        match conflict {
+            ImportConflict::Skip => {
+                self.skipped += 1;
+                false
+            }
+            ImportConflict::Overwrite => {
+                self.updated += 1;
+                true
+            }
+        }
+    }
+}
+
+pub struct ArchiveService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl ArchiveService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        ArchiveService { db, history }
+    }
+
+    pub fn export(&self) -> Result<Archive, ServiceError> {
+        let db = self.db.borrow();
+
+        Ok(Archive {
+            version: ARCHIVE_VERSION,
+            products: query_all(
+                &db,
+                "
+                SELECT
+                    id, name, company, calories, fats, proteins, carbohydrates,
+                    fiber, sugar, saturated_fats, salt, barcode, archived
+                FROM products
+                ORDER BY id ASC",
+                |row| {
+                    Ok(Product {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        company: row.get(2)?,
+                        calories: row.get(3)?,
+                        fats: row.get(4)?,
+                        proteins: row.get(5)?,
+                        carbohydrates: row.get(6)?,
+                        fiber: row.get(7)?,
+                        sugar: row.get(8)?,
+                        saturated_fats: row.get(9)?,
+                        salt: row.get(10)?,
+                        barcode: row.get(11)?,
+                        archived: row.get(12)?,
+                    })
+                },
+            )?,
+            nutrients: query_all(
+                &db,
+                "
+                SELECT id, name, unit
+                FROM nutrients
+                ORDER BY id ASC",
+                |row| {
+                    Ok(Nutrient {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        unit: row.get(2)?,
+                    })
+                },
+            )?,
+            product_nutrients: query_all(
+                &db,
+                "
+                SELECT product_id, nutrient_id, amount
+                FROM product_nutrients
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveProductNutrient {
+                        product_id: row.get(0)?,
+                        nutrient_id: row.get(1)?,
+                        amount: row.get(2)?,
+                    })
+                },
+            )?,
+            product_aliases: query_all(
+                &db,
+                "
+                SELECT product_id, alias
+                FROM product_aliases
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveProductAlias {
+                        product_id: row.get(0)?,
+                        alias: row.get(1)?,
+                    })
+                },
+            )?,
+            product_portions: query_all(
+                &db,
+                "
+                SELECT id, name, product_id, weight
+                FROM product_portions
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ProductPortion {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        product_id: row.get(2)?,
+                        weight: row.get(3)?,
+                    })
+                },
+            )?,
+            recipes: query_all(
+                &db,
+                "
+                SELECT id, product_id, weight
+                FROM recipes
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveRecipe {
+                        id: row.get(0)?,
+                        product_id: row.get(1)?,
+                        weight: row.get(2)?,
+                    })
+                },
+            )?,
+            recipe_ingredients: query_all(
+                &db,
+                "
+                SELECT recipe_id, product_id, weight
+                FROM recipe_ingredients
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveRecipeIngredient {
+                        recipe_id: row.get(0)?,
+                        product_id: row.get(1)?,
+                        weight: row.get(2)?,
+                    })
+                },
+            )?,
+            meals: query_all(
+                &db,
+                "
+                SELECT id, day, position, name
+                FROM meals
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveMeal {
+                        id: row.get(0)?,
+                        day: row.get(1)?,
+                        position: row.get(2)?,
+                        name: row.get(3)?,
+                    })
+                },
+            )?,
+            meal_products: export_meal_products(&db)?,
+            meal_templates: query_all(
+                &db,
+                "
+                SELECT weekday, position, name
+                FROM meal_templates
+                ORDER BY weekday ASC, position ASC",
+                |row| {
+                    Ok(MealTemplate {
+                        weekday: row.get(0)?,
+                        position: row.get(1)?,
+                        name: row.get(2)?,
+                    })
+                },
+            )?,
+            meal_presets: query_all(
+                &db,
+                "
+                SELECT id, name
+                FROM meal_presets
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveMealPreset {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                    })
+                },
+            )?,
+            meal_preset_products: query_all(
+                &db,
+                "
+                SELECT preset_id, product_id, weight
+                FROM meal_preset_products
+                ORDER BY id ASC",
+                |row| {
+                    Ok(ArchiveMealPresetProduct {
+                        preset_id: row.get(0)?,
+                        product_id: row.get(1)?,
+                        weight: row.get(2)?,
+                    })
+                },
+            )?,
+            nutrition_targets: query_all(
+                &db,
+                "
+                SELECT day, calories, fats, proteins, carbohydrates
+                FROM nutrition_targets
+                ORDER BY day ASC",
+                |row| {
+                    Ok(NutritionTarget {
+                        day: row.get(0)?,
+                        calories: row.get(1)?,
+                        fats: row.get(2)?,
+                        proteins: row.get(3)?,
+                        carbohydrates: row.get(4)?,
+                    })
+                },
+            )?,
+            nutrition_target_weekdays: query_all(
+                &db,
+                "
+                SELECT target_day, weekday, calories, fats, proteins, carbohydrates
+                FROM nutrition_target_weekdays
+                ORDER BY target_day ASC, weekday ASC",
+                |row| {
+                    Ok(WeekdayTarget {
+                        day: row.get(0)?,
+                        weekday: row.get(1)?,
+                        calories: row.get(2)?,
+                        fats: row.get(3)?,
+                        proteins: row.get(4)?,
+                        carbohydrates: row.get(5)?,
+                    })
+                },
+            )?,
+            nutrition_target_meals: query_all(
+                &db,
+                "
+                SELECT target_day, meal, share
+                FROM nutrition_target_meals
+                ORDER BY target_day ASC, meal ASC",
+                |row| {
+                    Ok(MealBudget {
+                        day: row.get(0)?,
+                        meal: row.get(1)?,
+                        share: row.get(2)?,
+                    })
+                },
+            )?,
+            weights: query_all(
+                &db,
+                "
+                SELECT day, weight
+                FROM weights
+                ORDER BY day ASC",
+                |row| {
+                    Ok(Weight {
+                        day: row.get(0)?,
+                        weight: row.get(1)?,
+                    })
+                },
+            )?,
+            weight_goal: db
+                .query_row("SELECT weight FROM weight_goal WHERE id = 1", [], |row| {
+                    row.get(0)
+                })
+                .optional()?,
+            measurement_types: query_all(
+                &db,
+                "
+                SELECT id, name, unit
+                FROM measurement_types
+                ORDER BY id ASC",
+                |row| {
+                    Ok(MeasurementType {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        unit: row.get(2)?,
+                    })
+                },
+            )?,
+            measurements: query_all(
+                &db,
+                "
+                SELECT type_id, day, value
+                FROM measurements
+                ORDER BY day ASC, type_id ASC",
+                |row| {
+                    Ok(ArchiveMeasurement {
+                        type_id: row.get(0)?,
+                        day: row.get(1)?,
+                        value: row.get(2)?,
+                    })
+                },
+            )?,
+        })
+    }
+
+    /// Imports archive into database in a single transaction, either everything is imported
+    /// or nothing is. Rows are matched with existing ones by their natural keys (product name,
+    /// meal day and name, target day...) and conflicts are resolved according to `conflict`.
+    /// Undo history is cleared after import as it can't undo rows the import changed.
+    pub fn import(
+        &self,
+        archive: &Archive,
+        conflict: ImportConflict,
+    ) -> Result<ImportSummary, ServiceError> {
+        if archive.version > ARCHIVE_VERSION {
+            return Err(ServiceError::Custom(format!(
+                "archive version {} is newer than supported version {}",
+                archive.version, ARCHIVE_VERSION
+            )));
+        }
+
+        let mut db = self.db.borrow_mut();
+        let tx = db.transaction()?;
+        let mut summary = ImportSummary::default();
+
+        let nutrient_ids = import_nutrients(&tx, archive, conflict, &mut summary)?;
+        let (product_ids, written_products) =
+            import_products(&tx, archive, conflict, &mut summary)?;
+        import_product_nutrients(
+            &tx,
+            archive,
+            &product_ids,
+            &nutrient_ids,
+            &written_products,
+            &mut summary,
+        )?;
+        import_product_aliases(&tx, archive, &product_ids, &written_products, &mut summary)?;
+        import_product_portions(&tx, archive, conflict, &product_ids, &mut summary)?;
+        let recipe_ids =
+            import_recipes(&tx, archive, &product_ids, &written_products, &mut summary)?;
+        let meal_ids = import_meals(&tx, archive, conflict, &mut summary)?;
+        import_meal_products(
+            &tx,
+            archive,
+            conflict,
+            &meal_ids,
+            &product_ids,
+            &nutrient_ids,
+            &mut summary,
+        )?;
+        import_meal_templates(&tx, archive, conflict, &mut summary)?;
+        let meal_preset_ids = import_meal_presets(&tx, archive, &mut summary)?;
+        import_meal_preset_products(
+            &tx,
+            archive,
+            conflict,
+            &meal_preset_ids,
+            &product_ids,
+            &mut summary,
+        )?;
+        import_nutrition_targets(&tx, archive, conflict, &mut summary)?;
+        import_nutrition_target_weekdays(&tx, archive, conflict, &mut summary)?;
+        import_nutrition_target_meals(&tx, archive, conflict, &mut summary)?;
+        import_weights(&tx, archive, conflict, &mut summary)?;
+        import_weight_goal(&tx, archive, conflict, &mut summary)?;
+        let measurement_type_ids = import_measurement_types(&tx, archive, conflict, &mut summary)?;
+        import_measurements(&tx, archive, conflict, &measurement_type_ids, &mut summary)?;
+
+        for recipe_id in recipe_ids {
+            recalculate_recipe(&tx, recipe_id)?;
+        }
+
+        tx.commit()?;
+        self.history.borrow_mut().clear();
+
+        Ok(summary)
+    }
+}
+
+fn query_all<T, F>(db: &Connection, query: &str, f: F) -> Result<Vec<T>, ServiceError>
+where
+    F: FnMut(&rusqlite::Row<'_>) -> rusqlite::Result<T>,
+{
+    let mut stmt = db.prepare(query)?;
+    let rows = stmt
+        .query_map([], f)
+        .map_err(ServiceError::from)?
+        .collect::<Result<Vec<T>, _>>()?;
+
+    Ok(rows)
+}
+
+fn export_meal_products(db: &Connection) -> Result<Vec<ArchiveMealProduct>, ServiceError> {
+    let mut nutrients: HashMap<usize, Vec<ArchiveNutrientAmount>> = HashMap::new();
+    for (meal_product_id, amount) in query_all(
+        db,
+        "
+        SELECT meal_product_id, nutrient_id, amount
+        FROM meal_product_nutrients
+        ORDER BY id ASC",
+        |row| {
+            Ok((
+                row.get(0)?,
+                ArchiveNutrientAmount {
+                    nutrient_id: row.get(1)?,
+                    amount: row.get(2)?,
+                },
+            ))
+        },
+    )? {
+        nutrients.entry(meal_product_id).or_default().push(amount);
+    }
+
+    query_all(
+        db,
+        "
+        SELECT
+            id, meal_id, product_id, weight, frozen, calories, fats, proteins, carbohydrates,
+            fiber, sugar, saturated_fats, salt
+        FROM meal_products
+        ORDER BY id ASC",
+        |row| {
+            let id: usize = row.get(0)?;
+            let nutrition = if row.get(4)? {
+                Some(ArchiveMealProductNutrition {
+                    calories: row.get(5)?,
+                    fats: row.get(6)?,
+                    proteins: row.get(7)?,
+                    carbohydrates: row.get(8)?,
+                    fiber: row.get(9)?,
+                    sugar: row.get(10)?,
+                    saturated_fats: row.get(11)?,
+                    salt: row.get(12)?,
+                    nutrients: nutrients.remove(&id).unwrap_or_default(),
+                })
+            } else {
+                None
+            };
+
+            Ok(ArchiveMealProduct {
+                meal_id: row.get(1)?,
+                product_id: row.get(2)?,
+                weight: row.get(3)?,
+                nutrition,
+            })
+        },
+    )
+}
+
+fn mapped_id(ids: &HashMap<usize, usize>, id: usize, kind: &str) -> Result<usize, ServiceError> {
+    ids.get(&id).copied().ok_or(ServiceError::Custom(format!(
+        "archive references missing {kind} {id}"
+    )))
+}
+
+fn import_nutrients(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<HashMap<usize, usize>, ServiceError> {
+    let mut ids = HashMap::new();
+
+    for nutrient in &archive.nutrients {
+        let inserted = tx.execute(
+            "
+            INSERT INTO nutrients (name, unit)
+            VALUES (?1, ?2)
+            ON CONFLICT (name) DO NOTHING",
+            params![nutrient.name, nutrient.unit],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE nutrients
+                SET unit=?1
+                WHERE name = ?2",
+                params![nutrient.unit, nutrient.name],
+            )?;
+        }
+
+        let id: usize = tx.query_row(
+            "SELECT id FROM nutrients WHERE name = ?1",
+            params![nutrient.name],
+            |row| row.get(0),
+        )?;
+        ids.insert(nutrient.id, id);
+    }
+
+    Ok(ids)
+}
+
+/// Returns mapping of archive product ids to database ids and set of archive ids of products
+/// that were created or overwritten. Rows owned by products (nutrients, recipes) are only
+/// imported for those, skipped products keep what they already have.
+fn import_products(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(HashMap<usize, usize>, HashSet<usize>), ServiceError> {
+    let mut ids = HashMap::new();
+    let mut written = HashSet::new();
+
+    for product in &archive.products {
+        // Product can conflict with existing one either by name or by barcode, both are
+        // treated as the same product.
+        let inserted = tx.execute(
+            "
+            INSERT INTO products (
+                name, company, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, barcode, archived
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT DO NOTHING",
+            params![
+                product.name,
+                product.company,
+                product.calories,
+                product.fats,
+                product.proteins,
+                product.carbohydrates,
+                product.fiber,
+                product.sugar,
+                product.saturated_fats,
+                product.salt,
+                product.barcode,
+                product.archived,
+            ],
+        )?;
+
+        let id: usize = tx.query_row(
+            "
+            SELECT id
+            FROM products
+            WHERE name = ?1 OR barcode = ?2
+            ORDER BY name = ?1 DESC
+            LIMIT 1",
+            params![product.name, product.barcode],
+            |row| row.get(0),
+        )?;
+
+        // Overwriting product found by name with barcode of another product would violate
+        // uniqueness of barcodes, such product is skipped.
+        let barcode_taken: bool = inserted == 0
+            && tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM products WHERE barcode = ?1 AND id != ?2)",
+                params![product.barcode, id],
+                |row| row.get(0),
+            )?;
+
+        if barcode_taken {
+            summary.skipped += 1;
+        } else if summary.record(inserted, conflict) {
+            freeze_past_meal_products(tx, id)?;
+            tx.execute(
+                "
+                UPDATE products
+                SET name=?1, company=?2, calories=?3, fats=?4, proteins=?5, carbohydrates=?6,
+                    fiber=?7, sugar=?8, saturated_fats=?9, salt=?10, barcode=?11, archived=?12
+                WHERE id = ?13",
+                params![
+                    product.name,
+                    product.company,
+                    product.calories,
+                    product.fats,
+                    product.proteins,
+                    product.carbohydrates,
+                    product.fiber,
+                    product.sugar,
+                    product.saturated_fats,
+                    product.salt,
+                    product.barcode,
+                    product.archived,
+                    id,
+                ],
+            )?;
+        }
+        if inserted > 0 || (conflict == ImportConflict::Overwrite && !barcode_taken) {
+            written.insert(product.id);
+        }
+
+        ids.insert(product.id, id);
+    }
+
+    Ok((ids, written))
+}
+
+fn import_product_nutrients(
+    tx: &Transaction,
+    archive: &Archive,
+    product_ids: &HashMap<usize, usize>,
+    nutrient_ids: &HashMap<usize, usize>,
+    written_products: &HashSet<usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for product_id in written_products {
+        tx.execute(
+            "DELETE FROM product_nutrients WHERE product_id = ?1",
+            params![mapped_id(product_ids, *product_id, "product")?],
+        )?;
+    }
+
+    for product_nutrient in &archive.product_nutrients {
+        if !written_products.contains(&product_nutrient.product_id) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        tx.execute(
+            "
+            INSERT INTO product_nutrients (product_id, nutrient_id, amount)
+            VALUES (?1, ?2, ?3)",
+            params![
+                mapped_id(product_ids, product_nutrient.product_id, "product")?,
+                mapped_id(nutrient_ids, product_nutrient.nutrient_id, "nutrient")?,
+                product_nutrient.amount,
+            ],
+        )?;
+        summary.created += 1;
+    }
+
+    Ok(())
+}
+
+/// Replaces aliases of created or overwritten products, like nutrients.
+fn import_product_aliases(
+    tx: &Transaction,
+    archive: &Archive,
+    product_ids: &HashMap<usize, usize>,
+    written_products: &HashSet<usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for product_id in written_products {
+        tx.execute(
+            "DELETE FROM product_aliases WHERE product_id = ?1",
+            params![mapped_id(product_ids, *product_id, "product")?],
+        )?;
+    }
+
+    for alias in &archive.product_aliases {
+        if !written_products.contains(&alias.product_id) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        tx.execute(
+            "
+            INSERT INTO product_aliases (product_id, alias)
+            VALUES (?1, ?2)
+            ON CONFLICT (product_id, alias) DO NOTHING",
+            params![
+                mapped_id(product_ids, alias.product_id, "product")?,
+                alias.alias,
+            ],
+        )?;
+        summary.created += 1;
+    }
+
+    Ok(())
+}
+
+fn import_product_portions(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    product_ids: &HashMap<usize, usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for portion in &archive.product_portions {
+        let product_id = mapped_id(product_ids, portion.product_id, "product")?;
+        let inserted = tx.execute(
+            "
+            INSERT INTO product_portions (name, product_id, weight)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (name, product_id) DO NOTHING",
+            params![portion.name, product_id, portion.weight],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE product_portions
+                SET weight=?1
+                WHERE name = ?2 AND product_id = ?3",
+                params![portion.weight, portion.name, product_id],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Imports recipes of created or overwritten products, ingredients of those recipes are
+/// replaced with ones from archive. Returns database ids of imported recipes.
+fn import_recipes(
+    tx: &Transaction,
+    archive: &Archive,
+    product_ids: &HashMap<usize, usize>,
+    written_products: &HashSet<usize>,
+    summary: &mut ImportSummary,
+) -> Result<Vec<usize>, ServiceError> {
+    let mut ids = HashMap::new();
+
+    for recipe in &archive.recipes {
+        if !written_products.contains(&recipe.product_id) {
+            summary.skipped += 1;
+            continue;
+        }
+
+        let product_id = mapped_id(product_ids, recipe.product_id, "product")?;
+        let existing: Option<usize> = tx
+            .query_row(
+                "SELECT id FROM recipes WHERE product_id = ?1",
+                params![product_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let id = match existing {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE recipes SET weight=?1 WHERE id = ?2",
+                    params![recipe.weight, id],
+                )?;
+                tx.execute(
+                    "DELETE FROM recipe_ingredients WHERE recipe_id = ?1",
+                    params![id],
+                )?;
+                summary.updated += 1;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO recipes (product_id, weight) VALUES (?1, ?2)",
+                    params![product_id, recipe.weight],
+                )?;
+                summary.created += 1;
+                tx.last_insert_rowid() as usize
+            }
+        };
+        ids.insert(recipe.id, id);
+    }
+
+    for ingredient in &archive.recipe_ingredients {
+        let Some(recipe_id) = ids.get(&ingredient.recipe_id) else {
+            summary.skipped += 1;
+            continue;
+        };
+
+        tx.execute(
+            "
+            INSERT INTO recipe_ingredients (recipe_id, product_id, weight)
+            VALUES (?1, ?2, ?3)",
+            params![
+                recipe_id,
+                mapped_id(product_ids, ingredient.product_id, "product")?,
+                ingredient.weight,
+            ],
+        )?;
+        summary.created += 1;
+    }
+
+    Ok(ids.into_values().collect())
+}
+
+fn import_meals(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<HashMap<usize, usize>, ServiceError> {
+    let mut ids = HashMap::new();
+
+    for meal in &archive.meals {
+        let day = format!("{}", meal.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO meals (day, position, name)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (day, name) DO NOTHING",
+            params![day, meal.position, meal.name],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE meals
+                SET position=?1
+                WHERE day = ?2 AND name = ?3",
+                params![meal.position, day, meal.name],
+            )?;
+        }
+
+        let id: usize = tx.query_row(
+            "SELECT id FROM meals WHERE day = ?1 AND name = ?2",
+            params![day, meal.name],
+            |row| row.get(0),
+        )?;
+        ids.insert(meal.id, id);
+    }
+
+    Ok(ids)
+}
+
+fn import_meal_products(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    meal_ids: &HashMap<usize, usize>,
+    product_ids: &HashMap<usize, usize>,
+    nutrient_ids: &HashMap<usize, usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for meal_product in &archive.meal_products {
+        let meal_id = mapped_id(meal_ids, meal_product.meal_id, "meal")?;
+        let product_id = mapped_id(product_ids, meal_product.product_id, "product")?;
+        let nutrition = meal_product
+            .nutrition
+            .as_ref()
+            .filter(|_| archive.version >= FROZEN_NUTRITION_VERSION);
+        let nutrition_args = params![
+            nutrition.is_some(),
+            nutrition.map(|n| n.calories),
+            nutrition.map(|n| n.fats),
+            nutrition.map(|n| n.proteins),
+            nutrition.map(|n| n.carbohydrates),
+            nutrition.and_then(|n| n.fiber),
+            nutrition.and_then(|n| n.sugar),
+            nutrition.and_then(|n| n.saturated_fats),
+            nutrition.and_then(|n| n.salt),
+            meal_product.weight,
+            meal_id,
+            product_id,
+        ];
+
+        let inserted = tx.execute(
+            "
+            INSERT INTO meal_products (
+                frozen, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, weight, meal_id, product_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT (meal_id, product_id) DO NOTHING",
+            nutrition_args,
+        )?;
+        let overwrite = summary.record(inserted, conflict);
+        if overwrite {
+            tx.execute(
+                "
+                UPDATE meal_products
+                SET frozen=?1, calories=?2, fats=?3, proteins=?4, carbohydrates=?5,
+                    fiber=?6, sugar=?7, saturated_fats=?8, salt=?9, weight=?10
+                WHERE meal_id = ?11 AND product_id = ?12",
+                nutrition_args,
+            )?;
+        }
+
+        if inserted > 0 || overwrite {
+            let meal_product_id: usize = tx.query_row(
+                "SELECT id FROM meal_products WHERE meal_id = ?1 AND product_id = ?2",
+                params![meal_id, product_id],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "DELETE FROM meal_product_nutrients WHERE meal_product_id = ?1",
+                params![meal_product_id],
+            )?;
+            for amount in nutrition.iter().flat_map(|n| &n.nutrients) {
+                tx.execute(
+                    "
+                    INSERT INTO meal_product_nutrients (meal_product_id, nutrient_id, amount)
+                    VALUES (?1, ?2, ?3)",
+                    params![
+                        meal_product_id,
+                        mapped_id(nutrient_ids, amount.nutrient_id, "nutrient")?,
+                        amount.amount,
+                    ],
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Presets only have a name, so there is nothing to overwrite, products of existing presets are
+/// handled in `import_meal_preset_products`.
+fn import_meal_presets(
+    tx: &Transaction,
+    archive: &Archive,
+    summary: &mut ImportSummary,
+) -> Result<HashMap<usize, usize>, ServiceError> {
+    let mut ids = HashMap::new();
+
+    for preset in &archive.meal_presets {
+        let inserted = tx.execute(
+            "
+            INSERT INTO meal_presets (name)
+            VALUES (?1)
+            ON CONFLICT (name) DO NOTHING",
+            params![preset.name],
+        )?;
+        if inserted > 0 {
+            summary.created += 1;
+        }
+
+        let id: usize = tx.query_row(
+            "SELECT id FROM meal_presets WHERE name = ?1",
+            params![preset.name],
+            |row| row.get(0),
+        )?;
+        ids.insert(preset.id, id);
+    }
+
+    Ok(ids)
+}
+
+fn import_meal_preset_products(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    preset_ids: &HashMap<usize, usize>,
+    product_ids: &HashMap<usize, usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for preset_product in &archive.meal_preset_products {
+        let preset_id = mapped_id(preset_ids, preset_product.preset_id, "meal preset")?;
+        let product_id = mapped_id(product_ids, preset_product.product_id, "product")?;
+        let inserted = tx.execute(
+            "
+            INSERT INTO meal_preset_products (preset_id, product_id, weight)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (preset_id, product_id) DO NOTHING",
+            params![preset_id, product_id, preset_product.weight],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE meal_preset_products
+                SET weight=?1
+                WHERE preset_id = ?2 AND product_id = ?3",
+                params![preset_product.weight, preset_id, product_id],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Meal templates are imported per weekday, layout of a weekday is either kept as it is or
+/// replaced as a whole.
+fn import_meal_templates(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    let weekdays: HashSet<u32> = archive.meal_templates.iter().map(|t| t.weekday).collect();
+
+    for weekday in weekdays {
+        let mut templates: Vec<&MealTemplate> = archive
+            .meal_templates
+            .iter()
+            .filter(|t| t.weekday == weekday)
+            .collect();
+        templates.sort_by_key(|t| t.position);
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+
+        let existing: Vec<String> = tx
+            .prepare(
+                "
+                SELECT name
+                FROM meal_templates
+                WHERE weekday = ?1
+                ORDER BY position ASC",
+            )?
+            .query_map(params![weekday], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        // Every weekday starts with the default layout, it is replaced as if it wasn't there.
+        if existing == names {
+            summary.skipped += 1;
+            continue;
+        } else if existing.is_empty() || existing == DEFAULT_MEAL_NAMES {
+            summary.created += 1;
+        } else if conflict == ImportConflict::Skip {
+            summary.skipped += 1;
+            continue;
+        } else {
+            summary.updated += 1;
+        }
+        tx.execute(
+            "DELETE FROM meal_templates WHERE weekday = ?1",
+            params![weekday],
+        )?;
+
+        for template in templates {
+            tx.execute(
+                "
+                INSERT INTO meal_templates (weekday, position, name)
+                VALUES (?1, ?2, ?3)",
+                params![template.weekday, template.position, template.name],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import_nutrition_targets(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for target in &archive.nutrition_targets {
+        let day = format!("{}", target.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO nutrition_targets (day, calories, fats, proteins, carbohydrates)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (day) DO NOTHING",
+            params![
+                day,
+                target.calories,
+                target.fats,
+                target.proteins,
+                target.carbohydrates
+            ],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE nutrition_targets
+                SET calories=?1, fats=?2, proteins=?3, carbohydrates=?4
+                WHERE day = ?5",
+                params![
+                    target.calories,
+                    target.fats,
+                    target.proteins,
+                    target.carbohydrates,
+                    day
+                ],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import_nutrition_target_weekdays(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for weekday in &archive.nutrition_target_weekdays {
+        let day = format!("{}", weekday.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO nutrition_target_weekdays
+                (target_day, weekday, calories, fats, proteins, carbohydrates)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT (target_day, weekday) DO NOTHING",
+            params![
+                day,
+                weekday.weekday,
+                weekday.calories,
+                weekday.fats,
+                weekday.proteins,
+                weekday.carbohydrates
+            ],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE nutrition_target_weekdays
+                SET calories=?1, fats=?2, proteins=?3, carbohydrates=?4
+                WHERE target_day = ?5 AND weekday = ?6",
+                params![
+                    weekday.calories,
+                    weekday.fats,
+                    weekday.proteins,
+                    weekday.carbohydrates,
+                    day,
+                    weekday.weekday
+                ],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import_nutrition_target_meals(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for budget in &archive.nutrition_target_meals {
+        let day = format!("{}", budget.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO nutrition_target_meals (target_day, meal, share)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (target_day, meal) DO NOTHING",
+            params![day, budget.meal, budget.share],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "UPDATE nutrition_target_meals SET share=?1 WHERE target_day = ?2 AND meal = ?3",
+                params![budget.share, day, budget.meal],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import_weights(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for weight in &archive.weights {
+        let day = format!("{}", weight.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO weights (day, weight)
+            VALUES (?1, ?2)
+            ON CONFLICT (day) DO NOTHING",
+            params![day, weight.weight],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "UPDATE weights SET weight=?1 WHERE day = ?2",
+                params![weight.weight, day],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn import_weight_goal(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    let Some(weight) = archive.weight_goal else {
+        return Ok(());
+    };
+
+    let inserted = tx.execute(
+        "
+        INSERT INTO weight_goal (id, weight)
+        VALUES (1, ?1)
+        ON CONFLICT (id) DO NOTHING",
+        params![weight],
+    )?;
+    if summary.record(inserted, conflict) {
+        tx.execute(
+            "UPDATE weight_goal SET weight=?1 WHERE id = 1",
+            params![weight],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn import_measurement_types(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    summary: &mut ImportSummary,
+) -> Result<HashMap<usize, usize>, ServiceError> {
+    let mut ids = HashMap::new();
+
+    for measurement_type in &archive.measurement_types {
+        let inserted = tx.execute(
+            "
+            INSERT INTO measurement_types (name, unit)
+            VALUES (?1, ?2)
+            ON CONFLICT (name) DO NOTHING",
+            params![measurement_type.name, measurement_type.unit],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "
+                UPDATE measurement_types
+                SET unit=?1
+                WHERE name = ?2",
+                params![measurement_type.unit, measurement_type.name],
+            )?;
+        }
+
+        let id: usize = tx.query_row(
+            "SELECT id FROM measurement_types WHERE name = ?1",
+            params![measurement_type.name],
+            |row| row.get(0),
+        )?;
+        ids.insert(measurement_type.id, id);
+    }
+
+    Ok(ids)
+}
+
+fn import_measurements(
+    tx: &Transaction,
+    archive: &Archive,
+    conflict: ImportConflict,
+    type_ids: &HashMap<usize, usize>,
+    summary: &mut ImportSummary,
+) -> Result<(), ServiceError> {
+    for measurement in &archive.measurements {
+        let type_id = mapped_id(type_ids, measurement.type_id, "measurement type")?;
+        let day = format!("{}", measurement.day.format("%Y-%m-%d"));
+        let inserted = tx.execute(
+            "
+            INSERT INTO measurements (type_id, day, value)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (type_id, day) DO NOTHING",
+            params![type_id, day, measurement.value],
+        )?;
+        if summary.record(inserted, conflict) {
+            tx.execute(
+                "UPDATE measurements SET value=?1 WHERE type_id = ?2 AND day = ?3",
+                params![measurement.value, type_id, day],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+    use chrono::Weekday;
+
+    use crate::{test_utils::product, AddMealProduct, CreateUpdateProduct, Services};
+
+    use super::*;
+
+    fn oats() -> CreateUpdateProduct {
+        CreateUpdateProduct {
+            calories: 380.0,
+            ..product("Oats")
+        }
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let source = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        source.product.create(oats()).unwrap();
+        let product_id = source.product.list().unwrap()[0].id;
+        let meal_id = source.meal.list_or_create_default(day).unwrap()[0].id;
+        source
+            .meal
+            .add_product(AddMealProduct {
+                meal_id,
+                product_id,
+                weight: 50.0,
+            })
+            .unwrap();
+        source.weight.create(Weight::new(day, 80.0)).unwrap();
+        let layout = ["Brunch", "Dinner"].map(String::from);
+        source.meal_template.replace(Weekday::Sat, &layout).unwrap();
+
+        let archive = source.archive.export().unwrap();
+        let json = serde_json::to_string(&archive).unwrap();
+        let archive: Archive = serde_json::from_str(&json).unwrap();
+
+        let target = Services::new(prepare_in_memory_conn().unwrap());
+        let summary = target
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert!(summary.created > 0);
+
+        let meals = target.meal.list(day).unwrap();
+        assert_eq!(meals[0].products.len(), 1);
+        assert_eq!(meals[0].products[0].calories, 190.0);
+        assert_eq!(target.weight.read(day).unwrap().weight, 80.0);
+        let names: Vec<String> = target
+            .meal_template
+            .list(Weekday::Sat)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, layout);
+
+        let summary = target
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert_eq!(summary.created, 0);
+    }
+
+    #[test]
+    fn skip_product_whose_barcode_belongs_to_another() {
+        let source = Services::new(prepare_in_memory_conn().unwrap());
+        source
+            .product
+            .create(CreateUpdateProduct {
+                barcode: Some("96385074".to_string()),
+                ..oats()
+            })
+            .unwrap();
+        let archive = source.archive.export().unwrap();
+
+        let target = Services::new(prepare_in_memory_conn().unwrap());
+        target.product.create(oats()).unwrap();
+        target
+            .product
+            .create(CreateUpdateProduct {
+                barcode: Some("96385074".to_string()),
+                ..product("Oat flakes")
+            })
+            .unwrap();
+
+        target
+            .archive
+            .import(&archive, ImportConflict::Overwrite)
+            .unwrap();
+        let oats = target.product.search("Oats").unwrap().remove(0);
+        assert_eq!(oats.barcode, None);
+        assert_eq!(
+            target.product.read_by_barcode("96385074").unwrap().name,
+            "Oat flakes"
+        );
+    }
+
+    #[test]
+    fn import_frozen_nutrition_only_from_version_2() {
+        let source = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        source.product.create(oats()).unwrap();
+        let product_id = source.product.list().unwrap()[0].id;
+        let meal_id = source.meal.list_or_create_default(day).unwrap()[0].id;
+        source
+            .meal
+            .add_product(AddMealProduct {
+                meal_id,
+                product_id,
+                weight: 50.0,
+            })
+            .unwrap();
+        source
+            .product
+            .update(
+                product_id,
+                CreateUpdateProduct {
+                    calories: 400.0,
+                    ..oats()
+                },
+            )
+            .unwrap();
+        let mut archive = source.archive.export().unwrap();
+        assert_eq!(archive.version, 2);
+
+        let target = Services::new(prepare_in_memory_conn().unwrap());
+        target
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert_eq!(target.meal.day_stats(day).unwrap().calories, 190.0);
+
+        archive.version = 1;
+        let target = Services::new(prepare_in_memory_conn().unwrap());
+        target
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert_eq!(target.meal.day_stats(day).unwrap().calories, 200.0);
+    }
+
+    #[test]
+    fn import_overwrites_conflicts() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        services.weight.create(Weight::new(day, 80.0)).unwrap();
+
+        let mut archive = services.archive.export().unwrap();
+        archive.weights[0].weight = 75.0;
+
+        services
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert_eq!(services.weight.read(day).unwrap().weight, 80.0);
+
+        services
+            .archive
+            .import(&archive, ImportConflict::Overwrite)
+            .unwrap();
+        assert_eq!(services.weight.read(day).unwrap().weight, 75.0);
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use rusqlite::Connection;
+
+use super::{history::History, ServiceError};
+
+pub struct BackupService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl BackupService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        BackupService { db, history }
+    }
+
+    pub fn create(&self) -> Result<PathBuf, ServiceError> {
+        let db = self.db.borrow();
+        chomp_db::create_backup(&db).map_err(ServiceError::from)
+    }
+
+    /// Creates backup if there's none from today yet, returns path of created backup.
+    pub fn create_if_due(&self) -> Result<Option<PathBuf>, ServiceError> {
+        let db = self.db.borrow();
+        chomp_db::create_backup_if_due(&db).map_err(ServiceError::from)
+    }
+
+    /// Lists existing backups, newest first.
+    pub fn list(&self) -> Result<Vec<PathBuf>, ServiceError> {
+        let db = self.db.borrow();
+        chomp_db::list_backups(&db).map_err(ServiceError::from)
+    }
+
+    /// Replaces the database with given backup, undo history is cleared as it was recorded
+    /// against the replaced data. It is cleared even when restoring fails, the database may be
+    /// replaced already, e.g. when migrating the restored one fails.
+    pub fn restore(&self, backup_path: &Path) -> Result<(), ServiceError> {
+        let mut db = self.db.borrow_mut();
+        let result = chomp_db::restore_backup(&mut db, backup_path);
+        self.history.borrow_mut().clear();
+
+        result.map_err(ServiceError::from)
+    }
+}
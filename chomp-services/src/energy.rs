@@ -0,0 +1,201 @@
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::{Days, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{weight_trend::smooth, NutritionTarget, ServiceError, Weight};
+
+/// Energy stored in one kilogram of body weight, mix of fat and lean tissue.
+pub const KCAL_PER_KG: f32 = 7700.0;
+/// Default amount of days used to estimate maintenance calories.
+pub const ENERGY_WINDOW_DAYS: u64 = 28;
+/// Shortest time between first and last weigh-in of the window that gives usable rate of change.
+const MIN_WEIGHT_SPAN_DAYS: i64 = 7;
+
+/// Maintenance calories estimated from logged intake and change of the weight trend. Weight
+/// change that is not explained by intake is the difference between intake and expenditure.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnergyEstimate {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days_logged: usize,
+    /// Average calories of days with anything logged, days without meals are not counted as
+    /// zero because they are most likely days that were not logged.
+    pub average_intake: f32,
+    /// Change of the trend weight in kg per week.
+    pub weekly_change: f32,
+    pub maintenance: f32,
+}
+
+impl EnergyEstimate {
+    /// Target with calories of maintenance plus `adjustment` (negative for deficit). Macros
+    /// keep the split of `base` target.
+    pub fn target(
+        &self,
+        day: NaiveDate,
+        adjustment: f32,
+        base: &NutritionTarget,
+    ) -> NutritionTarget {
+        let calories = (self.maintenance + adjustment).max(0.0).round();
+        let ratio = if base.calories > 0.0 {
+            calories / base.calories
+        } else {
+            0.0
+        };
+
+        NutritionTarget::new(
+            day,
+            calories,
+            (base.fats * ratio).round(),
+            (base.proteins * ratio).round(),
+            (base.carbohydrates * ratio).round(),
+        )
+    }
+}
+
+pub struct EnergyService {
+    db: Rc<RefCell<Connection>>,
+}
+
+impl EnergyService {
+    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
+        EnergyService { db }
+    }
+
+    /// Estimates maintenance calories over `window_days` ending on `to` (inclusive). Returns
+    /// `None` when less than half of the days are logged or weigh-ins don't span at least a
+    /// week of the window.
+    pub fn estimate(
+        &self,
+        to: NaiveDate,
+        window_days: u64,
+    ) -> Result<Option<EnergyEstimate>, ServiceError> {
+        let Some(from) = to
+            .checked_sub_days(Days::new(window_days))
+            .and_then(|d| d.succ_opt())
+        else {
+            return Ok(None);
+        };
+
+        let intake_query = "
+            SELECT SUM(meal_product_nutrition.calories * meal_products.weight / 100)
+            FROM meals
+            INNER JOIN meal_products ON meals.id = meal_products.meal_id
+            INNER JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
+            WHERE meals.day BETWEEN ?1 AND ?2
+            GROUP BY meals.day";
+        // Trend needs weights from before the window to settle down.
+        let weights_query = "
+            SELECT day, weight
+            FROM weights
+            WHERE day <= ?1
+            ORDER BY day ASC";
+        let from_arg = format!("{}", from.format("%Y-%m-%d"));
+        let to_arg = format!("{}", to.format("%Y-%m-%d"));
+
+        let db = self.db.borrow();
+
+        let mut stmt = db.prepare(intake_query)?;
+        let intake = stmt
+            .query_map(params![from_arg, to_arg], |row| row.get(0))
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<f32>, _>>()?;
+
+        let mut stmt = db.prepare(weights_query)?;
+        let weights = stmt
+            .query_map(params![to_arg], |row| {
+                Ok(Weight {
+                    day: row.get(0)?,
+                    weight: row.get(1)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<Weight>, _>>()?;
+
+        if intake.is_empty() || (intake.len() as u64) * 2 < window_days {
+            return Ok(None);
+        }
+
+        let trend = smooth(&weights);
+        let mut in_window = trend.iter().filter(|t| t.day >= from);
+        let (Some(first), Some(last)) = (in_window.next(), in_window.next_back()) else {
+            return Ok(None);
+        };
+        let span = (last.day - first.day).num_days();
+        if span < MIN_WEIGHT_SPAN_DAYS {
+            return Ok(None);
+        }
+
+        let daily_change = (last.trend - first.trend) / span as f32;
+        let average_intake = intake.iter().sum::<f32>() / intake.len() as f32;
+
+        Ok(Some(EnergyEstimate {
+            from,
+            to,
+            days_logged: intake.len(),
+            average_intake,
+            weekly_change: daily_change * 7.0,
+            maintenance: average_intake - daily_change * KCAL_PER_KG,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, AddMealProduct, Services};
+
+    use super::*;
+
+    #[test]
+    fn maintenance_accounts_for_weight_loss() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services.product.create(product("Rice")).unwrap();
+        let product_id = services.product.list().unwrap()[0].id;
+
+        // 2000 kcal a day while losing 0.1 kg a day, which is 770 kcal of deficit.
+        let first_day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        for i in 0..28 {
+            let day = first_day + Days::new(i);
+            let meal_id = services.meal.list_or_create_default(day).unwrap()[0].id;
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id,
+                    product_id,
+                    weight: 2000.0,
+                })
+                .unwrap();
+            services
+                .weight
+                .create(Weight::new(day, 90.0 - i as f32 * 0.1))
+                .unwrap();
+        }
+
+        let last_day = first_day + Days::new(27);
+        let estimate = services
+            .energy
+            .estimate(last_day, ENERGY_WINDOW_DAYS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(estimate.days_logged, 28);
+        assert_eq!(estimate.average_intake, 2000.0);
+        // Trend lags behind at the start, so the rate is a bit smaller than the real one.
+        assert!(estimate.maintenance > 2400.0 && estimate.maintenance < 2800.0);
+
+        let base = NutritionTarget::new(first_day, 2000.0, 50.0, 150.0, 200.0);
+        let target = estimate.target(last_day, -500.0, &base);
+        assert_eq!(target.calories, (estimate.maintenance - 500.0).round());
+        assert!(target.proteins > 150.0);
+
+        assert_eq!(
+            services
+                .energy
+                .estimate(first_day + Days::new(5), ENERGY_WINDOW_DAYS)
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -62,3 +62,9 @@ impl From<SqliteError> for ServiceError {
         }
     }
 }
+
+impl From<chomp_db::Error> for ServiceError {
+    fn from(err: chomp_db::Error) -> Self {
+        ServiceError::Custom(err.to_string())
+    }
+}
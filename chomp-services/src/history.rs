@@ -0,0 +1,474 @@
+//! Undo and redo of changes made through the services. Every recorded change stores rows it
+//! touched as they were before and after it, undoing restores the former and redoing the latter.
+
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::NaiveDate;
+use rusqlite::{params, params_from_iter, types::Value, Connection, Transaction};
+
+use super::ServiceError;
+
+/// Number of changes that can be undone, older ones are forgotten.
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    table: &'static str,
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl Row {
+    fn key(&self) -> &Value {
+        let column = key_column(self.table);
+        let index = self
+            .columns
+            .iter()
+            .position(|c| c == column)
+            .expect("captured row always contains key column");
+
+        &self.values[index]
+    }
+
+    fn is_same(&self, other: &Row) -> bool {
+        self.table == other.table && self.key() == other.key()
+    }
+}
+
+/// Tables keyed by day or key instead of id.
+fn key_column(table: &str) -> &'static str {
+    match table {
+        "weights" | "nutrition_targets" => "day",
+        "settings" => "key",
+        _ => "id",
+    }
+}
+
+/// Rows of a table that a change can touch, selected by SQL condition with a single parameter.
+pub(crate) struct Scope {
+    table: &'static str,
+    filter: String,
+    param: Option<Value>,
+}
+
+impl Scope {
+    pub(crate) fn new(table: &'static str, filter: impl Into<String>, param: Value) -> Self {
+        Scope {
+            table,
+            filter: filter.into(),
+            param: Some(param),
+        }
+    }
+
+    /// Rows the change inserts into the table, their ids are above the largest one before it.
+    pub(crate) fn inserted(table: &'static str) -> Self {
+        Scope {
+            table,
+            filter: "id > ?1".to_string(),
+            param: None,
+        }
+    }
+
+    /// All rows of the table.
+    pub(crate) fn all(table: &'static str) -> Self {
+        Scope::new(table, "?1 IS NULL", Value::Null)
+    }
+
+    fn resolve(&self, db: &Connection) -> Result<Value, ServiceError> {
+        match &self.param {
+            Some(param) => Ok(param.clone()),
+            None => {
+                let query = format!("SELECT COALESCE(MAX(id), 0) FROM {}", self.table);
+                db.query_row(&query, [], |row| row.get(0))
+                    .map_err(ServiceError::from)
+            }
+        }
+    }
+}
+
+pub(crate) fn id_value(id: usize) -> Value {
+    Value::Integer(id as i64)
+}
+
+pub(crate) fn day_value(day: NaiveDate) -> Value {
+    Value::Text(format!("{}", day.format("%Y-%m-%d")))
+}
+
+struct Change {
+    label: String,
+    before: Vec<Row>,
+    after: Vec<Row>,
+}
+
+#[derive(Default)]
+pub(crate) struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    fn push(&mut self, change: Change) {
+        self.undo.push(change);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Forgets all changes, used when the database is replaced as a whole and recorded rows no
+    /// longer describe it.
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Runs the change in a transaction and records rows of the scopes so that it can be undone.
+/// Nothing is recorded when the change fails or leaves the rows as they were.
+pub(crate) fn record<T>(
+    db: &Rc<RefCell<Connection>>,
+    history: &Rc<RefCell<History>>,
+    label: &str,
+    scopes: &[Scope],
+    change: impl FnOnce(&Transaction) -> Result<T, ServiceError>,
+) -> Result<T, ServiceError> {
+    let mut db = db.borrow_mut();
+    let tx = db.transaction()?;
+
+    let params = scopes
+        .iter()
+        .map(|s| s.resolve(&tx))
+        .collect::<Result<Vec<Value>, _>>()?;
+    let before = capture(&tx, scopes, &params)?;
+    let result = change(&tx)?;
+    let mut after = capture(&tx, scopes, &params)?;
+
+    // Rows that still exist but no longer match their scope, for example after a recipe lost
+    // one of its ingredients.
+    for row in &before {
+        if !after.iter().any(|r| r.is_same(row)) {
+            if let Some(row) = read_row(&tx, row.table, row.key())? {
+                after.push(row);
+            }
+        }
+    }
+
+    tx.commit()?;
+
+    if before != after {
+        history.borrow_mut().push(Change {
+            label: label.to_string(),
+            before,
+            after,
+        });
+    }
+
+    Ok(result)
+}
+
+fn capture(db: &Connection, scopes: &[Scope], params: &[Value]) -> Result<Vec<Row>, ServiceError> {
+    let mut rows = Vec::new();
+    for (scope, param) in scopes.iter().zip(params) {
+        let query = format!("SELECT * FROM {} WHERE {}", scope.table, scope.filter);
+        rows.extend(select_rows(db, scope.table, &query, param)?);
+    }
+
+    Ok(rows)
+}
+
+fn read_row(
+    db: &Connection,
+    table: &'static str,
+    key: &Value,
+) -> Result<Option<Row>, ServiceError> {
+    let query = format!("SELECT * FROM {table} WHERE {} = ?1", key_column(table));
+
+    Ok(select_rows(db, table, &query, key)?.pop())
+}
+
+fn select_rows(
+    db: &Connection,
+    table: &'static str,
+    query: &str,
+    param: &Value,
+) -> Result<Vec<Row>, ServiceError> {
+    let mut stmt = db.prepare(query)?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    let rows = stmt
+        .query_map(params![param], |row| {
+            let values = (0..columns.len())
+                .map(|i| row.get(i))
+                .collect::<Result<Vec<Value>, _>>()?;
+
+            Ok(Row {
+                table,
+                columns: columns.clone(),
+                values,
+            })
+        })
+        .map_err(ServiceError::from)?
+        .collect::<Result<Vec<Row>, _>>()?;
+
+    Ok(rows)
+}
+
+/// Turns rows currently in the database into target ones. Rows missing from target are deleted
+/// children first, the rest are upserted parents first. Upsert is used instead of REPLACE as
+/// that deletes the old row and with it every row referencing it.
+fn apply(db: &Connection, current: &[Row], target: &[Row]) -> Result<(), ServiceError> {
+    for row in current.iter().rev() {
+        if !target.iter().any(|r| r.is_same(row)) {
+            let query = format!(
+                "DELETE FROM {} WHERE {} = ?1",
+                row.table,
+                key_column(row.table)
+            );
+            db.execute(&query, params![row.key()])
+                .map_err(ServiceError::from)?;
+        }
+    }
+
+    for row in target {
+        let placeholders: Vec<String> = (1..=row.columns.len()).map(|i| format!("?{i}")).collect();
+        let updates: Vec<String> = row
+            .columns
+            .iter()
+            .map(|c| format!("{c}=excluded.{c}"))
+            .collect();
+        let query = format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT({}) DO UPDATE SET {}",
+            row.table,
+            row.columns.join(", "),
+            placeholders.join(", "),
+            key_column(row.table),
+            updates.join(", ")
+        );
+        db.execute(&query, params_from_iter(row.values.iter()))
+            .map_err(ServiceError::from)?;
+    }
+
+    Ok(())
+}
+
+pub struct HistoryService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl HistoryService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        HistoryService { db, history }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.borrow().undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.borrow().redo.is_empty()
+    }
+
+    /// Reverts the last recorded change and returns its label, `None` when there is nothing to
+    /// undo. Failed undo (for example because a restored name is taken now) stays undoable.
+    pub fn undo(&self) -> Result<Option<String>, ServiceError> {
+        let Some(change) = self.history.borrow_mut().undo.pop() else {
+            return Ok(None);
+        };
+
+        match self.restore(&change.after, &change.before) {
+            Ok(()) => {
+                let label = change.label.clone();
+                self.history.borrow_mut().redo.push(change);
+                Ok(Some(label))
+            }
+            Err(err) => {
+                self.history.borrow_mut().undo.push(change);
+                Err(err)
+            }
+        }
+    }
+
+    /// Applies the last undone change again and returns its label, `None` when there is nothing
+    /// to redo.
+    pub fn redo(&self) -> Result<Option<String>, ServiceError> {
+        let Some(change) = self.history.borrow_mut().redo.pop() else {
+            return Ok(None);
+        };
+
+        match self.restore(&change.before, &change.after) {
+            Ok(()) => {
+                let label = change.label.clone();
+                self.history.borrow_mut().undo.push(change);
+                Ok(Some(label))
+            }
+            Err(err) => {
+                self.history.borrow_mut().redo.push(change);
+                Err(err)
+            }
+        }
+    }
+
+    fn restore(&self, current: &[Row], target: &[Row]) -> Result<(), ServiceError> {
+        let mut db = self.db.borrow_mut();
+        let tx = db.transaction()?;
+        apply(&tx, current, target)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+    use chrono::NaiveDate;
+
+    use crate::{
+        test_utils::product, AddMealProduct, AddRecipeIngredient, CreateUpdateProduct,
+        CreateUpdateRecipe, ImportConflict, Services, UpdateRecipeIngredientWeight,
+    };
+
+    #[test]
+    fn undo_and_redo_archiving_of_product() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        services
+            .product
+            .create(CreateUpdateProduct {
+                calories: 30.0,
+                ..product("Jalapeño")
+            })
+            .unwrap();
+        let product = services.product.list().unwrap().remove(0);
+        services
+            .product
+            .set_aliases(product.id, &["chili".to_string()])
+            .unwrap();
+        let meals = services.meal.list_or_create_default(day).unwrap();
+        services
+            .meal
+            .add_product(AddMealProduct {
+                meal_id: meals[0].id,
+                product_id: product.id,
+                weight: 20.0,
+            })
+            .unwrap();
+
+        services.product.delete(product.id).unwrap();
+        assert_eq!(services.meal.day_stats(day).unwrap().calories, 6.0);
+        assert!(services.product.search("chili").unwrap().is_empty());
+
+        assert_eq!(
+            services.history.undo().unwrap().as_deref(),
+            Some("Archive product")
+        );
+        assert_eq!(services.product.search("chili").unwrap()[0].id, product.id);
+
+        services.history.redo().unwrap();
+        assert!(services.product.list().unwrap().is_empty());
+        assert_eq!(services.product.list_archived().unwrap()[0].id, product.id);
+        services.product.restore(product.id).unwrap();
+        assert!(services.product.list_archived().unwrap().is_empty());
+
+        // Undo everything back to the empty database and redo it again.
+        while services.history.undo().unwrap().is_some() {}
+        assert!(services.product.list().unwrap().is_empty());
+        assert!(services.history.can_redo());
+        services.history.redo().unwrap();
+        services.history.redo().unwrap();
+        services.history.redo().unwrap();
+        assert_eq!(services.meal.day_stats(day).unwrap().calories, 6.0);
+    }
+
+    #[test]
+    fn undo_changes_of_meals_and_forget_them_after_import() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let names = |services: &Services| -> Vec<String> {
+            services
+                .meal
+                .list(day)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.name)
+                .collect()
+        };
+
+        services.meal.add(day, "Lunch").unwrap();
+        services.meal.add(day, "Dinner").unwrap();
+        let meals = services.meal.list(day).unwrap();
+        services.meal.rename(meals[1].id, "Supper").unwrap();
+        services.meal.move_up(meals[1].id).unwrap();
+        services.meal.delete(meals[0].id).unwrap();
+        assert_eq!(names(&services), vec!["Supper"]);
+
+        services.history.undo().unwrap();
+        assert_eq!(names(&services), vec!["Supper", "Lunch"]);
+        services.history.undo().unwrap();
+        services.history.undo().unwrap();
+        assert_eq!(names(&services), vec!["Lunch", "Dinner"]);
+
+        let archive = services.archive.export().unwrap();
+        services
+            .archive
+            .import(&archive, ImportConflict::Skip)
+            .unwrap();
+        assert!(!services.history.can_undo());
+        assert!(!services.history.can_redo());
+    }
+
+    #[test]
+    fn undo_ingredient_change_made_after_product_change() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services.product.create(product("Rice")).unwrap();
+        let rice = services.product.list().unwrap().remove(0);
+        services
+            .recipe
+            .create(CreateUpdateRecipe {
+                name: "Risotto".to_string(),
+                weight: 100.0,
+            })
+            .unwrap();
+        let recipe = services.recipe.list().unwrap().remove(0);
+        services
+            .recipe
+            .add_ingredient(AddRecipeIngredient {
+                recipe_id: recipe.id,
+                product_id: rice.id,
+                weight: 100.0,
+            })
+            .unwrap();
+        let ingredient_id = services.recipe.read(recipe.id).unwrap().ingredients[0].id;
+
+        services
+            .product
+            .update(
+                rice.id,
+                CreateUpdateProduct {
+                    calories: 200.0,
+                    ..product("Rice")
+                },
+            )
+            .unwrap();
+        services
+            .recipe
+            .update_ingredient_weight(UpdateRecipeIngredientWeight {
+                recipe_ingredient_id: ingredient_id,
+                weight: 50.0,
+            })
+            .unwrap();
+        assert_eq!(services.recipe.read(recipe.id).unwrap().calories, 100.0);
+
+        // Undoing the product change must not bring back recipe nutrition from before the
+        // ingredient change.
+        services.history.undo().unwrap();
+        let restored = services.recipe.read(recipe.id).unwrap();
+        assert_eq!(restored.ingredients[0].weight, 100.0);
+        assert_eq!(restored.calories, 200.0);
+        services.history.undo().unwrap();
+        assert_eq!(services.recipe.read(recipe.id).unwrap().calories, 100.0);
+        assert_eq!(services.product.read(rice.id).unwrap().calories, 100.0);
+    }
+}
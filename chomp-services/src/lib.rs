@@ -1,51 +1,132 @@
 use std::{cell::RefCell, rc::Rc};
 
+use archive::ArchiveService;
+use backup::BackupService;
+use energy::EnergyService;
+use history::{History, HistoryService};
+use meal_preset::MealPresetService;
+use meal_template::MealTemplateService;
 use meals::MealService;
+use measurement::MeasurementService;
+use nutrient::NutrientService;
 use nutrition_target::NutritionTargetService;
 use product::ProductService;
+use recipe::RecipeService;
+use report::ReportService;
 pub use rusqlite::Connection;
+use settings::SettingsService;
 use weight::WeightService;
 
+mod archive;
+mod backup;
+mod energy;
 mod error;
+mod history;
+mod meal_preset;
+mod meal_template;
 mod meals;
+mod measurement;
+mod nutrient;
 mod nutrition_target;
 mod product;
 mod product_portion;
+mod recipe;
+mod report;
+mod search;
+mod settings;
+#[cfg(test)]
+mod test_utils;
+mod units;
 mod weight;
+mod weight_trend;
 
+pub use archive::{
+    Archive, ArchiveMeal, ArchiveMealPreset, ArchiveMealPresetProduct, ArchiveMealProduct,
+    ArchiveMealProductNutrition, ArchiveMeasurement, ArchiveNutrientAmount, ArchiveProductAlias,
+    ArchiveProductNutrient, ArchiveRecipe, ArchiveRecipeIngredient, ImportConflict, ImportSummary,
+    ARCHIVE_VERSION,
+};
+pub use energy::{EnergyEstimate, ENERGY_WINDOW_DAYS};
 pub use error::ServiceError;
-pub use meals::{AddMealProduct, Meal, MealDayStats, MealProduct, UpdateMealProductWeight};
-pub use nutrition_target::NutritionTarget;
-pub use product::{CreateUpdateProduct, Product};
+pub use meal_preset::{MealPreset, MealPresetProduct};
+pub use meal_template::MealTemplate;
+pub use meals::{
+    AddMealProduct, Meal, MealDayStats, MealProduct, ProductUsage, UpdateMealProductWeight,
+};
+pub use measurement::{CreateMeasurement, CreateMeasurementType, Measurement, MeasurementType};
+pub use nutrient::{CreateNutrient, Nutrient, NutrientAmount, SetProductNutrient};
+pub use nutrition_target::{MealBudget, NutritionTarget, WeekdayTarget};
+pub use product::{is_valid_barcode, CreateUpdateProduct, Product, ProductImporter};
 pub use product_portion::{CreateProductPortion, ProductPortion};
+pub use recipe::{
+    AddRecipeIngredient, CreateUpdateRecipe, Recipe, RecipeIngredient, UpdateRecipeIngredientWeight,
+};
+pub use report::{Adherence, DayTotals, Macros, PeriodSummary, Report, ReportPeriod};
+pub use settings::Settings;
+pub use units::{BodyWeightUnit, EnergyUnit, FoodWeightUnit, Units};
 pub use weight::Weight;
+pub use weight_trend::{WeightAnalysis, WeightTrend};
 
 use crate::product_portion::ProductPortionService;
 
 pub struct Services {
     pub product: ProductService,
     pub product_portion: ProductPortionService,
+    pub nutrient: NutrientService,
+    pub recipe: RecipeService,
     pub weight: WeightService,
+    pub measurement: MeasurementService,
     pub meal: MealService,
+    pub meal_template: MealTemplateService,
+    pub meal_preset: MealPresetService,
     pub nutrition_target: NutritionTargetService,
+    pub report: ReportService,
+    pub energy: EnergyService,
+    pub settings: SettingsService,
+    pub archive: ArchiveService,
+    pub backup: BackupService,
+    pub history: HistoryService,
 }
 
 impl Services {
     pub fn new(db: Connection) -> Self {
         let db_rc = Rc::new(RefCell::new(db));
+        let history = Rc::new(RefCell::new(History::default()));
 
-        let product = ProductService::new(db_rc.clone());
-        let product_portion = ProductPortionService::new(db_rc.clone());
-        let weight = WeightService::new(db_rc.clone());
-        let meal = MealService::new(db_rc.clone());
-        let nutrition_target = NutritionTargetService::new(db_rc.clone());
+        let product = ProductService::new(db_rc.clone(), history.clone());
+        let product_portion = ProductPortionService::new(db_rc.clone(), history.clone());
+        let nutrient = NutrientService::new(db_rc.clone(), history.clone());
+        let recipe = RecipeService::new(db_rc.clone(), history.clone());
+        let weight = WeightService::new(db_rc.clone(), history.clone());
+        let measurement = MeasurementService::new(db_rc.clone(), history.clone());
+        let meal = MealService::new(db_rc.clone(), history.clone());
+        let meal_template = MealTemplateService::new(db_rc.clone(), history.clone());
+        let meal_preset = MealPresetService::new(db_rc.clone(), history.clone());
+        let nutrition_target = NutritionTargetService::new(db_rc.clone(), history.clone());
+        let report = ReportService::new(db_rc.clone());
+        let energy = EnergyService::new(db_rc.clone());
+        let settings = SettingsService::new(db_rc.clone(), history.clone());
+        let archive = ArchiveService::new(db_rc.clone(), history.clone());
+        let backup = BackupService::new(db_rc.clone(), history.clone());
+        let history = HistoryService::new(db_rc.clone(), history);
 
         Services {
             product,
             product_portion,
+            nutrient,
+            recipe,
             weight,
+            measurement,
             meal,
+            meal_template,
+            meal_preset,
             nutrition_target,
+            report,
+            energy,
+            settings,
+            archive,
+            backup,
+            history,
         }
     }
 }
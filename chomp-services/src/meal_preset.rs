@@ -0,0 +1,217 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{
+    history::{id_value, record, History, Scope},
+    ServiceError,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MealPresetProduct {
+    pub product_id: usize,
+    pub name: String,
+    pub weight: f32,
+}
+
+/// Named list of products with weights that can be added to any meal at once.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MealPreset {
+    pub id: usize,
+    pub name: String,
+    pub products: Vec<MealPresetProduct>,
+}
+
+pub struct MealPresetService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl MealPresetService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        MealPresetService { db, history }
+    }
+
+    /// Creates preset with products of the meal and their current weights.
+    pub fn create_from_meal(&self, name: &str, meal_id: usize) -> Result<(), ServiceError> {
+        let preset_query = "
+            INSERT INTO meal_presets (name)
+            VALUES (?1)";
+        let products_query = "
+            INSERT INTO meal_preset_products (preset_id, product_id, weight)
+            SELECT ?1, product_id, weight
+            FROM meal_products
+            WHERE meal_id = ?2";
+        let scopes = [
+            Scope::inserted("meal_presets"),
+            Scope::inserted("meal_preset_products"),
+        ];
+
+        record(&self.db, &self.history, "Add preset", &scopes, |tx| {
+            tx.execute(preset_query, params![name])
+                .map_err(ServiceError::from)?;
+            let preset_id = tx.last_insert_rowid();
+            tx.execute(products_query, params![preset_id, meal_id])
+                .map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn list(&self) -> Result<Vec<MealPreset>, ServiceError> {
+        let query = "
+            SELECT
+                meal_presets.id,
+                meal_presets.name,
+                products.id,
+                products.name,
+                meal_preset_products.weight
+            FROM meal_presets
+            LEFT JOIN meal_preset_products ON meal_presets.id = meal_preset_products.preset_id
+            LEFT JOIN products ON meal_preset_products.product_id = products.id
+            ORDER BY meal_presets.name COLLATE NOCASE ASC, meal_preset_products.id ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let rows: Vec<(usize, String, Option<MealPresetProduct>)> = stmt
+            .query_map([], |row| {
+                let product = match row.get::<_, Option<usize>>(2)? {
+                    Some(product_id) => Some(MealPresetProduct {
+                        product_id,
+                        name: row.get(3)?,
+                        weight: row.get(4)?,
+                    }),
+                    None => None,
+                };
+
+                Ok((row.get(0)?, row.get(1)?, product))
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut presets: Vec<MealPreset> = Vec::new();
+        let mut positions: HashMap<usize, usize> = HashMap::new();
+
+        for (preset_id, preset_name, product) in rows {
+            let position = *positions.entry(preset_id).or_insert_with(|| {
+                presets.push(MealPreset {
+                    id: preset_id,
+                    name: preset_name,
+                    products: Vec::new(),
+                });
+                presets.len() - 1
+            });
+
+            if let Some(product) = product {
+                presets[position].products.push(product);
+            }
+        }
+
+        Ok(presets)
+    }
+
+    /// Adds products of the preset to the meal. Products that are already in the meal are
+    /// left as they are, archived ones are skipped.
+    pub fn apply(&self, preset_id: usize, meal_id: usize) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO meal_products (meal_id, product_id, weight)
+            SELECT ?1, meal_preset_products.product_id, meal_preset_products.weight
+            FROM meal_preset_products
+            INNER JOIN products ON meal_preset_products.product_id = products.id
+            WHERE meal_preset_products.preset_id = ?2 AND products.archived = 0
+            ORDER BY meal_preset_products.id ASC
+            ON CONFLICT (meal_id, product_id) DO NOTHING";
+        let args = params![meal_id, preset_id];
+        let scopes = [Scope::inserted("meal_products")];
+
+        record(
+            &self.db,
+            &self.history,
+            "Add preset to meal",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    pub fn delete(&self, preset_id: usize) -> Result<(), ServiceError> {
+        let scopes = [
+            Scope::new("meal_presets", "id = ?1", id_value(preset_id)),
+            Scope::new(
+                "meal_preset_products",
+                "preset_id = ?1",
+                id_value(preset_id),
+            ),
+        ];
+
+        record(&self.db, &self.history, "Delete preset", &scopes, |tx| {
+            tx.execute(
+                "DELETE FROM meal_preset_products WHERE preset_id = ?1",
+                params![preset_id],
+            )
+            .map_err(ServiceError::from)?;
+            tx.execute("DELETE FROM meal_presets WHERE id = ?1", params![preset_id])
+                .map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+    use chrono::NaiveDate;
+
+    use crate::{test_utils::product, AddMealProduct, Services};
+
+    #[test]
+    fn preset_is_applied_to_other_meal() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        services.product.create(product("Oats")).unwrap();
+        services.product.create(product("Milk")).unwrap();
+        let products = services.product.list().unwrap();
+        let meals = services.meal.list_or_create_default(day).unwrap();
+        for p in products.iter() {
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id: meals[0].id,
+                    product_id: p.id,
+                    weight: 50.0,
+                })
+                .unwrap();
+        }
+
+        services
+            .meal_preset
+            .create_from_meal("Porridge", meals[0].id)
+            .unwrap();
+        let presets = services.meal_preset.list().unwrap();
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].products.len(), 2);
+
+        services
+            .meal
+            .add_product(AddMealProduct {
+                meal_id: meals[1].id,
+                product_id: products[0].id,
+                weight: 20.0,
+            })
+            .unwrap();
+        services
+            .meal_preset
+            .apply(presets[0].id, meals[1].id)
+            .unwrap();
+
+        let meal = services.meal.read(meals[1].id).unwrap();
+        assert_eq!(meal.products.len(), 2);
+        assert_eq!(meal.products[0].weight, 20.0);
+
+        services.meal_preset.delete(presets[0].id).unwrap();
+        assert!(services.meal_preset.list().unwrap().is_empty());
+    }
+}
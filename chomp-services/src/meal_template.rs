@@ -0,0 +1,97 @@
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::Weekday;
+use rusqlite::{params, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    history::{record, History, Scope},
+    ServiceError,
+};
+
+/// Layout every weekday starts with, seeded when the templates table is created.
+pub(crate) const DEFAULT_MEAL_NAMES: [&str; 4] = ["Breakfast", "Snack", "Lunch", "Dinner"];
+
+/// Meal slot created for every new day falling on `weekday`. Weekdays are stored as number of
+/// days from monday.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MealTemplate {
+    pub weekday: u32,
+    pub position: usize,
+    pub name: String,
+}
+
+pub struct MealTemplateService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl MealTemplateService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        MealTemplateService { db, history }
+    }
+
+    pub fn list(&self, weekday: Weekday) -> Result<Vec<MealTemplate>, ServiceError> {
+        let query = "
+            SELECT weekday, position, name
+            FROM meal_templates
+            WHERE weekday = ?1
+            ORDER BY position ASC";
+        let args = params![weekday.num_days_from_monday()];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let templates = stmt
+            .query_map(args, |row| {
+                Ok(MealTemplate {
+                    weekday: row.get(0)?,
+                    position: row.get(1)?,
+                    name: row.get(2)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<MealTemplate>, _>>()?;
+
+        Ok(templates)
+    }
+
+    /// Replaces meal layout of `weekday` with `names` in given order. Days that already have
+    /// meals are not changed.
+    pub fn replace(&self, weekday: Weekday, names: &[String]) -> Result<(), ServiceError> {
+        if names.is_empty() {
+            return Err(ServiceError::Custom(
+                "meal template needs at least one meal".to_string(),
+            ));
+        }
+
+        let delete_query = "
+            DELETE FROM meal_templates
+            WHERE weekday = ?1";
+        let insert_query = "
+            INSERT INTO meal_templates (weekday, position, name)
+            VALUES (?1, ?2, ?3)";
+        let weekday = weekday.num_days_from_monday();
+        let scopes = [Scope::new(
+            "meal_templates",
+            "weekday = ?1",
+            Value::Integer(weekday.into()),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Change meal layout",
+            &scopes,
+            |tx| {
+                tx.execute(delete_query, params![weekday])
+                    .map_err(ServiceError::from)?;
+                for (position, name) in names.iter().enumerate() {
+                    tx.execute(insert_query, params![weekday, position, name])
+                        .map_err(ServiceError::from)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
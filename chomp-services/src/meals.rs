@@ -1,14 +1,16 @@
 use std::{cell::RefCell, cmp::Ordering, collections::HashMap, rc::Rc};
 
-use chrono::NaiveDate;
-use rusqlite::{params, Connection};
+use chrono::{Datelike, Days, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
 
-use super::{Product, ServiceError};
-
-const DEFAULT_MEALS: [&str; 4] = ["Breakfast", "Snack", "Lunch", "Dinner"];
+use super::{
+    history::{id_value, record, History, Scope},
+    NutrientAmount, Product, ServiceError,
+};
 
 #[allow(unused)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct MealProduct {
     pub id: usize,
     pub product_id: usize,
@@ -28,7 +30,7 @@ impl PartialEq<MealProduct> for Product {
 }
 
 #[allow(unused)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Meal {
     pub id: usize,
     pub day: NaiveDate,
@@ -39,7 +41,7 @@ pub struct Meal {
 
 impl PartialOrd for Meal {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.position.cmp(&other.position))
+        Some(self.cmp(other))
     }
 }
 
@@ -77,21 +79,50 @@ pub struct CreateMeal {
     pub name: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct MealDayStats {
     pub calories: f32,
     pub proteins: f32,
     pub fats: f32,
     pub carbohydrates: f32,
+    pub fiber: f32,
+    pub sugar: f32,
+    pub saturated_fats: f32,
+    pub salt: f32,
+    pub nutrients: Vec<NutrientAmount>,
+}
+
+/// How a product was used in meals, see `MealService::product_usage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductUsage {
+    pub product: Product,
+    pub uses: usize,
+    pub last_used: NaiveDate,
+    /// Weight of the last use, preferring uses in the same meal when usage is ranked for a meal.
+    pub last_weight: f32,
+}
+
+/// Uses older by this many days count half as much when ranking product usage.
+const USAGE_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// Uses older than this many days are not considered, they count too little to matter.
+const USAGE_WINDOW_DAYS: u64 = 365;
+
+impl MealDayStats {
+    /// Sodium is not stored separately, it's derived from salt (sodium chloride).
+    pub fn sodium(&self) -> f32 {
+        self.salt / 2.5
+    }
 }
 
 pub struct MealService {
     db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
 }
 
 impl MealService {
-    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
-        MealService { db }
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        MealService { db, history }
     }
 
     pub fn create(&self, meal: CreateMeal) -> Result<(), ServiceError> {
@@ -103,25 +134,43 @@ impl MealService {
             meal.name,
             meal.position,
         ];
+        let scopes = [Scope::inserted("meals")];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        record(&self.db, &self.history, "Add meal", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn delete_product(&self, meal_product_id: usize) -> Result<(), ServiceError> {
+        let nutrients_query = "
+            DELETE FROM meal_product_nutrients
+            WHERE meal_product_id = ?1";
         let query = "
             DELETE FROM meal_products
             WHERE id = ?1";
         let args = params![meal_product_id];
+        let scopes = [
+            Scope::new("meal_products", "id = ?1", id_value(meal_product_id)),
+            Scope::new(
+                "meal_product_nutrients",
+                "meal_product_id = ?1",
+                id_value(meal_product_id),
+            ),
+        ];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        record(
+            &self.db,
+            &self.history,
+            "Remove product from meal",
+            &scopes,
+            |tx| {
+                tx.execute(nutrients_query, args)
+                    .map_err(ServiceError::from)?;
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
     }
 
     pub fn add_product(&self, add_meal_product: AddMealProduct) -> Result<(), ServiceError> {
@@ -133,12 +182,30 @@ impl MealService {
             add_meal_product.product_id,
             add_meal_product.weight
         ];
+        let scopes = [Scope::inserted("meal_products")];
+
+        record(
+            &self.db,
+            &self.history,
+            "Add product to meal",
+            &scopes,
+            |tx| {
+                // Archived products stay in meals they were eaten in, but no new ones.
+                let archived: bool = tx.query_row(
+                    "SELECT archived FROM products WHERE id = ?1",
+                    params![add_meal_product.product_id],
+                    |row| row.get(0),
+                )?;
+                if archived {
+                    return Err(ServiceError::Custom(
+                        "archived product can't be added to a meal, restore it first".to_string(),
+                    ));
+                }
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
     }
 
     pub fn update_product_weight(
@@ -153,12 +220,22 @@ impl MealService {
             update_meal_product_weight.weight,
             update_meal_product_weight.meal_product_id,
         ];
-
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        let scopes = [Scope::new(
+            "meal_products",
+            "id = ?1",
+            id_value(update_meal_product_weight.meal_product_id),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Change weight of meal product",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
     }
 
     pub fn read(&self, id: usize) -> Result<Meal, ServiceError> {
@@ -173,13 +250,14 @@ impl MealService {
                 products.id,
     			products.name,
     			products.company,
-    			products.calories * meal_products.weight / 100,
-    			products.fats * meal_products.weight / 100,
-    			products.proteins * meal_products.weight / 100,
-    			products.carbohydrates * meal_products.weight / 100
+    			meal_product_nutrition.calories * meal_products.weight / 100,
+    			meal_product_nutrition.fats * meal_products.weight / 100,
+    			meal_product_nutrition.proteins * meal_products.weight / 100,
+    			meal_product_nutrition.carbohydrates * meal_products.weight / 100
     		FROM meals
     		LEFT JOIN meal_products ON meals.id = meal_products.meal_id
     		LEFT JOIN products ON meal_products.product_id = products.id
+    		LEFT JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
     		WHERE meals.id = ?1";
         let args = params![id];
 
@@ -234,6 +312,103 @@ impl MealService {
         Ok(meal)
     }
 
+    /// Products used in meals ranked by how often and how recently they were used, relative to
+    /// `day`. When `meal` is given, uses in meals with that name count twice, so that oatmeal
+    /// ranks first for breakfast and chicken for dinner. Only uses from the last
+    /// `USAGE_WINDOW_DAYS` are considered, at most `limit` products are returned.
+    pub fn product_usage(
+        &self,
+        day: NaiveDate,
+        meal: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ProductUsage>, ServiceError> {
+        // SQLite has no power function, decay of every possible age is generated instead.
+        let query = "
+            WITH RECURSIVE decay(age, factor) AS (
+                SELECT 0, 1.0
+                UNION ALL
+                SELECT age + 1, factor * ?3
+                FROM decay
+                WHERE age < ?4
+            ),
+            uses AS (
+                SELECT
+                    meal_products.id, meal_products.product_id, meal_products.weight, meals.day,
+                    COALESCE(meals.name = ?2 COLLATE NOCASE, 0) AS same_meal,
+                    MAX(CAST(julianday(?1) - julianday(meals.day) AS INTEGER), 0) AS age
+                FROM meal_products
+                INNER JOIN meals ON meal_products.meal_id = meals.id
+                INNER JOIN products ON meal_products.product_id = products.id
+                WHERE products.archived = 0 AND meals.day >= ?5
+            )
+            SELECT
+                products.id, products.name, products.company, products.calories, products.fats,
+                products.proteins, products.carbohydrates, products.fiber, products.sugar,
+                products.saturated_fats, products.salt, products.barcode, products.archived,
+                usage.uses, usage.last_used,
+                (
+                    SELECT last.weight
+                    FROM uses AS last
+                    WHERE last.product_id = usage.product_id
+                    ORDER BY last.same_meal DESC, last.day DESC, last.id DESC
+                    LIMIT 1
+                )
+            FROM (
+                SELECT
+                    uses.product_id,
+                    COUNT(*) AS uses,
+                    MAX(uses.day) AS last_used,
+                    SUM(decay.factor * (1 + uses.same_meal)) AS score
+                FROM uses
+                INNER JOIN decay ON decay.age = uses.age
+                GROUP BY uses.product_id
+            ) AS usage
+            INNER JOIN products ON products.id = usage.product_id
+            ORDER BY usage.score DESC, usage.last_used DESC, products.name ASC
+            LIMIT ?6";
+        let window_start = day - Days::new(USAGE_WINDOW_DAYS);
+        let args = params![
+            format!("{}", day.format("%Y-%m-%d")),
+            meal,
+            0.5f64.powf(1.0 / USAGE_HALF_LIFE_DAYS),
+            USAGE_WINDOW_DAYS,
+            format!("{}", window_start.format("%Y-%m-%d")),
+            // Negative limit means no limit in SQLite.
+            limit.map_or(-1, |l| l as i64),
+        ];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let usage = stmt
+            .query_map(args, |row| {
+                Ok(ProductUsage {
+                    product: Product {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        company: row.get(2)?,
+                        calories: row.get(3)?,
+                        fats: row.get(4)?,
+                        proteins: row.get(5)?,
+                        carbohydrates: row.get(6)?,
+                        fiber: row.get(7)?,
+                        sugar: row.get(8)?,
+                        saturated_fats: row.get(9)?,
+                        salt: row.get(10)?,
+                        barcode: row.get(11)?,
+                        archived: row.get(12)?,
+                    },
+                    uses: row.get(13)?,
+                    last_used: row.get(14)?,
+                    last_weight: row.get(15)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(usage)
+    }
+
     pub fn read_by_day_and_name(&self, day: NaiveDate, name: &str) -> Result<Meal, ServiceError> {
         let meal_id = self.read_meal_id(day, name)?;
         self.read(meal_id)
@@ -261,12 +436,13 @@ impl MealService {
                 products.id,
     			products.name,
     			products.company,
-    			products.calories * meal_products.weight / 100,
-    			products.fats * meal_products.weight / 100,
-    			products.proteins * meal_products.weight / 100,
-    			products.carbohydrates * meal_products.weight / 100
+    			meal_product_nutrition.calories * meal_products.weight / 100,
+    			meal_product_nutrition.fats * meal_products.weight / 100,
+    			meal_product_nutrition.proteins * meal_products.weight / 100,
+    			meal_product_nutrition.carbohydrates * meal_products.weight / 100
     		FROM meal_products
     		LEFT JOIN products ON meal_products.product_id = products.id
+    		LEFT JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
     		WHERE meal_products.id = ?1";
         let args = params![meal_product_id];
 
@@ -301,13 +477,14 @@ impl MealService {
                 products.id,
     			products.name,
     			products.company,
-    			products.calories * meal_products.weight / 100,
-    			products.fats * meal_products.weight / 100,
-    			products.proteins * meal_products.weight / 100,
-    			products.carbohydrates * meal_products.weight / 100
+    			meal_product_nutrition.calories * meal_products.weight / 100,
+    			meal_product_nutrition.fats * meal_products.weight / 100,
+    			meal_product_nutrition.proteins * meal_products.weight / 100,
+    			meal_product_nutrition.carbohydrates * meal_products.weight / 100
     		FROM meals
     		LEFT JOIN meal_products ON meals.id = meal_products.meal_id
     		LEFT JOIN products ON meal_products.product_id = products.id
+    		LEFT JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
     		WHERE meals.day = ?1";
         let args = params![format!("{}", day.format("%Y-%m-%d"))];
 
@@ -369,29 +546,68 @@ impl MealService {
     pub fn day_stats(&self, day: NaiveDate) -> Result<MealDayStats, ServiceError> {
         let query = "
             SELECT
-                COALESCE(SUM(products.calories * meal_products.weight / 100), 0) AS total_calories,
-                COALESCE(SUM(products.fats * meal_products.weight / 100), 0) AS total_fats,
-                COALESCE(SUM(products.proteins * meal_products.weight / 100), 0) AS total_proteins,
-                COALESCE(SUM(products.carbohydrates * meal_products.weight / 100), 0) AS total_carbohydrates
+                COALESCE(SUM(meal_product_nutrition.calories * meal_products.weight / 100), 0) AS total_calories,
+                COALESCE(SUM(meal_product_nutrition.fats * meal_products.weight / 100), 0) AS total_fats,
+                COALESCE(SUM(meal_product_nutrition.proteins * meal_products.weight / 100), 0) AS total_proteins,
+                COALESCE(SUM(meal_product_nutrition.carbohydrates * meal_products.weight / 100), 0) AS total_carbohydrates,
+                COALESCE(SUM(meal_product_nutrition.fiber * meal_products.weight / 100), 0) AS total_fiber,
+                COALESCE(SUM(meal_product_nutrition.sugar * meal_products.weight / 100), 0) AS total_sugar,
+                COALESCE(SUM(meal_product_nutrition.saturated_fats * meal_products.weight / 100), 0) AS total_saturated_fats,
+                COALESCE(SUM(meal_product_nutrition.salt * meal_products.weight / 100), 0) AS total_salt
             FROM meals
             LEFT JOIN meal_products ON meals.id = meal_products.meal_id
-            LEFT JOIN products ON meal_products.product_id = products.id
+            LEFT JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
             WHERE meals.day = ?1
             GROUP BY meals.day";
-        let args = params![format!("{}", day.format("%Y-%m-%d"))];
+        let nutrients_query = "
+            SELECT
+                nutrients.id,
+                nutrients.name,
+                nutrients.unit,
+                SUM(meal_product_nutrient_amounts.amount * meal_products.weight / 100)
+            FROM meals
+            INNER JOIN meal_products ON meals.id = meal_products.meal_id
+            INNER JOIN meal_product_nutrient_amounts
+                ON meal_products.id = meal_product_nutrient_amounts.meal_product_id
+            INNER JOIN nutrients ON meal_product_nutrient_amounts.nutrient_id = nutrients.id
+            WHERE meals.day = ?1
+            GROUP BY nutrients.id
+            ORDER BY nutrients.id ASC";
+        let day = format!("{}", day.format("%Y-%m-%d"));
 
         let db = self.db.borrow();
         let mut stmt = db.prepare(query)?;
 
-        stmt.query_row(args, |row| {
-            Ok(MealDayStats {
-                calories: row.get(0)?,
-                fats: row.get(1)?,
-                proteins: row.get(2)?,
-                carbohydrates: row.get(3)?,
+        let mut stats = stmt
+            .query_row(params![day], |row| {
+                Ok(MealDayStats {
+                    calories: row.get(0)?,
+                    fats: row.get(1)?,
+                    proteins: row.get(2)?,
+                    carbohydrates: row.get(3)?,
+                    fiber: row.get(4)?,
+                    sugar: row.get(5)?,
+                    saturated_fats: row.get(6)?,
+                    salt: row.get(7)?,
+                    nutrients: Vec::new(),
+                })
             })
-        })
-        .map_err(ServiceError::from)
+            .map_err(ServiceError::from)?;
+
+        let mut stmt = db.prepare(nutrients_query)?;
+        stats.nutrients = stmt
+            .query_map(params![day], |row| {
+                Ok(NutrientAmount {
+                    nutrient_id: row.get(0)?,
+                    name: row.get(1)?,
+                    unit: row.get(2)?,
+                    amount: row.get(3)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<NutrientAmount>, _>>()?;
+
+        Ok(stats)
     }
 
     pub fn list_or_create_default(&self, day: NaiveDate) -> Result<Vec<Meal>, ServiceError> {
@@ -408,18 +624,396 @@ impl MealService {
         }
     }
 
+    /// Creates meals for the day from meal template of its weekday.
     pub fn create_default(&self, day: NaiveDate) -> Result<(), ServiceError> {
-        DEFAULT_MEALS
-            .into_iter()
-            .enumerate()
-            .try_for_each(|(i, name)| {
-                let meal = CreateMeal {
-                    day,
-                    position: i,
-                    name: name.to_owned(),
-                };
+        let query = "
+            INSERT INTO meals (day, name, position)
+            SELECT ?1, name, position
+            FROM meal_templates
+            WHERE weekday = ?2
+            ORDER BY position ASC";
+        let args = params![
+            format!("{}", day.format("%Y-%m-%d")),
+            day.weekday().num_days_from_monday(),
+        ];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+        stmt.execute(args).map_err(ServiceError::from)?;
+
+        Ok(())
+    }
+
+    /// Adds meal after the last meal of the day.
+    pub fn add(&self, day: NaiveDate, name: &str) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO meals (day, name, position)
+            SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0)
+            FROM meals
+            WHERE day = ?1";
+        let args = params![format!("{}", day.format("%Y-%m-%d")), name];
+        let scopes = [Scope::inserted("meals")];
+
+        record(&self.db, &self.history, "Add meal", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn rename(&self, meal_id: usize, name: &str) -> Result<(), ServiceError> {
+        let query = "
+            UPDATE meals
+            SET name = ?1
+            WHERE id = ?2";
+        let args = params![name, meal_id];
+        let scopes = [Scope::new("meals", "id = ?1", id_value(meal_id))];
+
+        record(&self.db, &self.history, "Rename meal", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    /// Deletes meal together with its products and their frozen nutrients.
+    pub fn delete(&self, meal_id: usize) -> Result<(), ServiceError> {
+        let scopes = [
+            Scope::new("meals", "id = ?1", id_value(meal_id)),
+            Scope::new("meal_products", "meal_id = ?1", id_value(meal_id)),
+            Scope::new(
+                "meal_product_nutrients",
+                "meal_product_id IN (SELECT id FROM meal_products WHERE meal_id = ?1)",
+                id_value(meal_id),
+            ),
+        ];
+
+        record(&self.db, &self.history, "Delete meal", &scopes, |tx| {
+            tx.execute(
+                "DELETE FROM meal_product_nutrients
+                WHERE meal_product_id IN (SELECT id FROM meal_products WHERE meal_id = ?1)",
+                params![meal_id],
+            )
+            .map_err(ServiceError::from)?;
+            tx.execute(
+                "DELETE FROM meal_products WHERE meal_id = ?1",
+                params![meal_id],
+            )
+            .map_err(ServiceError::from)?;
+            tx.execute("DELETE FROM meals WHERE id = ?1", params![meal_id])
+                .map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    /// Swaps position of the meal with previous meal of the same day, does nothing for the
+    /// first meal.
+    pub fn move_up(&self, meal_id: usize) -> Result<(), ServiceError> {
+        let neighbour_query = "
+            SELECT other.id
+            FROM meals
+            INNER JOIN meals AS other ON other.day = meals.day AND other.position < meals.position
+            WHERE meals.id = ?1
+            ORDER BY other.position DESC
+            LIMIT 1";
+        self.swap_with_neighbour(meal_id, neighbour_query)
+    }
+
+    /// Swaps position of the meal with next meal of the same day, does nothing for the last
+    /// meal.
+    pub fn move_down(&self, meal_id: usize) -> Result<(), ServiceError> {
+        let neighbour_query = "
+            SELECT other.id
+            FROM meals
+            INNER JOIN meals AS other ON other.day = meals.day AND other.position > meals.position
+            WHERE meals.id = ?1
+            ORDER BY other.position ASC
+            LIMIT 1";
+        self.swap_with_neighbour(meal_id, neighbour_query)
+    }
 
-                self.create(meal)
+    fn swap_with_neighbour(
+        &self,
+        meal_id: usize,
+        neighbour_query: &str,
+    ) -> Result<(), ServiceError> {
+        let position_query = "SELECT position FROM meals WHERE id = ?1";
+        let update_query = "UPDATE meals SET position = ?1 WHERE id = ?2";
+        let scopes = [Scope::new(
+            "meals",
+            "day = (SELECT day FROM meals WHERE id = ?1)",
+            id_value(meal_id),
+        )];
+
+        record(&self.db, &self.history, "Move meal", &scopes, |tx| {
+            let neighbour_id: Option<usize> = tx
+                .query_row(neighbour_query, params![meal_id], |row| row.get(0))
+                .optional()
+                .map_err(ServiceError::from)?;
+            if let Some(neighbour_id) = neighbour_id {
+                let position: usize =
+                    tx.query_row(position_query, params![meal_id], |row| row.get(0))?;
+                let neighbour_position: usize =
+                    tx.query_row(position_query, params![neighbour_id], |row| row.get(0))?;
+                tx.execute(update_query, params![neighbour_position, meal_id])
+                    .map_err(ServiceError::from)?;
+                tx.execute(update_query, params![position, neighbour_id])
+                    .map_err(ServiceError::from)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Copies current nutrition of the product into its meal products from days before today, so
+/// that changing the product afterwards does not rewrite what was eaten in the past. Meals of
+/// today and later keep following the product. Has to be called before the product changes.
+pub(crate) fn freeze_past_meal_products(
+    db: &Connection,
+    product_id: usize,
+) -> Result<(), ServiceError> {
+    let nutrients_query = "
+        INSERT INTO meal_product_nutrients (meal_product_id, nutrient_id, amount)
+        SELECT meal_products.id, product_nutrients.nutrient_id, product_nutrients.amount
+        FROM meal_products
+        INNER JOIN meals ON meal_products.meal_id = meals.id
+        INNER JOIN product_nutrients ON meal_products.product_id = product_nutrients.product_id
+        WHERE meal_products.product_id = ?1 AND NOT meal_products.frozen AND meals.day < ?2";
+    let query = "
+        UPDATE meal_products
+        SET frozen = 1, calories = products.calories, fats = products.fats,
+            proteins = products.proteins, carbohydrates = products.carbohydrates,
+            fiber = products.fiber, sugar = products.sugar,
+            saturated_fats = products.saturated_fats, salt = products.salt
+        FROM products
+        WHERE products.id = meal_products.product_id
+            AND meal_products.product_id = ?1
+            AND NOT meal_products.frozen
+            AND meal_products.meal_id IN (SELECT id FROM meals WHERE day < ?2)";
+    let today = format!("{}", Local::now().date_naive().format("%Y-%m-%d"));
+    let args = params![product_id, today];
+
+    db.execute(nutrients_query, args)
+        .map_err(ServiceError::from)?;
+    db.execute(query, args).map_err(ServiceError::from)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, CreateUpdateProduct, Services, SetProductNutrient};
+
+    use super::*;
+
+    #[test]
+    fn day_stats_sums_extended_nutrients() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let vitamin_c = services
+            .nutrient
+            .list()
+            .unwrap()
+            .into_iter()
+            .find(|n| n.name == "Vitamin C")
+            .unwrap();
+
+        services
+            .product
+            .create(CreateUpdateProduct {
+                calories: 50.0,
+                fiber: Some(2.0),
+                salt: Some(0.5),
+                nutrients: vec![SetProductNutrient {
+                    nutrient_id: vitamin_c.id,
+                    amount: 50.0,
+                }],
+                ..product("Orange")
+            })
+            .unwrap();
+        let product = services.product.list().unwrap().remove(0);
+        let meals = services.meal.list_or_create_default(day).unwrap();
+
+        services
+            .meal
+            .add_product(AddMealProduct {
+                meal_id: meals[0].id,
+                product_id: product.id,
+                weight: 200.0,
             })
+            .unwrap();
+
+        let stats = services.meal.day_stats(day).unwrap();
+        assert_eq!(stats.calories, 100.0);
+        assert_eq!(stats.fiber, 4.0);
+        assert_eq!(stats.saturated_fats, 0.0);
+        assert_eq!(stats.sodium(), 0.4);
+        assert_eq!(stats.nutrients.len(), 1);
+        assert_eq!(stats.nutrients[0].amount, 100.0);
+    }
+
+    #[test]
+    fn editing_product_keeps_nutrition_of_past_meals() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let vitamin_c = services.nutrient.list().unwrap().remove(0);
+        let orange = |calories: f32, vitamin: f32| CreateUpdateProduct {
+            calories,
+            nutrients: vec![SetProductNutrient {
+                nutrient_id: vitamin_c.id,
+                amount: vitamin,
+            }],
+            ..product("Orange")
+        };
+
+        services.product.create(orange(50.0, 50.0)).unwrap();
+        let product = services.product.list().unwrap().remove(0);
+        for day in [yesterday, today] {
+            let meals = services.meal.list_or_create_default(day).unwrap();
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id: meals[0].id,
+                    product_id: product.id,
+                    weight: 200.0,
+                })
+                .unwrap();
+        }
+
+        services
+            .product
+            .update(product.id, orange(45.0, 60.0))
+            .unwrap();
+        services
+            .product
+            .update(product.id, orange(40.0, 70.0))
+            .unwrap();
+
+        let past = services.meal.day_stats(yesterday).unwrap();
+        assert_eq!(past.calories, 100.0);
+        assert_eq!(past.nutrients[0].amount, 100.0);
+        let meal = &services.meal.list(yesterday).unwrap()[0];
+        assert_eq!(meal.products[0].calories, 100.0);
+
+        let current = services.meal.day_stats(today).unwrap();
+        assert_eq!(current.calories, 80.0);
+        assert_eq!(current.nutrients[0].amount, 140.0);
+
+        // Frozen nutrients are removed with their meal product without relying on cascades.
+        let frozen_nutrients = || -> usize {
+            services
+                .meal
+                .db
+                .borrow()
+                .query_row("SELECT COUNT(*) FROM meal_product_nutrients", [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        services
+            .meal
+            .db
+            .borrow()
+            .pragma_update(None, "foreign_keys", false)
+            .unwrap();
+        assert_eq!(frozen_nutrients(), 1);
+        services.meal.delete_product(meal.products[0].id).unwrap();
+        assert_eq!(frozen_nutrients(), 0);
+        services.history.undo().unwrap();
+        assert_eq!(frozen_nutrients(), 1);
+        services.meal.delete(meal.id).unwrap();
+        assert_eq!(frozen_nutrients(), 0);
+    }
+
+    #[test]
+    fn default_meals_follow_weekday_template() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2025, 1, 7).unwrap();
+        let names = ["Breakfast", "Pre-workout", "Dinner"].map(String::from);
+        services
+            .meal_template
+            .replace(monday.weekday(), &names)
+            .unwrap();
+
+        let meals = services.meal.list_or_create_default(monday).unwrap();
+        let meal_names: Vec<&str> = meals.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(meal_names, ["Breakfast", "Pre-workout", "Dinner"]);
+        assert_eq!(
+            services.meal.list_or_create_default(tuesday).unwrap().len(),
+            4
+        );
+
+        services.meal.add(monday, "Supper").unwrap();
+        services.meal.move_up(meals[2].id).unwrap();
+        services.meal.rename(meals[1].id, "Workout").unwrap();
+        services.meal.delete(meals[0].id).unwrap();
+
+        let meals = services.meal.list(monday).unwrap();
+        let meal_names: Vec<&str> = meals.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(meal_names, ["Dinner", "Workout", "Supper"]);
+    }
+
+    #[test]
+    fn product_usage_prefers_frequent_recent_and_same_meal() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        for name in ["Oatmeal", "Chicken", "Rice"] {
+            services.product.create(product(name)).unwrap();
+        }
+        let ids: HashMap<String, usize> = services
+            .product
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.name, p.id))
+            .collect();
+        let add = |day: NaiveDate, meal: &str, product: &str, weight: f32| {
+            let meal_id = services.meal.read_meal_id(day, meal).unwrap();
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id,
+                    product_id: ids[product],
+                    weight,
+                })
+                .unwrap();
+        };
+
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        for days_ago in 1..=3 {
+            let day = today - chrono::Days::new(days_ago);
+            services.meal.list_or_create_default(day).unwrap();
+            add(day, "Breakfast", "Oatmeal", 60.0 + days_ago as f32);
+            add(day, "Dinner", "Chicken", 150.0);
+            add(day, "Breakfast", "Chicken", 50.0);
+        }
+        let long_ago = today - chrono::Days::new(300);
+        services.meal.list_or_create_default(long_ago).unwrap();
+        add(long_ago, "Dinner", "Rice", 100.0);
+
+        let names = |meal| -> Vec<String> {
+            services
+                .meal
+                .product_usage(today, meal, None)
+                .unwrap()
+                .into_iter()
+                .map(|u| u.product.name)
+                .collect()
+        };
+        assert_eq!(names(None), ["Chicken", "Oatmeal", "Rice"]);
+        let recent = services.meal.product_usage(today, None, Some(1)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(names(Some("breakfast"))[0], "Chicken");
+
+        let usage = services
+            .meal
+            .product_usage(today, Some("Dinner"), None)
+            .unwrap();
+        assert_eq!(usage[0].product.name, "Chicken");
+        assert_eq!(usage[0].uses, 6);
+        assert_eq!(usage[0].last_weight, 150.0);
+        let oatmeal = usage.iter().find(|u| u.product.name == "Oatmeal").unwrap();
+        assert_eq!(oatmeal.last_weight, 61.0);
     }
 }
@@ -0,0 +1,322 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    history::{id_value, record, History, Scope},
+    ServiceError,
+};
+
+/// Kind of body measurement (waist, body fat...) with unit its values are in. Names are
+/// unique regardless of case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeasurementType {
+    pub id: usize,
+    pub name: String,
+    pub unit: String,
+}
+
+impl fmt::Display for MeasurementType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.unit)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateMeasurementType {
+    pub name: String,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Measurement {
+    pub id: usize,
+    pub type_id: usize,
+    pub name: String,
+    pub unit: String,
+    pub day: NaiveDate,
+    pub value: f32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateMeasurement {
+    pub type_id: usize,
+    pub day: NaiveDate,
+    pub value: f32,
+}
+
+pub struct MeasurementService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl MeasurementService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        MeasurementService { db, history }
+    }
+
+    pub fn create_type(&self, measurement_type: CreateMeasurementType) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO measurement_types (name, unit)
+            VALUES (?1, ?2)";
+        let args = params![measurement_type.name, measurement_type.unit];
+        let scopes = [Scope::inserted("measurement_types")];
+
+        record(
+            &self.db,
+            &self.history,
+            "Add measurement type",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns id of type with given name, creating it with `unit` if it doesn't exist yet.
+    pub fn find_or_create_type(&self, name: &str, unit: &str) -> Result<usize, ServiceError> {
+        let insert_query = "
+            INSERT INTO measurement_types (name, unit)
+            VALUES (?1, ?2)
+            ON CONFLICT (name) DO NOTHING";
+        let select_query = "
+            SELECT id
+            FROM measurement_types
+            WHERE name = ?1";
+
+        let scopes = [Scope::inserted("measurement_types")];
+
+        record(
+            &self.db,
+            &self.history,
+            "Add measurement type",
+            &scopes,
+            |tx| {
+                tx.execute(insert_query, params![name, unit])
+                    .map_err(ServiceError::from)?;
+                tx.query_row(select_query, params![name], |row| row.get(0))
+                    .map_err(ServiceError::from)
+            },
+        )
+    }
+
+    /// Deletes type together with all its measurements.
+    pub fn delete_type(&self, type_id: usize) -> Result<(), ServiceError> {
+        let scopes = [
+            Scope::new("measurement_types", "id = ?1", id_value(type_id)),
+            Scope::new("measurements", "type_id = ?1", id_value(type_id)),
+        ];
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete measurement type",
+            &scopes,
+            |tx| {
+                tx.execute(
+                    "DELETE FROM measurements WHERE type_id = ?1",
+                    params![type_id],
+                )
+                .map_err(ServiceError::from)?;
+                tx.execute(
+                    "DELETE FROM measurement_types WHERE id = ?1",
+                    params![type_id],
+                )
+                .map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    pub fn list_types(&self) -> Result<Vec<MeasurementType>, ServiceError> {
+        let query = "
+            SELECT id, name, unit
+            FROM measurement_types
+            ORDER BY id ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let types = stmt
+            .query_map([], |row| {
+                Ok(MeasurementType {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    unit: row.get(2)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<MeasurementType>, _>>()?;
+
+        Ok(types)
+    }
+
+    pub fn create(&self, measurement: CreateMeasurement) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO measurements (type_id, day, value)
+            VALUES (?1, ?2, ?3)";
+        let args = params![
+            measurement.type_id,
+            format!("{}", measurement.day.format("%Y-%m-%d")),
+            measurement.value
+        ];
+        let scopes = [Scope::inserted("measurements")];
+
+        record(&self.db, &self.history, "Add measurement", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn update(&self, id: usize, value: f32) -> Result<(), ServiceError> {
+        let query = "
+            UPDATE measurements
+            SET value=?1
+            WHERE id = ?2";
+        let args = params![value, id];
+        let scopes = [Scope::new("measurements", "id = ?1", id_value(id))];
+
+        record(
+            &self.db,
+            &self.history,
+            "Change measurement",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    pub fn read(&self, id: usize) -> Result<Measurement, ServiceError> {
+        let query = "
+            SELECT
+                measurements.id,
+                measurement_types.id,
+                measurement_types.name,
+                measurement_types.unit,
+                measurements.day,
+                measurements.value
+            FROM measurements
+            INNER JOIN measurement_types ON measurements.type_id = measurement_types.id
+            WHERE measurements.id = ?1";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        stmt.query_row(params![id], measurement_from_row)
+            .map_err(ServiceError::from)
+    }
+
+    pub fn delete(&self, id: usize) -> Result<(), ServiceError> {
+        let query = "
+            DELETE FROM measurements
+            WHERE id = ?1";
+        let scopes = [Scope::new("measurements", "id = ?1", id_value(id))];
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete measurement",
+            &scopes,
+            |tx| {
+                tx.execute(query, params![id]).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Lists measurements of all types, newest first.
+    pub fn list(&self) -> Result<Vec<Measurement>, ServiceError> {
+        let query = "
+            SELECT
+                measurements.id,
+                measurement_types.id,
+                measurement_types.name,
+                measurement_types.unit,
+                measurements.day,
+                measurements.value
+            FROM measurements
+            INNER JOIN measurement_types ON measurements.type_id = measurement_types.id
+            ORDER BY measurements.day DESC, measurement_types.id ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let measurements = stmt
+            .query_map([], measurement_from_row)
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<Measurement>, _>>()?;
+
+        Ok(measurements)
+    }
+}
+
+fn measurement_from_row(row: &rusqlite::Row) -> rusqlite::Result<Measurement> {
+    Ok(Measurement {
+        id: row.get(0)?,
+        type_id: row.get(1)?,
+        name: row.get(2)?,
+        unit: row.get(3)?,
+        day: row.get(4)?,
+        value: row.get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::Services;
+
+    use super::*;
+
+    #[test]
+    fn types_are_matched_regardless_of_case() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let types = services.measurement.list_types().unwrap();
+        let waist = types.iter().find(|t| t.name == "Waist").unwrap();
+
+        let type_id = services
+            .measurement
+            .find_or_create_type("waist", "in")
+            .unwrap();
+        assert_eq!(type_id, waist.id);
+        let biceps_id = services
+            .measurement
+            .find_or_create_type("Biceps", "cm")
+            .unwrap();
+        assert_eq!(
+            services.measurement.list_types().unwrap().len(),
+            types.len() + 1
+        );
+
+        let day = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        for (type_id, value) in [(type_id, 80.0), (biceps_id, 35.0)] {
+            services
+                .measurement
+                .create(CreateMeasurement {
+                    type_id,
+                    day,
+                    value,
+                })
+                .unwrap();
+        }
+        assert!(matches!(
+            services.measurement.create(CreateMeasurement {
+                type_id,
+                day,
+                value: 81.0,
+            }),
+            Err(ServiceError::UniqueConstraintViolation(_))
+        ));
+
+        services.measurement.delete_type(biceps_id).unwrap();
+        let measurements = services.measurement.list().unwrap();
+        assert_eq!(measurements.len(), 1);
+        assert_eq!(measurements[0].unit, "cm");
+    }
+}
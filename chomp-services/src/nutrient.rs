@@ -0,0 +1,202 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    history::{id_value, record, History, Scope},
+    ServiceError,
+};
+
+/// Definition of additional nutrient (vitamin, mineral...) that can be tracked for products
+/// on top of the fixed set of columns in `products`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nutrient {
+    pub id: usize,
+    pub name: String,
+    pub unit: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateNutrient {
+    pub name: String,
+    pub unit: String,
+}
+
+/// Amount of nutrient, per 100g when it belongs to a product or summed when it's part of
+/// statistics.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NutrientAmount {
+    pub nutrient_id: usize,
+    pub name: String,
+    pub unit: String,
+    pub amount: f32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SetProductNutrient {
+    pub nutrient_id: usize,
+    pub amount: f32,
+}
+
+pub struct NutrientService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl NutrientService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        NutrientService { db, history }
+    }
+
+    pub fn create(&self, nutrient: CreateNutrient) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO nutrients (name, unit)
+    	    VALUES (?1, ?2)";
+        let args = params![nutrient.name, nutrient.unit];
+        let scopes = [Scope::inserted("nutrients")];
+
+        record(&self.db, &self.history, "Add nutrient", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    /// Deletes nutrient together with its amounts in products and meals.
+    pub fn delete(&self, id: usize) -> Result<(), ServiceError> {
+        let query = "
+            DELETE FROM nutrients
+    	    WHERE id = ?1";
+        let args = params![id];
+        let scopes = [
+            Scope::new("nutrients", "id = ?1", id_value(id)),
+            Scope::new("product_nutrients", "nutrient_id = ?1", id_value(id)),
+            Scope::new("meal_product_nutrients", "nutrient_id = ?1", id_value(id)),
+        ];
+
+        record(&self.db, &self.history, "Delete nutrient", &scopes, |tx| {
+            tx.execute("DELETE FROM product_nutrients WHERE nutrient_id = ?1", args)
+                .map_err(ServiceError::from)?;
+            tx.execute(
+                "DELETE FROM meal_product_nutrients WHERE nutrient_id = ?1",
+                args,
+            )
+            .map_err(ServiceError::from)?;
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn list(&self) -> Result<Vec<Nutrient>, ServiceError> {
+        let query = "
+            SELECT id, name, unit
+            FROM nutrients
+            ORDER BY id ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let nutrients = stmt
+            .query_map([], |row| {
+                Ok(Nutrient {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    unit: row.get(2)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<Nutrient>, _>>()?;
+
+        Ok(nutrients)
+    }
+
+    pub fn list_for_product(&self, product_id: usize) -> Result<Vec<NutrientAmount>, ServiceError> {
+        let query = "
+            SELECT nutrients.id, nutrients.name, nutrients.unit, product_nutrients.amount
+            FROM product_nutrients
+            INNER JOIN nutrients ON product_nutrients.nutrient_id = nutrients.id
+            WHERE product_nutrients.product_id = ?1
+            ORDER BY nutrients.id ASC";
+        let args = params![product_id];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let nutrients = stmt
+            .query_map(args, |row| {
+                Ok(NutrientAmount {
+                    nutrient_id: row.get(0)?,
+                    name: row.get(1)?,
+                    unit: row.get(2)?,
+                    amount: row.get(3)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<NutrientAmount>, _>>()?;
+
+        Ok(nutrients)
+    }
+}
+
+/// Replaces all nutrient amounts of given product.
+pub(crate) fn set_product_nutrients(
+    db: &Connection,
+    product_id: usize,
+    nutrients: &[SetProductNutrient],
+) -> Result<(), ServiceError> {
+    let delete_query = "
+        DELETE FROM product_nutrients
+        WHERE product_id = ?1";
+    let insert_query = "
+        INSERT INTO product_nutrients (product_id, nutrient_id, amount)
+        VALUES (?1, ?2, ?3)";
+
+    db.execute(delete_query, params![product_id])
+        .map_err(ServiceError::from)?;
+
+    let mut stmt = db.prepare(insert_query)?;
+    for nutrient in nutrients {
+        stmt.execute(params![product_id, nutrient.nutrient_id, nutrient.amount])
+            .map_err(ServiceError::from)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, CreateUpdateProduct, Services};
+
+    use super::*;
+
+    #[test]
+    fn deleting_nutrient_removes_its_amounts() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let nutrient = services.nutrient.list().unwrap().remove(0);
+        services
+            .product
+            .create(CreateUpdateProduct {
+                nutrients: vec![SetProductNutrient {
+                    nutrient_id: nutrient.id,
+                    amount: 10.0,
+                }],
+                ..product("Kale")
+            })
+            .unwrap();
+        assert_eq!(
+            services.archive.export().unwrap().product_nutrients.len(),
+            1
+        );
+
+        services.nutrient.delete(nutrient.id).unwrap();
+
+        assert!(services
+            .archive
+            .export()
+            .unwrap()
+            .product_nutrients
+            .is_empty());
+    }
+}
@@ -1,11 +1,16 @@
 use std::{cell::RefCell, rc::Rc};
 
-use chrono::{Local, NaiveDate};
-use rusqlite::{params, Connection};
+use chrono::{Datelike, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 
-use super::ServiceError;
+use super::{
+    history::{day_value, record, History, Scope},
+    settings::SettingsService,
+    Macros, ServiceError,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NutritionTarget {
     pub day: NaiveDate,
     pub calories: f32,
@@ -30,34 +35,112 @@ impl NutritionTarget {
             carbohydrates,
         }
     }
+
+    /// Part of the target planned for a meal, `share` is in percents of the day.
+    pub fn portion(&self, share: f32) -> Macros {
+        Macros {
+            calories: self.calories * share / 100.0,
+            fats: self.fats * share / 100.0,
+            proteins: self.proteins * share / 100.0,
+            carbohydrates: self.carbohydrates * share / 100.0,
+        }
+    }
+}
+
+/// Values replacing the target set on `day` for one weekday while that target is in effect, for
+/// example higher carbohydrates on training days. Weekdays are stored as number of days from
+/// monday.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekdayTarget {
+    pub day: NaiveDate,
+    pub weekday: u32,
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+}
+
+impl WeekdayTarget {
+    fn apply(&self, target: &NutritionTarget) -> NutritionTarget {
+        NutritionTarget {
+            day: target.day,
+            calories: self.calories,
+            fats: self.fats,
+            proteins: self.proteins,
+            carbohydrates: self.carbohydrates,
+        }
+    }
+}
+
+/// Share (in percents) of the day target planned for meals named `meal` while target set on `day`
+/// is in effect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MealBudget {
+    pub day: NaiveDate,
+    pub meal: String,
+    pub share: f32,
+}
+
+/// Target in effect on `day` with weekday schedule applied. `targets` have to be sorted by day
+/// and `weekdays` can contain schedules of any of them.
+pub(crate) fn target_in_effect(
+    day: NaiveDate,
+    targets: &[NutritionTarget],
+    weekdays: &[WeekdayTarget],
+) -> Option<NutritionTarget> {
+    let target = targets.iter().rev().find(|t| t.day <= day)?;
+    let weekday = day.weekday().num_days_from_monday();
+
+    Some(
+        weekdays
+            .iter()
+            .find(|w| w.day == target.day && w.weekday == weekday)
+            .map(|w| w.apply(target))
+            .unwrap_or(target.clone()),
+    )
+}
+
+fn insert_target(db: &Connection, target: &NutritionTarget) -> Result<(), ServiceError> {
+    let query = "
+        INSERT INTO nutrition_targets (day, calories, fats, proteins, carbohydrates)
+        VALUES (?1, ?2, ?3, ?4, ?5)";
+    let args = params![
+        format!("{}", target.day.format("%Y-%m-%d")),
+        target.calories,
+        target.fats,
+        target.proteins,
+        target.carbohydrates
+    ];
+
+    db.execute(query, args).map_err(ServiceError::from)?;
+
+    Ok(())
 }
 
 pub struct NutritionTargetService {
     db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
 }
 
 impl NutritionTargetService {
-    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
-        NutritionTargetService { db }
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        NutritionTargetService { db, history }
     }
 
     pub fn create(&self, target: NutritionTarget) -> Result<(), ServiceError> {
-        let query = "
-            INSERT INTO nutrition_targets (day, calories, fats, proteins, carbohydrates)
-    	    VALUES (?1, ?2, ?3, ?4, ?5)";
-        let args = params![
-            format!("{}", target.day.format("%Y-%m-%d")),
-            target.calories,
-            target.fats,
-            target.proteins,
-            target.carbohydrates
-        ];
-
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [Scope::new(
+            "nutrition_targets",
+            "day = ?1",
+            day_value(target.day),
+        )];
 
-        Ok(())
+        record(
+            &self.db,
+            &self.history,
+            "Add nutrition target",
+            &scopes,
+            |tx| insert_target(tx, &target),
+        )
     }
 
     pub fn update(&self, target: NutritionTarget) -> Result<(), ServiceError> {
@@ -73,11 +156,22 @@ impl NutritionTargetService {
             format!("{}", target.day.format("%Y-%m-%d")),
         ];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [Scope::new(
+            "nutrition_targets",
+            "day = ?1",
+            day_value(target.day),
+        )];
 
-        Ok(())
+        record(
+            &self.db,
+            &self.history,
+            "Change nutrition target",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
     }
 
     pub fn read(&self, day: NaiveDate) -> Result<NutritionTarget, ServiceError> {
@@ -129,25 +223,285 @@ impl NutritionTargetService {
             Ok(t) => Ok(t),
             Err(ServiceError::NoRows) => {
                 let today = Local::now().date_naive();
-                let target = NutritionTarget::new(today, 2500.0, 80.0, 200.0, 245.0);
-                self.create(target)?;
+                let defaults = SettingsService::new(self.db.clone(), self.history.clone())
+                    .read()?
+                    .default_target;
+                let target = NutritionTarget::new(
+                    today,
+                    defaults.calories,
+                    defaults.fats,
+                    defaults.proteins,
+                    defaults.carbohydrates,
+                );
+                // Default target is not a change made by the user, so it is not undoable.
+                insert_target(&self.db.borrow(), &target)?;
                 self.read_last()
             }
             err => err,
         }
     }
 
+    /// Target in effect on `day`, which is the last target set on or before it with weekday
+    /// schedule of that target applied. Returned target keeps the day it was set on.
+    pub fn read_in_effect(&self, day: NaiveDate) -> Result<Option<NutritionTarget>, ServiceError> {
+        let query = "
+            SELECT day, calories, fats, proteins, carbohydrates
+            FROM nutrition_targets
+            WHERE day <= ?1
+            ORDER BY day DESC
+            LIMIT 1";
+        let args = params![format!("{}", day.format("%Y-%m-%d"))];
+
+        let target = {
+            let db = self.db.borrow();
+            let mut stmt = db.prepare(query)?;
+            stmt.query_row(args, |row| {
+                Ok(NutritionTarget {
+                    day: row.get(0)?,
+                    calories: row.get(1)?,
+                    fats: row.get(2)?,
+                    proteins: row.get(3)?,
+                    carbohydrates: row.get(4)?,
+                })
+            })
+            .optional()?
+        };
+
+        match target {
+            Some(target) => {
+                let weekdays = self.list_weekdays(target.day)?;
+                Ok(target_in_effect(day, &[target], &weekdays))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Deletes target together with its weekday schedule and meal budgets.
     pub fn delete(&self, day: NaiveDate) -> Result<(), ServiceError> {
+        let scopes = [
+            Scope::new("nutrition_targets", "day = ?1", day_value(day)),
+            Scope::new(
+                "nutrition_target_weekdays",
+                "target_day = ?1",
+                day_value(day),
+            ),
+            Scope::new("nutrition_target_meals", "target_day = ?1", day_value(day)),
+        ];
+        let day = format!("{}", day.format("%Y-%m-%d"));
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete nutrition target",
+            &scopes,
+            |tx| {
+                tx.execute(
+                    "DELETE FROM nutrition_target_weekdays WHERE target_day = ?1",
+                    params![day],
+                )
+                .map_err(ServiceError::from)?;
+                tx.execute(
+                    "DELETE FROM nutrition_target_meals WHERE target_day = ?1",
+                    params![day],
+                )
+                .map_err(ServiceError::from)?;
+                tx.execute("DELETE FROM nutrition_targets WHERE day = ?1", params![day])
+                    .map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Sets values for one weekday of target set on `weekday_target.day`, replacing previous
+    /// values of that weekday.
+    pub fn set_weekday(&self, weekday_target: WeekdayTarget) -> Result<(), ServiceError> {
+        if weekday_target.weekday > 6 {
+            return Err(ServiceError::Custom(format!(
+                "invalid weekday {}",
+                weekday_target.weekday
+            )));
+        }
+
+        let query = "
+            INSERT INTO nutrition_target_weekdays
+                (target_day, weekday, calories, fats, proteins, carbohydrates)
+            SELECT day, ?2, ?3, ?4, ?5, ?6
+            FROM nutrition_targets
+            WHERE day = ?1
+            ON CONFLICT (target_day, weekday) DO UPDATE SET
+                calories = excluded.calories,
+                fats = excluded.fats,
+                proteins = excluded.proteins,
+                carbohydrates = excluded.carbohydrates";
+        let args = params![
+            format!("{}", weekday_target.day.format("%Y-%m-%d")),
+            weekday_target.weekday,
+            weekday_target.calories,
+            weekday_target.fats,
+            weekday_target.proteins,
+            weekday_target.carbohydrates,
+        ];
+        let scopes = [Scope::new(
+            "nutrition_target_weekdays",
+            "target_day = ?1",
+            day_value(weekday_target.day),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Change weekday target",
+            &scopes,
+            |tx| match tx.execute(query, args).map_err(ServiceError::from)? {
+                0 => Err(ServiceError::NoRows),
+                _ => Ok(()),
+            },
+        )
+    }
+
+    pub fn delete_weekday(&self, day: NaiveDate, weekday: u32) -> Result<(), ServiceError> {
+        let query = "
+            DELETE FROM nutrition_target_weekdays
+            WHERE target_day = ?1 AND weekday = ?2";
+        let args = params![format!("{}", day.format("%Y-%m-%d")), weekday];
+        let scopes = [Scope::new(
+            "nutrition_target_weekdays",
+            "target_day = ?1",
+            day_value(day),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete weekday target",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Weekday schedule of target set on `day`, ordered from monday.
+    pub fn list_weekdays(&self, day: NaiveDate) -> Result<Vec<WeekdayTarget>, ServiceError> {
+        let query = "
+            SELECT target_day, weekday, calories, fats, proteins, carbohydrates
+            FROM nutrition_target_weekdays
+            WHERE target_day = ?1
+            ORDER BY weekday ASC";
+        let args = params![format!("{}", day.format("%Y-%m-%d"))];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let weekdays = stmt
+            .query_map(args, |row| {
+                Ok(WeekdayTarget {
+                    day: row.get(0)?,
+                    weekday: row.get(1)?,
+                    calories: row.get(2)?,
+                    fats: row.get(3)?,
+                    proteins: row.get(4)?,
+                    carbohydrates: row.get(5)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<WeekdayTarget>, _>>()?;
+
+        Ok(weekdays)
+    }
+
+    /// Sets share of the day planned for a meal, shares of all meals of a target can't exceed
+    /// 100%.
+    pub fn set_meal_budget(&self, budget: MealBudget) -> Result<(), ServiceError> {
+        let planned: f32 = self
+            .list_meal_budgets(budget.day)?
+            .iter()
+            .filter(|b| !b.meal.eq_ignore_ascii_case(&budget.meal))
+            .map(|b| b.share)
+            .sum();
+        if budget.share < 0.0 || planned + budget.share > 100.0 {
+            return Err(ServiceError::Custom(format!(
+                "meal budgets must sum to at most 100%, {planned}% is already planned for other meals"
+            )));
+        }
+
+        let query = "
+            INSERT INTO nutrition_target_meals (target_day, meal, share)
+            SELECT day, ?2, ?3
+            FROM nutrition_targets
+            WHERE day = ?1
+            ON CONFLICT (target_day, meal) DO UPDATE SET share = excluded.share";
+        let args = params![
+            format!("{}", budget.day.format("%Y-%m-%d")),
+            budget.meal,
+            budget.share,
+        ];
+        let scopes = [Scope::new(
+            "nutrition_target_meals",
+            "target_day = ?1",
+            day_value(budget.day),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Change meal budget",
+            &scopes,
+            |tx| match tx.execute(query, args).map_err(ServiceError::from)? {
+                0 => Err(ServiceError::NoRows),
+                _ => Ok(()),
+            },
+        )
+    }
+
+    pub fn delete_meal_budget(&self, day: NaiveDate, meal: &str) -> Result<(), ServiceError> {
         let query = "
-            DELETE FROM nutrition_targets
-    	    WHERE day = ?1";
+            DELETE FROM nutrition_target_meals
+            WHERE target_day = ?1 AND meal = ?2";
+        let args = params![format!("{}", day.format("%Y-%m-%d")), meal];
+        let scopes = [Scope::new(
+            "nutrition_target_meals",
+            "target_day = ?1",
+            day_value(day),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete meal budget",
+            &scopes,
+            |tx| {
+                tx.execute(query, args).map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Meal budgets of target set on `day`, ordered by meal name.
+    pub fn list_meal_budgets(&self, day: NaiveDate) -> Result<Vec<MealBudget>, ServiceError> {
+        let query = "
+            SELECT target_day, meal, share
+            FROM nutrition_target_meals
+            WHERE target_day = ?1
+            ORDER BY meal ASC";
         let args = params![format!("{}", day.format("%Y-%m-%d"))];
 
         let db = self.db.borrow();
         let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
 
-        Ok(())
+        let budgets = stmt
+            .query_map(args, |row| {
+                Ok(MealBudget {
+                    day: row.get(0)?,
+                    meal: row.get(1)?,
+                    share: row.get(2)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<MealBudget>, _>>()?;
+
+        Ok(budgets)
     }
 
     pub fn list(&self) -> Result<Vec<NutritionTarget>, ServiceError> {
@@ -175,3 +529,130 @@ impl NutritionTargetService {
         Ok(targets)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::Services;
+
+    use super::*;
+
+    #[test]
+    fn weekday_schedule_replaces_target_and_budgets_are_limited() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        // 2025-01-06 is a monday.
+        let day = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        services
+            .nutrition_target
+            .create(NutritionTarget::new(day, 2000.0, 60.0, 150.0, 200.0))
+            .unwrap();
+        services
+            .nutrition_target
+            .set_weekday(WeekdayTarget {
+                day,
+                weekday: 2,
+                calories: 2400.0,
+                fats: 60.0,
+                proteins: 150.0,
+                carbohydrates: 300.0,
+            })
+            .unwrap();
+
+        let tuesday = services
+            .nutrition_target
+            .read_in_effect(day.succ_opt().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(tuesday.calories, 2000.0);
+        let wednesday = services
+            .nutrition_target
+            .read_in_effect(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(wednesday.day, day);
+        assert_eq!(wednesday.carbohydrates, 300.0);
+        assert!(services
+            .nutrition_target
+            .read_in_effect(day.pred_opt().unwrap())
+            .unwrap()
+            .is_none());
+
+        let budget = |meal: &str, share| MealBudget {
+            day,
+            meal: meal.to_string(),
+            share,
+        };
+        services
+            .nutrition_target
+            .set_meal_budget(budget("Breakfast", 30.0))
+            .unwrap();
+        services
+            .nutrition_target
+            .set_meal_budget(budget("Dinner", 70.0))
+            .unwrap();
+        assert!(services
+            .nutrition_target
+            .set_meal_budget(budget("Snack", 10.0))
+            .is_err());
+        assert_eq!(tuesday.portion(30.0).calories, 600.0);
+
+        services.nutrition_target.delete(day).unwrap();
+        assert!(services
+            .nutrition_target
+            .list_weekdays(day)
+            .unwrap()
+            .is_empty());
+        assert!(services
+            .nutrition_target
+            .list_meal_budgets(day)
+            .unwrap()
+            .is_empty());
+
+        // Schedule and budgets can be undone one by one.
+        services.history.undo().unwrap();
+        assert_eq!(
+            services
+                .nutrition_target
+                .list_meal_budgets(day)
+                .unwrap()
+                .len(),
+            2
+        );
+        services
+            .nutrition_target
+            .delete_meal_budget(day, "Dinner")
+            .unwrap();
+        services.nutrition_target.delete_weekday(day, 2).unwrap();
+        services.history.undo().unwrap();
+        assert_eq!(
+            services.nutrition_target.list_weekdays(day).unwrap()[0].calories,
+            2400.0
+        );
+        services.history.undo().unwrap();
+        assert_eq!(
+            services
+                .nutrition_target
+                .list_meal_budgets(day)
+                .unwrap()
+                .len(),
+            2
+        );
+        services.history.undo().unwrap();
+        services.history.undo().unwrap();
+        assert_eq!(
+            services
+                .nutrition_target
+                .list_meal_budgets(day)
+                .unwrap()
+                .len(),
+            0
+        );
+        services.history.undo().unwrap();
+        assert!(services
+            .nutrition_target
+            .list_weekdays(day)
+            .unwrap()
+            .is_empty());
+    }
+}
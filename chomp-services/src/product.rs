@@ -1,11 +1,33 @@
 use std::{cell::RefCell, fmt, rc::Rc};
 
-use rusqlite::{params, Connection};
-use serde::Deserialize;
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+use serde::{Deserialize, Serialize};
 
-use super::ServiceError;
+use super::{
+    history::{id_value, record, History, Scope},
+    meals::freeze_past_meal_products,
+    nutrient::{set_product_nutrients, SetProductNutrient},
+    recipe::{recalculate_recipe, recipe_id_for_product, recipes_with_ingredient},
+    search::{score, trigram_query, words, SearchField},
+    ServiceError,
+};
 
-#[derive(Debug, Clone)]
+/// Condition matching the product and recipes using it (directly or through other recipes),
+/// which are recalculated when it changes.
+pub(crate) const DEPENDENT_PRODUCTS: &str = "
+    IN (
+        WITH RECURSIVE dependents(product_id) AS (
+            SELECT ?1
+            UNION
+            SELECT recipes.product_id
+            FROM dependents
+            INNER JOIN recipe_ingredients ON recipe_ingredients.product_id = dependents.product_id
+            INNER JOIN recipes ON recipes.id = recipe_ingredients.recipe_id
+        )
+        SELECT product_id FROM dependents
+    )";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Product {
     pub id: usize,
     pub name: String,
@@ -14,11 +36,32 @@ pub struct Product {
     pub fats: f32,
     pub proteins: f32,
     pub carbohydrates: f32,
+    pub fiber: Option<f32>,
+    pub sugar: Option<f32>,
+    pub saturated_fats: Option<f32>,
+    pub salt: Option<f32>,
+    #[serde(default)]
+    pub barcode: Option<String>,
+    /// Archived products are hidden from product lists and search but stay in past meals.
+    #[serde(default)]
+    pub archived: bool,
 }
 
+impl Product {
+    /// Sodium is not stored separately, it's derived from salt (sodium chloride).
+    pub fn sodium(&self) -> Option<f32> {
+        self.salt.map(|salt| salt / 2.5)
+    }
+}
+
+/// Company is included so that products with the same name from different brands can be told
+/// apart in pick lists.
 impl fmt::Display for Product {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.name)
+        match &self.company {
+            Some(company) if !company.is_empty() => write!(f, "{} ({})", self.name, company),
+            _ => write!(f, "{}", self.name),
+        }
     }
 }
 
@@ -30,43 +73,142 @@ pub struct CreateUpdateProduct {
     pub fats: f32,
     pub proteins: f32,
     pub carbohydrates: f32,
+    pub fiber: Option<f32>,
+    pub sugar: Option<f32>,
+    pub saturated_fats: Option<f32>,
+    pub salt: Option<f32>,
+    #[serde(default)]
+    pub barcode: Option<String>,
+    #[serde(default)]
+    pub nutrients: Vec<SetProductNutrient>,
+}
+
+/// Checks length and check digit of EAN-8, UPC-A, EAN-13 and GTIN-14 barcodes.
+pub fn is_valid_barcode(barcode: &str) -> bool {
+    if !matches!(barcode.len(), 8 | 12 | 13 | 14) || !barcode.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let digits: Vec<u32> = barcode.chars().filter_map(|c| c.to_digit(10)).collect();
+    let (check_digit, body) = digits.split_last().unwrap();
+
+    // Weights alternate 3, 1, 3... starting from the digit right next to the check digit.
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+
+    (10 - sum % 10) % 10 == *check_digit
+}
+
+fn validate_barcode(barcode: &Option<String>) -> Result<(), ServiceError> {
+    match barcode {
+        Some(b) if !is_valid_barcode(b) => Err(ServiceError::Custom(format!(
+            "invalid barcode {b}, expected EAN-8, UPC-A, EAN-13 or GTIN-14 with valid check digit"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn insert_product(tx: &Transaction, product: &CreateUpdateProduct) -> Result<(), ServiceError> {
+    let query = "
+        INSERT INTO products (
+            name, company, calories, fats, proteins, carbohydrates,
+            fiber, sugar, saturated_fats, salt, barcode
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+    let args = params![
+        product.name,
+        product.company,
+        product.calories,
+        product.fats,
+        product.proteins,
+        product.carbohydrates,
+        product.fiber,
+        product.sugar,
+        product.saturated_fats,
+        product.salt,
+        product.barcode
+    ];
+
+    tx.execute(query, args).map_err(ServiceError::from)?;
+    let id = tx.last_insert_rowid() as usize;
+    set_product_nutrients(tx, id, &product.nutrients)
+}
+
+/// Creates products of a bulk import, see [`ProductService::import_many`].
+pub struct ProductImporter<'a> {
+    tx: &'a Transaction<'a>,
+}
+
+impl ProductImporter<'_> {
+    /// Creates product, nothing is left behind when it fails so that the import can go on with
+    /// the next one.
+    pub fn create(&self, product: &CreateUpdateProduct) -> Result<(), ServiceError> {
+        validate_barcode(&product.barcode)?;
+
+        self.tx.execute_batch("SAVEPOINT import_product")?;
+        let result = insert_product(self.tx, product);
+        let end = match result {
+            Ok(_) => "RELEASE import_product",
+            Err(_) => "ROLLBACK TO import_product; RELEASE import_product",
+        };
+        self.tx.execute_batch(end)?;
+
+        result
+    }
 }
 
 pub struct ProductService {
     db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
 }
 
 #[allow(unused)]
 impl ProductService {
-    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
-        ProductService { db }
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        ProductService { db, history }
     }
 
     pub fn create(&self, product: CreateUpdateProduct) -> Result<(), ServiceError> {
-        let query = "
-            INSERT INTO products (name, company, calories, fats, proteins, carbohydrates)
-    	    VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
-        let args = params![
-            product.name,
-            product.company,
-            product.calories,
-            product.fats,
-            product.proteins,
-            product.carbohydrates
+        validate_barcode(&product.barcode)?;
+
+        let scopes = [
+            Scope::inserted("products"),
+            Scope::inserted("product_nutrients"),
         ];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        record(&self.db, &self.history, "Add product", &scopes, |tx| {
+            insert_product(tx, &product)
+        })
+    }
+
+    /// Runs bulk import of products in a single transaction. Imported products are not recorded
+    /// one by one, history is cleared instead because recorded changes no longer describe the
+    /// database.
+    pub fn import_many<T>(
+        &self,
+        import: impl FnOnce(&ProductImporter) -> T,
+    ) -> Result<T, ServiceError> {
+        let mut db = self.db.borrow_mut();
+        let tx = db.transaction()?;
+        let result = import(&ProductImporter { tx: &tx });
+        tx.commit()?;
+        self.history.borrow_mut().clear();
 
-        Ok(())
+        Ok(result)
     }
 
     pub fn update(&self, id: usize, product: CreateUpdateProduct) -> Result<(), ServiceError> {
+        validate_barcode(&product.barcode)?;
+
         let query = "
             UPDATE products
-            SET name=?1, company=?2, calories=?3, fats=?4, proteins=?5, carbohydrates=?6
-            WHERE id = ?7";
+            SET name=?1, company=?2, calories=?3, fats=?4, proteins=?5, carbohydrates=?6,
+                fiber=?7, sugar=?8, saturated_fats=?9, salt=?10, barcode=?11
+            WHERE id = ?12";
         let args = params![
             product.name,
             product.company,
@@ -74,19 +216,42 @@ impl ProductService {
             product.fats,
             product.proteins,
             product.carbohydrates,
+            product.fiber,
+            product.sugar,
+            product.saturated_fats,
+            product.salt,
+            product.barcode,
             id
         ];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [
+            Scope::new("products", format!("id {DEPENDENT_PRODUCTS}"), id_value(id)),
+            Scope::new(
+                "product_nutrients",
+                format!("product_id {DEPENDENT_PRODUCTS}"),
+                id_value(id),
+            ),
+        ];
 
-        Ok(())
+        record(&self.db, &self.history, "Change product", &scopes, |tx| {
+            freeze_past_meal_products(tx, id)?;
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            set_product_nutrients(tx, id, &product.nutrients)?;
+            if let Some(recipe_id) = recipe_id_for_product(tx, id)? {
+                recalculate_recipe(tx, recipe_id)?;
+            }
+            for recipe_id in recipes_with_ingredient(tx, id)? {
+                recalculate_recipe(tx, recipe_id)?;
+            }
+            Ok(())
+        })
     }
 
     pub fn read(&self, id: usize) -> Result<Product, ServiceError> {
         let query = "
-            SELECT id, name, company, calories, fats, proteins, carbohydrates
+            SELECT
+                id, name, company, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, barcode, archived
             FROM products
     		WHERE id = ?1";
         let args = params![id];
@@ -103,28 +268,95 @@ impl ProductService {
                 fats: row.get(4)?,
                 proteins: row.get(5)?,
                 carbohydrates: row.get(6)?,
+                fiber: row.get(7)?,
+                sugar: row.get(8)?,
+                saturated_fats: row.get(9)?,
+                salt: row.get(10)?,
+                barcode: row.get(11)?,
+                archived: row.get(12)?,
             })
         })
         .map_err(ServiceError::from)
     }
 
-    pub fn delete(&self, id: usize) -> Result<(), ServiceError> {
+    pub fn read_by_barcode(&self, barcode: &str) -> Result<Product, ServiceError> {
         let query = "
-            DELETE FROM products
-    	    WHERE id = ?1";
-        let args = params![id];
+            SELECT
+                id, name, company, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, barcode, archived
+            FROM products
+    		WHERE barcode = ?1 AND archived = 0";
+        let args = params![barcode];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        stmt.query_row(args, |row| {
+            Ok(Product {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                company: row.get(2)?,
+                calories: row.get(3)?,
+                fats: row.get(4)?,
+                proteins: row.get(5)?,
+                carbohydrates: row.get(6)?,
+                fiber: row.get(7)?,
+                sugar: row.get(8)?,
+                saturated_fats: row.get(9)?,
+                salt: row.get(10)?,
+                barcode: row.get(11)?,
+                archived: row.get(12)?,
+            })
+        })
+        .map_err(ServiceError::from)
+    }
+
+    /// Archives the product instead of deleting it, meals it was eaten in keep it and so their
+    /// totals don't change. Recipes using it keep it as an ingredient as well.
+    pub fn delete(&self, id: usize) -> Result<(), ServiceError> {
+        self.set_archived(id, true, "Archive product")
+    }
+
+    /// Whether the name belongs to an archived product. Such name can't be used for a new
+    /// product, the archived one has to be restored instead.
+    pub fn is_name_archived(&self, name: &str) -> Result<bool, ServiceError> {
+        let query = "SELECT EXISTS(SELECT 1 FROM products WHERE name = ?1 AND archived = 1)";
+        let args = params![name];
 
         let db = self.db.borrow();
         let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        stmt.query_row(args, |row| row.get(0))
+            .map_err(ServiceError::from)
+    }
+
+    /// Brings archived product back to product lists and search.
+    pub fn restore(&self, id: usize) -> Result<(), ServiceError> {
+        self.set_archived(id, false, "Restore product")
+    }
 
-        Ok(())
+    fn set_archived(&self, id: usize, archived: bool, label: &str) -> Result<(), ServiceError> {
+        let query = "
+            UPDATE products
+            SET archived = ?1
+            WHERE id = ?2";
+        let args = params![archived, id];
+        let scopes = [Scope::new("products", "id = ?1", id_value(id))];
+
+        record(&self.db, &self.history, label, &scopes, |tx| {
+            match tx.execute(query, args).map_err(ServiceError::from)? {
+                0 => Err(ServiceError::NoRows),
+                _ => Ok(()),
+            }
+        })
     }
 
     pub fn list(&self) -> Result<Vec<Product>, ServiceError> {
         let query = "
-            SELECT id, name, company, calories, fats, proteins, carbohydrates
+            SELECT
+                id, name, company, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, barcode, archived
             FROM products
+            WHERE archived = 0
             ORDER BY id ASC";
 
         let db = self.db.borrow();
@@ -140,6 +372,12 @@ impl ProductService {
                     fats: row.get(4)?,
                     proteins: row.get(5)?,
                     carbohydrates: row.get(6)?,
+                    fiber: row.get(7)?,
+                    sugar: row.get(8)?,
+                    saturated_fats: row.get(9)?,
+                    salt: row.get(10)?,
+                    barcode: row.get(11)?,
+                    archived: row.get(12)?,
                 })
             })
             .map_err(ServiceError::from)?
@@ -147,4 +385,317 @@ impl ProductService {
 
         Ok(products)
     }
+
+    pub fn list_archived(&self) -> Result<Vec<Product>, ServiceError> {
+        let query = "
+            SELECT
+                id, name, company, calories, fats, proteins, carbohydrates,
+                fiber, sugar, saturated_fats, salt, barcode, archived
+            FROM products
+            WHERE archived = 1
+            ORDER BY name ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let products = stmt
+            .query_map([], |row| {
+                Ok(Product {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    company: row.get(2)?,
+                    calories: row.get(3)?,
+                    fats: row.get(4)?,
+                    proteins: row.get(5)?,
+                    carbohydrates: row.get(6)?,
+                    fiber: row.get(7)?,
+                    sugar: row.get(8)?,
+                    saturated_fats: row.get(9)?,
+                    salt: row.get(10)?,
+                    barcode: row.get(11)?,
+                    archived: row.get(12)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<Product>, _>>()?;
+
+        Ok(products)
+    }
+
+    /// Products matching the query ranked from the best match, see `search` module. Name,
+    /// aliases and company are searched. Empty query returns all products ordered by name.
+    pub fn search(&self, query: &str) -> Result<Vec<Product>, ServiceError> {
+        let query_words = words(query);
+        if query_words.is_empty() {
+            let mut products = self.list()?;
+            products.sort_by_key(|p| p.name.to_lowercase());
+            return Ok(products);
+        }
+
+        let columns = "
+                products.id, products.name, products.company, products.calories, products.fats,
+                products.proteins, products.carbohydrates, products.fiber, products.sugar,
+                products.saturated_fats, products.salt, products.barcode, products.archived,
+                COALESCE(product_search.aliases, '')";
+        let fts_query = trigram_query(&query_words);
+
+        // Queries without trigrams (only words shorter than 3 characters) rank all products.
+        let query = match fts_query {
+            Some(_) => format!(
+                "
+                SELECT {columns}
+                FROM product_search
+                INNER JOIN products ON products.id = product_search.rowid
+                WHERE product_search MATCH ?1 AND products.archived = 0"
+            ),
+            None => format!(
+                "
+                SELECT {columns}
+                FROM products
+                LEFT JOIN product_search ON product_search.rowid = products.id
+                WHERE products.archived = 0"
+            ),
+        };
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(&query)?;
+
+        let candidates = stmt
+            .query_map(params_from_iter(fts_query.iter()), |row| {
+                Ok((
+                    Product {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        company: row.get(2)?,
+                        calories: row.get(3)?,
+                        fats: row.get(4)?,
+                        proteins: row.get(5)?,
+                        carbohydrates: row.get(6)?,
+                        fiber: row.get(7)?,
+                        sugar: row.get(8)?,
+                        saturated_fats: row.get(9)?,
+                        salt: row.get(10)?,
+                        barcode: row.get(11)?,
+                        archived: row.get(12)?,
+                    },
+                    row.get::<_, String>(13)?,
+                ))
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<(Product, String)>, _>>()?;
+
+        let mut ranked: Vec<(f32, Product)> = candidates
+            .into_iter()
+            .filter_map(|(product, aliases)| {
+                let fields = [
+                    SearchField {
+                        text: &product.name,
+                        weight: 1.0,
+                    },
+                    SearchField {
+                        text: &aliases,
+                        weight: 0.95,
+                    },
+                    SearchField {
+                        text: product.company.as_deref().unwrap_or_default(),
+                        weight: 0.7,
+                    },
+                ];
+                score(&query_words, &fields).map(|score| (score, product))
+            })
+            .collect();
+        ranked.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then(a.name.len().cmp(&b.name.len()))
+                .then(a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+
+        Ok(ranked.into_iter().map(|(_, product)| product).collect())
+    }
+
+    /// Alternative names the product can be found by, for example "PB" for peanut butter.
+    pub fn list_aliases(&self, id: usize) -> Result<Vec<String>, ServiceError> {
+        let query = "
+            SELECT alias
+            FROM product_aliases
+            WHERE product_id = ?1
+            ORDER BY alias ASC";
+        let args = params![id];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let aliases = stmt
+            .query_map(args, |row| row.get(0))
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(aliases)
+    }
+
+    /// Replaces aliases of the product, blank and duplicate aliases are skipped.
+    pub fn set_aliases(&self, id: usize, aliases: &[String]) -> Result<(), ServiceError> {
+        let aliases: Vec<&str> = aliases
+            .iter()
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect();
+        let scopes = [Scope::new(
+            "product_aliases",
+            "product_id = ?1",
+            id_value(id),
+        )];
+
+        // Kept aliases are left untouched, so that setting the same aliases is not a change.
+        record(
+            &self.db,
+            &self.history,
+            "Change product aliases",
+            &scopes,
+            |tx| {
+                let mut stmt =
+                    tx.prepare("SELECT alias FROM product_aliases WHERE product_id = ?1")?;
+                let existing = stmt
+                    .query_map(params![id], |row| row.get(0))
+                    .map_err(ServiceError::from)?
+                    .collect::<Result<Vec<String>, _>>()?;
+                for alias in existing
+                    .iter()
+                    .filter(|e| !aliases.iter().any(|a| a.to_lowercase() == e.to_lowercase()))
+                {
+                    tx.execute(
+                        "DELETE FROM product_aliases WHERE product_id = ?1 AND alias = ?2",
+                        params![id, alias],
+                    )
+                    .map_err(ServiceError::from)?;
+                }
+                for alias in &aliases {
+                    tx.execute(
+                        "
+                        INSERT INTO product_aliases (product_id, alias)
+                        VALUES (?1, ?2)
+                        ON CONFLICT (product_id, alias) DO NOTHING",
+                        params![id, alias],
+                    )
+                    .map_err(ServiceError::from)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, Services};
+
+    use super::*;
+
+    #[test]
+    fn validates_barcode_check_digit() {
+        assert!(is_valid_barcode("96385074"));
+        assert!(is_valid_barcode("036000291452"));
+        assert!(is_valid_barcode("4006381333931"));
+        assert!(is_valid_barcode("10012345678902"));
+
+        assert!(!is_valid_barcode("4006381333932"));
+        assert!(!is_valid_barcode("400638133393"));
+        assert!(!is_valid_barcode("40063813339a1"));
+    }
+
+    #[test]
+    fn search_tolerates_typos_and_matches_aliases_and_company() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        for (name, company) in [
+            ("Chicken breast", Some("Farm Fresh")),
+            ("Chicken breast fillet", Some("Roastery")),
+            ("Crème fraîche", None),
+            ("Peanut butter", None),
+            ("Breadsticks", None),
+        ] {
+            services
+                .product
+                .create(CreateUpdateProduct {
+                    company: company.map(str::to_string),
+                    ..product(name)
+                })
+                .unwrap();
+        }
+        let names = |query: &str| -> Vec<String> {
+            services
+                .product
+                .search(query)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.name)
+                .collect()
+        };
+
+        assert_eq!(names("chiken brest").len(), 2);
+        assert_eq!(names("creme fraiche"), vec!["Crème fraîche"]);
+        assert_eq!(names("roastery chicken"), vec!["Chicken breast fillet"]);
+        assert!(names("pb").is_empty());
+
+        let peanut_butter = services.product.search("peanut").unwrap()[0].id;
+        services
+            .product
+            .set_aliases(peanut_butter, &["PB".to_string(), " ".to_string()])
+            .unwrap();
+        assert_eq!(names("pb"), vec!["Peanut butter"]);
+        assert_eq!(
+            services.product.list_aliases(peanut_butter).unwrap(),
+            ["PB"]
+        );
+
+        services.product.delete(peanut_butter).unwrap();
+        assert!(names("pb").is_empty());
+    }
+
+    #[test]
+    fn import_many_skips_failed_products_and_clears_history() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services.product.create(product("Oat milk")).unwrap();
+
+        let results = services
+            .product
+            .import_many(|importer| {
+                ["Oat milk", "Rice", "Oat milk"]
+                    .map(|name| importer.create(&product(name)))
+                    .map(|result| result.is_ok())
+            })
+            .unwrap();
+        assert_eq!(results, [false, true, false]);
+        assert_eq!(services.product.list().unwrap().len(), 2);
+        assert!(!services.history.can_undo());
+    }
+
+    #[test]
+    fn archived_products_cant_be_added_to_meals() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        services
+            .product
+            .create(CreateUpdateProduct {
+                barcode: Some("96385074".to_string()),
+                ..product("Oat milk")
+            })
+            .unwrap();
+        let oat_milk = services.product.read_by_barcode("96385074").unwrap();
+        assert!(!services.product.is_name_archived("Oat milk").unwrap());
+
+        services.product.delete(oat_milk.id).unwrap();
+        assert!(services.product.read_by_barcode("96385074").is_err());
+        assert!(services.product.is_name_archived("Oat milk").unwrap());
+
+        let meals = services.meal.list_or_create_default(day).unwrap();
+        let added = services.meal.add_product(crate::AddMealProduct {
+            meal_id: meals[0].id,
+            product_id: oat_milk.id,
+            weight: 200.0,
+        });
+        assert!(matches!(added, Err(ServiceError::Custom(_))));
+    }
 }
@@ -1,11 +1,14 @@
 use std::{cell::RefCell, rc::Rc};
 
 use rusqlite::{params, Connection};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::ServiceError;
+use super::{
+    history::{id_value, record, History, Scope},
+    ServiceError,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductPortion {
     pub id: usize,
     pub name: String,
@@ -22,11 +25,12 @@ pub struct CreateProductPortion {
 
 pub struct ProductPortionService {
     db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
 }
 
 impl ProductPortionService {
-    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
-        ProductPortionService { db }
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        ProductPortionService { db, history }
     }
 
     pub fn create(&self, product_portion: CreateProductPortion) -> Result<(), ServiceError> {
@@ -38,12 +42,12 @@ impl ProductPortionService {
             product_portion.product_id,
             product_portion.weight
         ];
+        let scopes = [Scope::inserted("product_portions")];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        record(&self.db, &self.history, "Add portion", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn update(&self, product_portion: ProductPortion) -> Result<(), ServiceError> {
@@ -56,12 +60,16 @@ impl ProductPortionService {
             product_portion.weight,
             product_portion.id,
         ];
-
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        let scopes = [Scope::new(
+            "product_portions",
+            "id = ?1",
+            id_value(product_portion.id),
+        )];
+
+        record(&self.db, &self.history, "Change portion", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn read(&self, id: usize) -> Result<ProductPortion, ServiceError> {
@@ -90,12 +98,12 @@ impl ProductPortionService {
             DELETE FROM product_portions
     	    WHERE id = ?1";
         let args = params![id];
+        let scopes = [Scope::new("product_portions", "id = ?1", id_value(id))];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
-
-        Ok(())
+        record(&self.db, &self.history, "Delete portion", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn list_all(&self) -> Result<Vec<ProductPortion>, ServiceError> {
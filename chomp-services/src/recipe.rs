@@ -0,0 +1,674 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    history::{id_value, record, History, Scope},
+    meals::freeze_past_meal_products,
+    product::DEPENDENT_PRODUCTS,
+    ServiceError,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeIngredient {
+    pub id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+    pub name: String,
+    pub company: Option<String>,
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+}
+
+/// Recipe is backed by a row in `products`, nutrition values of that product (per 100g) are
+/// derived from ingredients and final weight of the recipe, so it can be used in meals in the
+/// same way as any other product.
+#[derive(Debug, Clone, Serialize)]
+pub struct Recipe {
+    pub id: usize,
+    pub product_id: usize,
+    pub name: String,
+    pub weight: f32,
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+    pub ingredients: Vec<RecipeIngredient>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateUpdateRecipe {
+    pub name: String,
+    pub weight: f32,
+}
+
+#[derive(Debug)]
+pub struct AddRecipeIngredient {
+    pub recipe_id: usize,
+    pub product_id: usize,
+    pub weight: f32,
+}
+
+#[derive(Debug)]
+pub struct UpdateRecipeIngredientWeight {
+    pub recipe_ingredient_id: usize,
+    pub weight: f32,
+}
+
+pub struct RecipeService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl RecipeService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        RecipeService { db, history }
+    }
+
+    pub fn create(&self, recipe: CreateUpdateRecipe) -> Result<(), ServiceError> {
+        if recipe.weight <= 0.0 {
+            return Err(ServiceError::Custom(
+                "recipe weight must be greater than zero".to_string(),
+            ));
+        }
+
+        let create_product_query = "
+            INSERT INTO products (name, company, calories, fats, proteins, carbohydrates)
+    	    VALUES (?1, NULL, 0, 0, 0, 0)";
+        let create_recipe_query = "
+            INSERT INTO recipes (product_id, weight)
+    	    VALUES (?1, ?2)";
+
+        let scopes = [Scope::inserted("products"), Scope::inserted("recipes")];
+
+        record(&self.db, &self.history, "Add recipe", &scopes, |tx| {
+            tx.execute(create_product_query, params![recipe.name])
+                .map_err(ServiceError::from)?;
+            let product_id = tx.last_insert_rowid();
+            tx.execute(create_recipe_query, params![product_id, recipe.weight])
+                .map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn update(&self, id: usize, recipe: CreateUpdateRecipe) -> Result<(), ServiceError> {
+        if recipe.weight <= 0.0 {
+            return Err(ServiceError::Custom(
+                "recipe weight must be greater than zero".to_string(),
+            ));
+        }
+
+        let update_product_query = "
+            UPDATE products
+            SET name = ?1
+            WHERE id = (SELECT product_id FROM recipes WHERE id = ?2)";
+        let update_recipe_query = "
+            UPDATE recipes
+            SET weight = ?1
+            WHERE id = ?2";
+
+        let scopes = self.scopes(id)?;
+
+        record(&self.db, &self.history, "Change recipe", &scopes, |tx| {
+            tx.execute(update_product_query, params![recipe.name, id])
+                .map_err(ServiceError::from)?;
+            tx.execute(update_recipe_query, params![recipe.weight, id])
+                .map_err(ServiceError::from)?;
+            recalculate_recipe(tx, id)
+        })
+    }
+
+    pub fn read(&self, id: usize) -> Result<Recipe, ServiceError> {
+        let query = "
+            SELECT
+                recipes.id,
+                recipes.product_id,
+                products.name,
+                recipes.weight,
+                products.calories,
+                products.fats,
+                products.proteins,
+                products.carbohydrates
+            FROM recipes
+            INNER JOIN products ON recipes.product_id = products.id
+            WHERE recipes.id = ?1";
+        let args = params![id];
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let mut recipe = stmt
+            .query_row(args, |row| {
+                Ok(Recipe {
+                    id: row.get(0)?,
+                    product_id: row.get(1)?,
+                    name: row.get(2)?,
+                    weight: row.get(3)?,
+                    calories: row.get(4)?,
+                    fats: row.get(5)?,
+                    proteins: row.get(6)?,
+                    carbohydrates: row.get(7)?,
+                    ingredients: Vec::new(),
+                })
+            })
+            .map_err(ServiceError::from)?;
+
+        recipe.ingredients = list_ingredients(&db, id)?;
+
+        Ok(recipe)
+    }
+
+    pub fn read_by_product_id(&self, product_id: usize) -> Result<Recipe, ServiceError> {
+        let recipe_id = recipe_id_for_product(&self.db.borrow(), product_id)?;
+
+        match recipe_id {
+            Some(id) => self.read(id),
+            None => Err(ServiceError::NoRows),
+        }
+    }
+
+    /// Archives product of the recipe like `ProductService::delete`, the recipe itself is kept so
+    /// that restoring the product brings it back.
+    pub fn delete(&self, id: usize) -> Result<(), ServiceError> {
+        let query = "
+            UPDATE products
+            SET archived = 1
+            WHERE id = (SELECT product_id FROM recipes WHERE id = ?1)";
+        let args = params![id];
+        let scopes = [Scope::new(
+            "products",
+            "id = (SELECT product_id FROM recipes WHERE id = ?1)",
+            id_value(id),
+        )];
+
+        record(
+            &self.db,
+            &self.history,
+            "Archive recipe",
+            &scopes,
+            |tx| match tx.execute(query, args).map_err(ServiceError::from)? {
+                0 => Err(ServiceError::NoRows),
+                _ => Ok(()),
+            },
+        )
+    }
+
+    pub fn list(&self) -> Result<Vec<Recipe>, ServiceError> {
+        let query = "
+            SELECT
+                recipes.id,
+                recipes.product_id,
+                products.name,
+                recipes.weight,
+                products.calories,
+                products.fats,
+                products.proteins,
+                products.carbohydrates
+            FROM recipes
+            INNER JOIN products ON recipes.product_id = products.id
+            WHERE products.archived = 0
+            ORDER BY recipes.id ASC";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        let mut recipes = stmt
+            .query_map([], |row| {
+                Ok(Recipe {
+                    id: row.get(0)?,
+                    product_id: row.get(1)?,
+                    name: row.get(2)?,
+                    weight: row.get(3)?,
+                    calories: row.get(4)?,
+                    fats: row.get(5)?,
+                    proteins: row.get(6)?,
+                    carbohydrates: row.get(7)?,
+                    ingredients: Vec::new(),
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<Recipe>, _>>()?;
+
+        for recipe in recipes.iter_mut() {
+            recipe.ingredients = list_ingredients(&db, recipe.id)?;
+        }
+
+        Ok(recipes)
+    }
+
+    pub fn add_ingredient(&self, ingredient: AddRecipeIngredient) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO recipe_ingredients (recipe_id, product_id, weight)
+            VALUES (?1, ?2, ?3)";
+        let args = params![
+            ingredient.recipe_id,
+            ingredient.product_id,
+            ingredient.weight
+        ];
+
+        let scopes = self.scopes(ingredient.recipe_id)?;
+
+        record(&self.db, &self.history, "Add ingredient", &scopes, |tx| {
+            if recipe_depends_on(tx, ingredient.product_id, ingredient.recipe_id)? {
+                return Err(ServiceError::Custom(
+                    "recipe can't be used as its own ingredient".to_string(),
+                ));
+            }
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            recalculate_recipe(tx, ingredient.recipe_id)
+        })
+    }
+
+    pub fn update_ingredient_weight(
+        &self,
+        update_ingredient_weight: UpdateRecipeIngredientWeight,
+    ) -> Result<(), ServiceError> {
+        let query = "
+            UPDATE recipe_ingredients
+            SET weight = ?1
+            WHERE id = ?2
+            RETURNING recipe_id";
+        let args = params![
+            update_ingredient_weight.weight,
+            update_ingredient_weight.recipe_ingredient_id
+        ];
+        let recipe_id = self.ingredient_recipe_id(update_ingredient_weight.recipe_ingredient_id)?;
+        let scopes = self.scopes(recipe_id)?;
+
+        record(
+            &self.db,
+            &self.history,
+            "Change weight of ingredient",
+            &scopes,
+            |tx| {
+                let recipe_id: usize = tx
+                    .query_row(query, args, |row| row.get(0))
+                    .map_err(ServiceError::from)?;
+                recalculate_recipe(tx, recipe_id)
+            },
+        )
+    }
+
+    pub fn delete_ingredient(&self, recipe_ingredient_id: usize) -> Result<(), ServiceError> {
+        let query = "
+            DELETE FROM recipe_ingredients
+            WHERE id = ?1
+            RETURNING recipe_id";
+        let args = params![recipe_ingredient_id];
+        let recipe_id = self.ingredient_recipe_id(recipe_ingredient_id)?;
+        let scopes = self.scopes(recipe_id)?;
+
+        record(
+            &self.db,
+            &self.history,
+            "Remove ingredient",
+            &scopes,
+            |tx| {
+                let recipe_id: usize = tx
+                    .query_row(query, args, |row| row.get(0))
+                    .map_err(ServiceError::from)?;
+                recalculate_recipe(tx, recipe_id)
+            },
+        )
+    }
+
+    /// Rows a change of the recipe can touch, the recipe with its ingredients and its product
+    /// together with products of every recipe that uses it.
+    fn scopes(&self, recipe_id: usize) -> Result<[Scope; 4], ServiceError> {
+        let query = "SELECT product_id FROM recipes WHERE id = ?1";
+        let product_id: usize = self
+            .db
+            .borrow()
+            .query_row(query, params![recipe_id], |row| row.get(0))
+            .map_err(ServiceError::from)?;
+
+        Ok([
+            Scope::new(
+                "products",
+                format!("id {DEPENDENT_PRODUCTS}"),
+                id_value(product_id),
+            ),
+            Scope::new(
+                "product_nutrients",
+                format!("product_id {DEPENDENT_PRODUCTS}"),
+                id_value(product_id),
+            ),
+            Scope::new("recipes", "id = ?1", id_value(recipe_id)),
+            Scope::new("recipe_ingredients", "recipe_id = ?1", id_value(recipe_id)),
+        ])
+    }
+
+    fn ingredient_recipe_id(&self, recipe_ingredient_id: usize) -> Result<usize, ServiceError> {
+        let query = "SELECT recipe_id FROM recipe_ingredients WHERE id = ?1";
+
+        self.db
+            .borrow()
+            .query_row(query, params![recipe_ingredient_id], |row| row.get(0))
+            .map_err(ServiceError::from)
+    }
+}
+
+fn list_ingredients(
+    db: &Connection,
+    recipe_id: usize,
+) -> Result<Vec<RecipeIngredient>, ServiceError> {
+    let query = "
+        SELECT
+            recipe_ingredients.id,
+            recipe_ingredients.weight,
+            products.id,
+            products.name,
+            products.company,
+            products.calories * recipe_ingredients.weight / 100,
+            products.fats * recipe_ingredients.weight / 100,
+            products.proteins * recipe_ingredients.weight / 100,
+            products.carbohydrates * recipe_ingredients.weight / 100
+        FROM recipe_ingredients
+        INNER JOIN products ON recipe_ingredients.product_id = products.id
+        WHERE recipe_ingredients.recipe_id = ?1
+        ORDER BY recipe_ingredients.id ASC";
+    let args = params![recipe_id];
+
+    let mut stmt = db.prepare(query)?;
+
+    let ingredients = stmt
+        .query_map(args, |row| {
+            Ok(RecipeIngredient {
+                id: row.get(0)?,
+                weight: row.get(1)?,
+                product_id: row.get(2)?,
+                name: row.get(3)?,
+                company: row.get(4)?,
+                calories: row.get(5)?,
+                fats: row.get(6)?,
+                proteins: row.get(7)?,
+                carbohydrates: row.get(8)?,
+            })
+        })
+        .map_err(ServiceError::from)?
+        .collect::<Result<Vec<RecipeIngredient>, _>>()?;
+
+    Ok(ingredients)
+}
+
+/// Returns ids of recipes that use given product as one of their ingredients.
+pub(crate) fn recipes_with_ingredient(
+    db: &Connection,
+    product_id: usize,
+) -> Result<Vec<usize>, ServiceError> {
+    let query = "
+        SELECT recipe_id
+        FROM recipe_ingredients
+        WHERE product_id = ?1";
+    let args = params![product_id];
+
+    let mut stmt = db.prepare(query)?;
+
+    let recipe_ids = stmt
+        .query_map(args, |row| row.get(0))
+        .map_err(ServiceError::from)?
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    Ok(recipe_ids)
+}
+
+/// Returns id of recipe backed by given product, if there is one.
+pub(crate) fn recipe_id_for_product(
+    db: &Connection,
+    product_id: usize,
+) -> Result<Option<usize>, ServiceError> {
+    let query = "
+        SELECT id
+        FROM recipes
+        WHERE product_id = ?1";
+    let args = params![product_id];
+
+    match db.query_row(query, args, |row| row.get(0)) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(err) => Err(ServiceError::from(err)),
+    }
+}
+
+/// Checks whether product is the recipe itself or (directly or through nested recipes) contains
+/// it, adding such product as an ingredient would create a cycle.
+fn recipe_depends_on(
+    db: &Connection,
+    product_id: usize,
+    recipe_id: usize,
+) -> Result<bool, ServiceError> {
+    let query = "
+        WITH RECURSIVE dependencies(product_id) AS (
+            SELECT ?1
+            UNION
+            SELECT recipe_ingredients.product_id
+            FROM dependencies
+            INNER JOIN recipes ON recipes.product_id = dependencies.product_id
+            INNER JOIN recipe_ingredients ON recipe_ingredients.recipe_id = recipes.id
+        )
+        SELECT EXISTS (
+            SELECT 1
+            FROM dependencies
+            INNER JOIN recipes ON recipes.product_id = dependencies.product_id
+            WHERE recipes.id = ?2
+        )";
+    let args = params![product_id, recipe_id];
+
+    db.query_row(query, args, |row| row.get(0))
+        .map_err(ServiceError::from)
+}
+
+/// Recalculates nutrition values of the product backing given recipe and of every recipe that
+/// uses it (directly or through other recipes) as an ingredient.
+pub(crate) fn recalculate_recipe(db: &Connection, recipe_id: usize) -> Result<(), ServiceError> {
+    let mut visited = HashSet::new();
+    let mut pending = vec![recipe_id];
+
+    while let Some(recipe_id) = pending.pop() {
+        if !visited.insert(recipe_id) {
+            continue;
+        }
+
+        let product_id = recalculate_single_recipe(db, recipe_id)?;
+        pending.extend(recipes_with_ingredient(db, product_id)?);
+    }
+
+    Ok(())
+}
+
+fn recalculate_single_recipe(db: &Connection, recipe_id: usize) -> Result<usize, ServiceError> {
+    let totals_query = "
+        SELECT
+            recipes.product_id,
+            recipes.weight,
+            COALESCE(SUM(products.calories * recipe_ingredients.weight / 100), 0),
+            COALESCE(SUM(products.fats * recipe_ingredients.weight / 100), 0),
+            COALESCE(SUM(products.proteins * recipe_ingredients.weight / 100), 0),
+            COALESCE(SUM(products.carbohydrates * recipe_ingredients.weight / 100), 0),
+            SUM(products.fiber * recipe_ingredients.weight / 100),
+            SUM(products.sugar * recipe_ingredients.weight / 100),
+            SUM(products.saturated_fats * recipe_ingredients.weight / 100),
+            SUM(products.salt * recipe_ingredients.weight / 100)
+        FROM recipes
+        LEFT JOIN recipe_ingredients ON recipes.id = recipe_ingredients.recipe_id
+        LEFT JOIN products ON recipe_ingredients.product_id = products.id
+        WHERE recipes.id = ?1
+        GROUP BY recipes.id";
+    let update_query = "
+        UPDATE products
+        SET calories=?1, fats=?2, proteins=?3, carbohydrates=?4,
+            fiber=?5, sugar=?6, saturated_fats=?7, salt=?8
+        WHERE id = ?9";
+    let delete_nutrients_query = "
+        DELETE FROM product_nutrients
+        WHERE product_id = ?1";
+    let insert_nutrients_query = "
+        INSERT INTO product_nutrients (product_id, nutrient_id, amount)
+        SELECT
+            ?1,
+            product_nutrients.nutrient_id,
+            SUM(product_nutrients.amount * recipe_ingredients.weight / 100) * 100 / ?3
+        FROM recipe_ingredients
+        INNER JOIN product_nutrients
+            ON recipe_ingredients.product_id = product_nutrients.product_id
+        WHERE recipe_ingredients.recipe_id = ?2
+        GROUP BY product_nutrients.nutrient_id";
+
+    let (product_id, weight, totals, extended_totals): (usize, f32, [f32; 4], [Option<f32>; 4]) =
+        db.query_row(totals_query, params![recipe_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                [row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?],
+                [row.get(6)?, row.get(7)?, row.get(8)?, row.get(9)?],
+            ))
+        })
+        .map_err(ServiceError::from)?;
+
+    let per_100g = 100.0 / weight;
+    let [calories, fats, proteins, carbohydrates] = totals.map(|v| v * per_100g);
+    let [fiber, sugar, saturated_fats, salt] = extended_totals.map(|v| v.map(|v| v * per_100g));
+    freeze_past_meal_products(db, product_id)?;
+    db.execute(
+        update_query,
+        params![
+            calories,
+            fats,
+            proteins,
+            carbohydrates,
+            fiber,
+            sugar,
+            saturated_fats,
+            salt,
+            product_id
+        ],
+    )
+    .map_err(ServiceError::from)?;
+
+    db.execute(delete_nutrients_query, params![product_id])
+        .map_err(ServiceError::from)?;
+    db.execute(
+        insert_nutrients_query,
+        params![product_id, recipe_id, weight],
+    )
+    .map_err(ServiceError::from)?;
+
+    Ok(product_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, CreateUpdateProduct, Services};
+
+    use super::*;
+
+    #[test]
+    fn recipe_nutrition_follows_ingredients() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services
+            .product
+            .create(CreateUpdateProduct {
+                calories: 350.0,
+                ..product("Rice")
+            })
+            .unwrap();
+        services.product.create(product("Beans")).unwrap();
+        let products = services.product.list().unwrap();
+
+        services
+            .recipe
+            .create(CreateUpdateRecipe {
+                name: "Stew".to_string(),
+                weight: 500.0,
+            })
+            .unwrap();
+        let recipe = services.recipe.list().unwrap().remove(0);
+
+        for (p, weight) in products.iter().zip([100.0, 200.0]) {
+            services
+                .recipe
+                .add_ingredient(AddRecipeIngredient {
+                    recipe_id: recipe.id,
+                    product_id: p.id,
+                    weight,
+                })
+                .unwrap();
+        }
+
+        let recipe = services.recipe.read(recipe.id).unwrap();
+        assert_eq!(recipe.calories, 110.0);
+        assert_eq!(recipe.ingredients.len(), 2);
+
+        services
+            .product
+            .update(
+                products[0].id,
+                CreateUpdateProduct {
+                    calories: 450.0,
+                    ..product("Rice")
+                },
+            )
+            .unwrap();
+        let recipe_product = services.product.read(recipe.product_id).unwrap();
+        assert_eq!(recipe_product.calories, 130.0);
+
+        let err = services.recipe.add_ingredient(AddRecipeIngredient {
+            recipe_id: recipe.id,
+            product_id: recipe.product_id,
+            weight: 10.0,
+        });
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn undo_ingredient_changes() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services.product.create(product("Rice")).unwrap();
+        let rice = services.product.list().unwrap().remove(0);
+        services
+            .recipe
+            .create(CreateUpdateRecipe {
+                name: "Risotto".to_string(),
+                weight: 100.0,
+            })
+            .unwrap();
+        let recipe = services.recipe.list().unwrap().remove(0);
+        services
+            .recipe
+            .add_ingredient(AddRecipeIngredient {
+                recipe_id: recipe.id,
+                product_id: rice.id,
+                weight: 100.0,
+            })
+            .unwrap();
+        let ingredient_id = services.recipe.read(recipe.id).unwrap().ingredients[0].id;
+
+        services
+            .recipe
+            .update_ingredient_weight(UpdateRecipeIngredientWeight {
+                recipe_ingredient_id: ingredient_id,
+                weight: 200.0,
+            })
+            .unwrap();
+        assert_eq!(services.recipe.read(recipe.id).unwrap().calories, 200.0);
+        services.history.undo().unwrap();
+        assert_eq!(services.recipe.read(recipe.id).unwrap().calories, 100.0);
+
+        services.recipe.delete_ingredient(ingredient_id).unwrap();
+        assert_eq!(services.recipe.read(recipe.id).unwrap().calories, 0.0);
+        services.history.undo().unwrap();
+        let restored = services.recipe.read(recipe.id).unwrap();
+        assert_eq!(restored.calories, 100.0);
+        assert_eq!(restored.ingredients.len(), 1);
+
+        services.history.undo().unwrap();
+        let emptied = services.recipe.read(recipe.id).unwrap();
+        assert_eq!(emptied.calories, 0.0);
+        assert!(emptied.ingredients.is_empty());
+    }
+}
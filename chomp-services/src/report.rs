@@ -0,0 +1,321 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use chrono::{Datelike, Days, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{
+    nutrition_target::{target_in_effect, WeekdayTarget},
+    NutritionTarget, ServiceError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReportPeriod {
+    Week,
+    Month,
+}
+
+impl ReportPeriod {
+    pub const ALL: [ReportPeriod; 2] = [ReportPeriod::Week, ReportPeriod::Month];
+
+    /// First day of the period containing `day`, weeks start on monday.
+    pub fn start(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Week => day
+                .checked_sub_days(Days::new(day.weekday().num_days_from_monday() as u64))
+                .unwrap(),
+            ReportPeriod::Month => day.with_day(1).unwrap(),
+        }
+    }
+}
+
+impl fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportPeriod::Week => write!(f, "Weekly"),
+            ReportPeriod::Month => write!(f, "Monthly"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Macros {
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+}
+
+/// Percentage of days (0-100) on which nutrient was within tolerance of the target. Days without
+/// target are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Adherence {
+    pub days_with_target: usize,
+    pub calories: f32,
+    pub fats: f32,
+    pub proteins: f32,
+    pub carbohydrates: f32,
+}
+
+/// Totals of a day with at least one product in meals and the target in effect on that day,
+/// which is the last target set on or before it with its weekday schedule applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct DayTotals {
+    pub day: NaiveDate,
+    pub totals: Macros,
+    pub target: Option<NutritionTarget>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodSummary {
+    pub start: NaiveDate,
+    pub days_logged: usize,
+    pub averages: Macros,
+    pub adherence: Adherence,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub tolerance: f32,
+    pub days: Vec<DayTotals>,
+    pub periods: Vec<PeriodSummary>,
+    pub averages: Macros,
+    pub adherence: Adherence,
+}
+
+pub struct ReportService {
+    db: Rc<RefCell<Connection>>,
+}
+
+impl ReportService {
+    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
+        ReportService { db }
+    }
+
+    /// Builds report for days between `from` and `to` (inclusive). Tolerance is a fraction of
+    /// the target, 0.1 means that values within 10% of the target are on target.
+    pub fn report(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        period: ReportPeriod,
+        tolerance: f32,
+    ) -> Result<Report, ServiceError> {
+        let days = self.day_totals(from, to)?;
+
+        let mut periods: Vec<PeriodSummary> = Vec::new();
+        for chunk in days.chunk_by(|a, b| period.start(a.day) == period.start(b.day)) {
+            periods.push(PeriodSummary {
+                start: period.start(chunk[0].day),
+                days_logged: chunk.len(),
+                averages: averages(chunk),
+                adherence: adherence(chunk, tolerance),
+            });
+        }
+
+        Ok(Report {
+            from,
+            to,
+            tolerance,
+            averages: averages(&days),
+            adherence: adherence(&days, tolerance),
+            days,
+            periods,
+        })
+    }
+
+    /// Totals of days between `from` and `to` (inclusive) that have anything logged, ordered by
+    /// day.
+    pub fn day_totals(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DayTotals>, ServiceError> {
+        let query = "
+            SELECT
+                meals.day,
+                SUM(meal_product_nutrition.calories * meal_products.weight / 100),
+                SUM(meal_product_nutrition.fats * meal_products.weight / 100),
+                SUM(meal_product_nutrition.proteins * meal_products.weight / 100),
+                SUM(meal_product_nutrition.carbohydrates * meal_products.weight / 100)
+            FROM meals
+            INNER JOIN meal_products ON meals.id = meal_products.meal_id
+            INNER JOIN meal_product_nutrition ON meal_products.id = meal_product_nutrition.id
+            WHERE meals.day BETWEEN ?1 AND ?2
+            GROUP BY meals.day
+            ORDER BY meals.day ASC";
+        let targets_query = "
+            SELECT day, calories, fats, proteins, carbohydrates
+            FROM nutrition_targets
+            WHERE day <= ?1
+            ORDER BY day ASC";
+        let weekdays_query = "
+            SELECT target_day, weekday, calories, fats, proteins, carbohydrates
+            FROM nutrition_target_weekdays
+            WHERE target_day <= ?1";
+        let from = format!("{}", from.format("%Y-%m-%d"));
+        let to = format!("{}", to.format("%Y-%m-%d"));
+
+        let db = self.db.borrow();
+
+        let mut stmt = db.prepare(targets_query)?;
+        let targets = stmt
+            .query_map(params![to], |row| {
+                Ok(NutritionTarget {
+                    day: row.get(0)?,
+                    calories: row.get(1)?,
+                    fats: row.get(2)?,
+                    proteins: row.get(3)?,
+                    carbohydrates: row.get(4)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<NutritionTarget>, _>>()?;
+
+        let mut stmt = db.prepare(weekdays_query)?;
+        let weekdays = stmt
+            .query_map(params![to], |row| {
+                Ok(WeekdayTarget {
+                    day: row.get(0)?,
+                    weekday: row.get(1)?,
+                    calories: row.get(2)?,
+                    fats: row.get(3)?,
+                    proteins: row.get(4)?,
+                    carbohydrates: row.get(5)?,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<WeekdayTarget>, _>>()?;
+
+        let mut stmt = db.prepare(query)?;
+        let days = stmt
+            .query_map(params![from, to], |row| {
+                let day: NaiveDate = row.get(0)?;
+                let target = target_in_effect(day, &targets, &weekdays);
+
+                Ok(DayTotals {
+                    day,
+                    totals: Macros {
+                        calories: row.get(1)?,
+                        fats: row.get(2)?,
+                        proteins: row.get(3)?,
+                        carbohydrates: row.get(4)?,
+                    },
+                    target,
+                })
+            })
+            .map_err(ServiceError::from)?
+            .collect::<Result<Vec<DayTotals>, _>>()?;
+
+        Ok(days)
+    }
+}
+
+fn averages(days: &[DayTotals]) -> Macros {
+    if days.is_empty() {
+        return Macros::default();
+    }
+
+    let count = days.len() as f32;
+    Macros {
+        calories: days.iter().map(|d| d.totals.calories).sum::<f32>() / count,
+        fats: days.iter().map(|d| d.totals.fats).sum::<f32>() / count,
+        proteins: days.iter().map(|d| d.totals.proteins).sum::<f32>() / count,
+        carbohydrates: days.iter().map(|d| d.totals.carbohydrates).sum::<f32>() / count,
+    }
+}
+
+fn adherence(days: &[DayTotals], tolerance: f32) -> Adherence {
+    let with_target: Vec<(&Macros, &NutritionTarget)> = days
+        .iter()
+        .filter_map(|d| d.target.as_ref().map(|t| (&d.totals, t)))
+        .collect();
+
+    if with_target.is_empty() {
+        return Adherence::default();
+    }
+
+    let percentage = |value: fn(&Macros, &NutritionTarget) -> (f32, f32)| {
+        let on_target = with_target
+            .iter()
+            .filter(|(totals, target)| {
+                let (actual, expected) = value(totals, target);
+                (actual - expected).abs() <= expected * tolerance
+            })
+            .count();
+
+        on_target as f32 / with_target.len() as f32 * 100.0
+    };
+
+    Adherence {
+        days_with_target: with_target.len(),
+        calories: percentage(|m, t| (m.calories, t.calories)),
+        fats: percentage(|m, t| (m.fats, t.fats)),
+        proteins: percentage(|m, t| (m.proteins, t.proteins)),
+        carbohydrates: percentage(|m, t| (m.carbohydrates, t.carbohydrates)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::{test_utils::product, AddMealProduct, Services};
+
+    use super::*;
+
+    #[test]
+    fn report_uses_target_in_effect_on_each_day() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        services.product.create(product("Rice")).unwrap();
+        let product_id = services.product.list().unwrap()[0].id;
+
+        let first_day = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
+        services
+            .nutrition_target
+            .create(NutritionTarget::new(first_day, 2000.0, 20.0, 200.0, 400.0))
+            .unwrap();
+        services
+            .nutrition_target
+            .create(NutritionTarget::new(
+                NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(),
+                1000.0,
+                10.0,
+                100.0,
+                200.0,
+            ))
+            .unwrap();
+
+        // Sunday and monday of the next week, both with 1000 kcal.
+        for day in [first_day, first_day.succ_opt().unwrap()] {
+            let meal_id = services.meal.list_or_create_default(day).unwrap()[0].id;
+            services
+                .meal
+                .add_product(AddMealProduct {
+                    meal_id,
+                    product_id,
+                    weight: 1000.0,
+                })
+                .unwrap();
+        }
+
+        let report = services
+            .report
+            .report(
+                first_day,
+                NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+                ReportPeriod::Week,
+                0.1,
+            )
+            .unwrap();
+
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.periods.len(), 2);
+        assert_eq!(report.averages.calories, 1000.0);
+        assert_eq!(report.adherence.days_with_target, 2);
+        assert_eq!(report.adherence.calories, 50.0);
+        assert_eq!(report.periods[1].adherence.calories, 100.0);
+    }
+}
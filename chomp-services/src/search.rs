@@ -0,0 +1,160 @@
+//! Typo tolerant and accent insensitive text search. Candidates are found with the trigram FTS5
+//! index and ranked here by how closely words of the query match words of each field.
+
+/// Field that can be matched by a query, `weight` scales the score of matches in it so that for
+/// example a match in product name ranks above the same match in company.
+pub(crate) struct SearchField<'a> {
+    pub text: &'a str,
+    pub weight: f32,
+}
+
+/// Lowercase text with diacritics removed, so that "Jalapeño" and "jalapeno" are equal.
+pub(crate) fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+            'ç' | 'ć' | 'č' => folded.push('c'),
+            'ď' | 'đ' => folded.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+            'ğ' => folded.push('g'),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => folded.push('i'),
+            'ł' | 'ľ' => folded.push('l'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => folded.push('o'),
+            'ŕ' | 'ř' => folded.push('r'),
+            'ś' | 'š' | 'ș' | 'ş' => folded.push('s'),
+            'ť' | 'ț' => folded.push('t'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            c => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+/// Folded words of the text, anything that is not a letter or digit separates words.
+pub(crate) fn words(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// FTS5 query matching rows that share at least one trigram with any of the words, so that
+/// misspelled words still find candidates. `None` when no word is long enough to have trigrams.
+pub(crate) fn trigram_query(words: &[String]) -> Option<String> {
+    let mut trigrams: Vec<String> = Vec::new();
+    for word in words {
+        let chars: Vec<char> = word.chars().collect();
+        for trigram in chars.windows(3) {
+            let trigram: String = trigram.iter().collect();
+            if !trigrams.contains(&trigram) {
+                trigrams.push(trigram);
+            }
+        }
+    }
+
+    if trigrams.is_empty() {
+        None
+    } else {
+        Some(
+            trigrams
+                .iter()
+                .map(|t| format!("\"{t}\""))
+                .collect::<Vec<String>>()
+                .join(" OR "),
+        )
+    }
+}
+
+/// Score of the fields for the query words, higher is better. Every query word has to match a
+/// word of some field, otherwise there is no match.
+pub(crate) fn score(query: &[String], fields: &[SearchField]) -> Option<f32> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let fields: Vec<(Vec<String>, f32)> =
+        fields.iter().map(|f| (words(f.text), f.weight)).collect();
+
+    let mut total = 0.0;
+    for query_word in query {
+        let best = fields
+            .iter()
+            .flat_map(|(words, weight)| {
+                words
+                    .iter()
+                    .filter_map(move |w| word_score(query_word, w).map(|s| s * weight))
+            })
+            .fold(None, |best: Option<f32>, s| {
+                Some(best.map_or(s, |b| b.max(s)))
+            });
+        total += best?;
+    }
+
+    Some(total / query.len() as f32)
+}
+
+fn word_score(query: &str, word: &str) -> Option<f32> {
+    if word == query {
+        return Some(1.0);
+    }
+    if word.starts_with(query) {
+        return Some(0.9);
+    }
+
+    let query_len = query.chars().count();
+    if query_len < 3 {
+        return None;
+    }
+    if word.contains(query) {
+        return Some(0.75);
+    }
+    if query_len < 4 {
+        return None;
+    }
+
+    // Longer words can have more typos, prefix of the word covers query typed only partially.
+    let allowed = if query_len >= 8 { 2 } else { 1 };
+    let prefix: String = word.chars().take(query_len).collect();
+    let distance = edit_distance(query, word).min(edit_distance(query, &prefix) + 1);
+
+    (distance <= allowed).then_some(0.7 - 0.1 * distance as f32)
+}
+
+/// Optimal string alignment distance, which is Levenshtein distance that also counts swapped
+/// neighbouring characters as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in rows[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
@@ -0,0 +1,182 @@
+use std::{cell::RefCell, rc::Rc, str::FromStr};
+
+use chrono::Weekday;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::{
+    history::{record, History, Scope},
+    Macros, ServiceError, Units,
+};
+
+const THEME_KEY: &str = "theme";
+const BODY_WEIGHT_UNIT_KEY: &str = "units.body_weight";
+const FOOD_WEIGHT_UNIT_KEY: &str = "units.food_weight";
+const ENERGY_UNIT_KEY: &str = "units.energy";
+const WEEK_START_KEY: &str = "week_start";
+const TARGET_CALORIES_KEY: &str = "default_target.calories";
+const TARGET_FATS_KEY: &str = "default_target.fats";
+const TARGET_PROTEINS_KEY: &str = "default_target.proteins";
+const TARGET_CARBOHYDRATES_KEY: &str = "default_target.carbohydrates";
+const DASHBOARD_RANGE_KEY: &str = "dashboard.range";
+
+/// User preferences. Theme and dashboard range are stored by name and interpreted by the app,
+/// unknown names fall back to its defaults.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Settings {
+    pub theme: Option<String>,
+    pub units: Units,
+    pub week_start: Weekday,
+    /// Target created for the first day when there is no nutrition target yet.
+    pub default_target: Macros,
+    pub dashboard_range: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: None,
+            units: Units::default(),
+            week_start: Weekday::Mon,
+            default_target: Macros {
+                calories: 2500.0,
+                fats: 80.0,
+                proteins: 200.0,
+                carbohydrates: 245.0,
+            },
+            dashboard_range: None,
+        }
+    }
+}
+
+/// User preferences stored as text under a key, missing or unreadable values fall back to
+/// defaults.
+pub struct SettingsService {
+    db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
+}
+
+impl SettingsService {
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        SettingsService { db, history }
+    }
+
+    pub fn read(&self) -> Result<Settings, ServiceError> {
+        let defaults = Settings::default();
+
+        Ok(Settings {
+            theme: self.value(THEME_KEY)?,
+            units: Units {
+                body_weight: self.value_or(BODY_WEIGHT_UNIT_KEY, defaults.units.body_weight)?,
+                food_weight: self.value_or(FOOD_WEIGHT_UNIT_KEY, defaults.units.food_weight)?,
+                energy: self.value_or(ENERGY_UNIT_KEY, defaults.units.energy)?,
+            },
+            week_start: self.value_or(WEEK_START_KEY, defaults.week_start)?,
+            default_target: Macros {
+                calories: self.value_or(TARGET_CALORIES_KEY, defaults.default_target.calories)?,
+                fats: self.value_or(TARGET_FATS_KEY, defaults.default_target.fats)?,
+                proteins: self.value_or(TARGET_PROTEINS_KEY, defaults.default_target.proteins)?,
+                carbohydrates: self.value_or(
+                    TARGET_CARBOHYDRATES_KEY,
+                    defaults.default_target.carbohydrates,
+                )?,
+            },
+            dashboard_range: self.value(DASHBOARD_RANGE_KEY)?,
+        })
+    }
+
+    pub fn save(&self, settings: &Settings) -> Result<(), ServiceError> {
+        let target = &settings.default_target;
+        let values = [
+            (THEME_KEY, settings.theme.clone()),
+            (
+                BODY_WEIGHT_UNIT_KEY,
+                Some(settings.units.body_weight.to_string()),
+            ),
+            (
+                FOOD_WEIGHT_UNIT_KEY,
+                Some(settings.units.food_weight.to_string()),
+            ),
+            (ENERGY_UNIT_KEY, Some(settings.units.energy.to_string())),
+            (WEEK_START_KEY, Some(settings.week_start.to_string())),
+            (TARGET_CALORIES_KEY, Some(target.calories.to_string())),
+            (TARGET_FATS_KEY, Some(target.fats.to_string())),
+            (TARGET_PROTEINS_KEY, Some(target.proteins.to_string())),
+            (
+                TARGET_CARBOHYDRATES_KEY,
+                Some(target.carbohydrates.to_string()),
+            ),
+            (DASHBOARD_RANGE_KEY, settings.dashboard_range.clone()),
+        ];
+
+        let scopes = [Scope::all("settings")];
+
+        record(&self.db, &self.history, "Change settings", &scopes, |tx| {
+            for (key, value) in values {
+                match value {
+                    Some(value) => tx.execute(
+                        "INSERT INTO settings (key, value)
+                        VALUES (?1, ?2)
+                        ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                        params![key, value],
+                    ),
+                    None => tx.execute("DELETE FROM settings WHERE key = ?1", params![key]),
+                }
+                .map_err(ServiceError::from)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn value(&self, key: &str) -> Result<Option<String>, ServiceError> {
+        let query = "
+            SELECT value
+            FROM settings
+            WHERE key = ?1";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        stmt.query_row(params![key], |row| row.get(0))
+            .optional()
+            .map_err(ServiceError::from)
+    }
+
+    fn value_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, ServiceError> {
+        Ok(self
+            .value(key)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+    use chrono::Weekday;
+
+    use crate::{EnergyUnit, Services};
+
+    #[test]
+    fn saved_settings_are_read_back() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+
+        let mut settings = services.settings.read().unwrap();
+        assert_eq!(settings.week_start, Weekday::Mon);
+
+        settings.theme = Some("Dracula".to_string());
+        settings.units.energy = EnergyUnit::Kilojoule;
+        settings.week_start = Weekday::Sun;
+        settings.default_target.calories = 2100.0;
+        services.settings.save(&settings).unwrap();
+        assert_eq!(services.settings.read().unwrap(), settings);
+
+        let settings_with_theme = settings.clone();
+        settings.theme = None;
+        services.settings.save(&settings).unwrap();
+        assert_eq!(services.settings.read().unwrap().theme, None);
+
+        services.history.undo().unwrap();
+        assert_eq!(services.settings.read().unwrap(), settings_with_theme);
+    }
+}
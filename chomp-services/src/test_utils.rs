@@ -0,0 +1,20 @@
+use crate::CreateUpdateProduct;
+
+/// Product with 100 kcal and a few grams of each macro, fields that matter to a test are
+/// overridden with struct update syntax.
+pub(crate) fn product(name: &str) -> CreateUpdateProduct {
+    CreateUpdateProduct {
+        name: name.to_string(),
+        company: None,
+        calories: 100.0,
+        fats: 1.0,
+        proteins: 2.0,
+        carbohydrates: 3.0,
+        fiber: None,
+        sugar: None,
+        saturated_fats: None,
+        salt: None,
+        barcode: None,
+        nutrients: Vec::new(),
+    }
+}
@@ -0,0 +1,191 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+const POUNDS_PER_KILOGRAM: f32 = 2.204_622_6;
+const GRAMS_PER_OUNCE: f32 = 28.349_523;
+const KILOJOULES_PER_KILOCALORIE: f32 = 4.184;
+
+/// Unit of body weight, stored values are always in kilograms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyWeightUnit {
+    #[default]
+    Kilogram,
+    Pound,
+}
+
+/// Unit of food amounts, stored values are always in grams. Nutrition values stay per 100g.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FoodWeightUnit {
+    #[default]
+    Gram,
+    Ounce,
+}
+
+/// Unit of energy, stored values are always in kilocalories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnergyUnit {
+    #[default]
+    Kilocalorie,
+    Kilojoule,
+}
+
+impl BodyWeightUnit {
+    pub const ALL: [BodyWeightUnit; 2] = [BodyWeightUnit::Kilogram, BodyWeightUnit::Pound];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BodyWeightUnit::Kilogram => "kg",
+            BodyWeightUnit::Pound => "lb",
+        }
+    }
+
+    /// Converts kilograms to this unit.
+    pub fn from_canonical(&self, kilograms: f32) -> f32 {
+        match self {
+            BodyWeightUnit::Kilogram => kilograms,
+            BodyWeightUnit::Pound => kilograms * POUNDS_PER_KILOGRAM,
+        }
+    }
+
+    /// Converts value in this unit to kilograms.
+    pub fn to_canonical(&self, value: f32) -> f32 {
+        match self {
+            BodyWeightUnit::Kilogram => value,
+            BodyWeightUnit::Pound => value / POUNDS_PER_KILOGRAM,
+        }
+    }
+}
+
+impl FoodWeightUnit {
+    pub const ALL: [FoodWeightUnit; 2] = [FoodWeightUnit::Gram, FoodWeightUnit::Ounce];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FoodWeightUnit::Gram => "g",
+            FoodWeightUnit::Ounce => "oz",
+        }
+    }
+
+    /// Converts grams to this unit.
+    pub fn from_canonical(&self, grams: f32) -> f32 {
+        match self {
+            FoodWeightUnit::Gram => grams,
+            FoodWeightUnit::Ounce => grams / GRAMS_PER_OUNCE,
+        }
+    }
+
+    /// Converts value in this unit to grams.
+    pub fn to_canonical(&self, value: f32) -> f32 {
+        match self {
+            FoodWeightUnit::Gram => value,
+            FoodWeightUnit::Ounce => value * GRAMS_PER_OUNCE,
+        }
+    }
+}
+
+impl EnergyUnit {
+    pub const ALL: [EnergyUnit; 2] = [EnergyUnit::Kilocalorie, EnergyUnit::Kilojoule];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            EnergyUnit::Kilocalorie => "kcal",
+            EnergyUnit::Kilojoule => "kJ",
+        }
+    }
+
+    /// Converts kilocalories to this unit.
+    pub fn from_canonical(&self, kilocalories: f32) -> f32 {
+        match self {
+            EnergyUnit::Kilocalorie => kilocalories,
+            EnergyUnit::Kilojoule => kilocalories * KILOJOULES_PER_KILOCALORIE,
+        }
+    }
+
+    /// Converts value in this unit to kilocalories.
+    pub fn to_canonical(&self, value: f32) -> f32 {
+        match self {
+            EnergyUnit::Kilocalorie => value,
+            EnergyUnit::Kilojoule => value / KILOJOULES_PER_KILOCALORIE,
+        }
+    }
+}
+
+impl fmt::Display for BodyWeightUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl fmt::Display for FoodWeightUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl fmt::Display for EnergyUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl FromStr for BodyWeightUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BodyWeightUnit::ALL
+            .into_iter()
+            .find(|u| u.symbol().eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown body weight unit '{s}', expected kg or lb"))
+    }
+}
+
+impl FromStr for FoodWeightUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FoodWeightUnit::ALL
+            .into_iter()
+            .find(|u| u.symbol().eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown food weight unit '{s}', expected g or oz"))
+    }
+}
+
+impl FromStr for EnergyUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EnergyUnit::ALL
+            .into_iter()
+            .find(|u| u.symbol().eq_ignore_ascii_case(s))
+            .ok_or(format!("unknown energy unit '{s}', expected kcal or kJ"))
+    }
+}
+
+/// Units values are shown and entered in. Everything is stored in kilograms, grams and
+/// kilocalories regardless of these.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Units {
+    pub body_weight: BodyWeightUnit,
+    pub food_weight: FoodWeightUnit,
+    pub energy: EnergyUnit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_round_trip() {
+        let pounds = BodyWeightUnit::Pound.from_canonical(80.0);
+        assert!((pounds - 176.37).abs() < 0.01);
+        assert!((BodyWeightUnit::Pound.to_canonical(pounds) - 80.0).abs() < 0.001);
+
+        let ounces = FoodWeightUnit::Ounce.from_canonical(100.0);
+        assert!((ounces - 3.527).abs() < 0.001);
+        assert!((FoodWeightUnit::Ounce.to_canonical(ounces) - 100.0).abs() < 0.001);
+
+        assert_eq!(EnergyUnit::Kilojoule.from_canonical(100.0), 418.4);
+        assert_eq!("KJ".parse::<EnergyUnit>(), Ok(EnergyUnit::Kilojoule));
+    }
+}
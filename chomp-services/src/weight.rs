@@ -2,10 +2,14 @@ use std::{cell::RefCell, rc::Rc};
 
 use chrono::NaiveDate;
 use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
 
-use super::ServiceError;
+use super::{
+    history::{day_value, id_value, record, History, Scope},
+    ServiceError, WeightAnalysis,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Weight {
     pub day: NaiveDate,
     pub weight: f32,
@@ -19,11 +23,12 @@ impl Weight {
 
 pub struct WeightService {
     db: Rc<RefCell<Connection>>,
+    history: Rc<RefCell<History>>,
 }
 
 impl WeightService {
-    pub fn new(db: Rc<RefCell<Connection>>) -> Self {
-        WeightService { db }
+    pub(crate) fn new(db: Rc<RefCell<Connection>>, history: Rc<RefCell<History>>) -> Self {
+        WeightService { db, history }
     }
 
     pub fn create(&self, weight: Weight) -> Result<(), ServiceError> {
@@ -32,11 +37,12 @@ impl WeightService {
     	    VALUES (?1, ?2)";
         let args = params![format!("{}", weight.day.format("%Y-%m-%d")), weight.weight,];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [Scope::new("weights", "day = ?1", day_value(weight.day))];
 
-        Ok(())
+        record(&self.db, &self.history, "Add weight", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn update(&self, weight: Weight) -> Result<(), ServiceError> {
@@ -46,11 +52,12 @@ impl WeightService {
             WHERE day = ?2";
         let args = params![weight.weight, format!("{}", weight.day.format("%Y-%m-%d")),];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [Scope::new("weights", "day = ?1", day_value(weight.day))];
 
-        Ok(())
+        record(&self.db, &self.history, "Change weight", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn read(&self, day: NaiveDate) -> Result<Weight, ServiceError> {
@@ -78,11 +85,12 @@ impl WeightService {
     	    WHERE day = ?1";
         let args = params![format!("{}", day.format("%Y-%m-%d"))];
 
-        let db = self.db.borrow();
-        let mut stmt = db.prepare(query)?;
-        stmt.execute(args).map_err(ServiceError::from)?;
+        let scopes = [Scope::new("weights", "day = ?1", day_value(day))];
 
-        Ok(())
+        record(&self.db, &self.history, "Delete weight", &scopes, |tx| {
+            tx.execute(query, args).map_err(ServiceError::from)?;
+            Ok(())
+        })
     }
 
     pub fn list(&self) -> Result<Vec<Weight>, ServiceError> {
@@ -137,4 +145,84 @@ impl WeightService {
 
         Ok(weights)
     }
+
+    /// Sets weight the user wants to reach, replacing the previous goal.
+    pub fn set_goal(&self, weight: f32) -> Result<(), ServiceError> {
+        let query = "
+            INSERT INTO weight_goal (id, weight)
+            VALUES (1, ?1)
+            ON CONFLICT (id) DO UPDATE SET weight = excluded.weight";
+        let scopes = [Scope::new("weight_goal", "id = ?1", id_value(1))];
+
+        record(&self.db, &self.history, "Set weight goal", &scopes, |tx| {
+            tx.execute(query, params![weight])
+                .map_err(ServiceError::from)?;
+            Ok(())
+        })
+    }
+
+    pub fn read_goal(&self) -> Result<Option<f32>, ServiceError> {
+        let query = "
+            SELECT weight
+            FROM weight_goal
+            WHERE id = 1";
+
+        let db = self.db.borrow();
+        let mut stmt = db.prepare(query)?;
+
+        match stmt.query_row([], |row| row.get(0)) {
+            Ok(weight) => Ok(Some(weight)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(ServiceError::from(err)),
+        }
+    }
+
+    pub fn delete_goal(&self) -> Result<(), ServiceError> {
+        let scopes = [Scope::new("weight_goal", "id = ?1", id_value(1))];
+
+        record(
+            &self.db,
+            &self.history,
+            "Delete weight goal",
+            &scopes,
+            |tx| {
+                tx.execute("DELETE FROM weight_goal", [])
+                    .map_err(ServiceError::from)?;
+                Ok(())
+            },
+        )
+    }
+
+    /// Trend of all weights with rate of change and projection to the goal.
+    pub fn analysis(&self) -> Result<WeightAnalysis, ServiceError> {
+        let weights = self.list()?;
+        let goal = self.read_goal()?;
+
+        Ok(WeightAnalysis::new(&weights, goal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chomp_db::prepare_in_memory_conn;
+
+    use crate::Services;
+
+    #[test]
+    fn goal_is_replaced_and_cleared() {
+        let services = Services::new(prepare_in_memory_conn().unwrap());
+        assert_eq!(services.weight.read_goal().unwrap(), None);
+
+        services.weight.set_goal(80.0).unwrap();
+        services.weight.set_goal(75.5).unwrap();
+        assert_eq!(services.weight.read_goal().unwrap(), Some(75.5));
+
+        services.weight.delete_goal().unwrap();
+        assert_eq!(services.weight.read_goal().unwrap(), None);
+
+        services.history.undo().unwrap();
+        assert_eq!(services.weight.read_goal().unwrap(), Some(75.5));
+        services.history.undo().unwrap();
+        assert_eq!(services.weight.read_goal().unwrap(), Some(80.0));
+    }
 }
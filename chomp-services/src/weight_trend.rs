@@ -0,0 +1,153 @@
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+use super::Weight;
+
+/// How much a single weigh-in moves the trend, 10% is what "The Hacker's Diet" uses.
+pub const TREND_SMOOTHING: f32 = 0.1;
+/// Amount of days of the trend used to calculate rate of change.
+pub const TREND_RATE_DAYS: i64 = 28;
+/// Smallest weekly change that is used for goal projection, anything below is maintenance.
+const MIN_WEEKLY_CHANGE: f32 = 0.01;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeightTrend {
+    pub day: NaiveDate,
+    pub weight: f32,
+    pub trend: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeightAnalysis {
+    pub trend: Vec<WeightTrend>,
+    /// Change of the trend in kg per week over last `TREND_RATE_DAYS`.
+    pub weekly_change: Option<f32>,
+    pub goal: Option<f32>,
+    /// Day on which the trend reaches the goal if it keeps its current rate of change.
+    pub goal_day: Option<NaiveDate>,
+}
+
+impl WeightAnalysis {
+    pub fn new(weights: &[Weight], goal: Option<f32>) -> Self {
+        let trend = smooth(weights);
+        let weekly_change = weekly_change(&trend);
+        let goal_day = match (trend.last(), weekly_change, goal) {
+            (Some(last), Some(change), Some(goal)) => project(last, change, goal),
+            _ => None,
+        };
+
+        WeightAnalysis {
+            trend,
+            weekly_change,
+            goal,
+            goal_day,
+        }
+    }
+
+    pub fn current(&self) -> Option<&WeightTrend> {
+        self.trend.last()
+    }
+}
+
+/// Exponentially smoothed moving average of weights. Days without weigh-in are treated as if
+/// they had the weight equal to the trend, so longer gaps move the trend more.
+pub fn smooth(weights: &[Weight]) -> Vec<WeightTrend> {
+    let mut sorted: Vec<&Weight> = weights.iter().collect();
+    sorted.sort_by_key(|w| w.day);
+
+    let mut trend: Vec<WeightTrend> = Vec::with_capacity(sorted.len());
+    for weight in sorted {
+        let value = match trend.last() {
+            Some(previous) => {
+                let days = (weight.day - previous.day).num_days().max(1);
+                let factor = 1.0 - (1.0 - TREND_SMOOTHING).powi(days as i32);
+                previous.trend + factor * (weight.weight - previous.trend)
+            }
+            None => weight.weight,
+        };
+
+        trend.push(WeightTrend {
+            day: weight.day,
+            weight: weight.weight,
+            trend: value,
+        });
+    }
+
+    trend
+}
+
+/// Least squares slope of the trend over last `TREND_RATE_DAYS` in kg per week.
+pub fn weekly_change(trend: &[WeightTrend]) -> Option<f32> {
+    let last_day = trend.last()?.day;
+    let first_day = last_day.checked_sub_days(Days::new(TREND_RATE_DAYS as u64))?;
+    let recent: Vec<(f32, f32)> = trend
+        .iter()
+        .filter(|t| t.day >= first_day)
+        .map(|t| ((t.day - first_day).num_days() as f32, t.trend))
+        .collect();
+
+    if recent.len() < 2 {
+        return None;
+    }
+
+    let count = recent.len() as f32;
+    let mean_x = recent.iter().map(|(x, _)| x).sum::<f32>() / count;
+    let mean_y = recent.iter().map(|(_, y)| y).sum::<f32>() / count;
+    let covariance: f32 = recent
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f32 = recent.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    if variance < f32::EPSILON {
+        return None;
+    }
+
+    Some(covariance / variance * 7.0)
+}
+
+fn project(current: &WeightTrend, weekly_change: f32, goal: f32) -> Option<NaiveDate> {
+    let remaining = goal - current.trend;
+    if remaining.abs() < f32::EPSILON {
+        return Some(current.day);
+    }
+
+    // Trend going away from the goal or not moving at all never reaches it.
+    if weekly_change.abs() < MIN_WEEKLY_CHANGE || remaining.signum() != weekly_change.signum() {
+        return None;
+    }
+
+    let days = (remaining / weekly_change * 7.0).ceil();
+    current.day.checked_add_days(Days::new(days as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trend_is_projected_to_goal() {
+        let first_day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        // Losing 0.1 kg a day with water weight noise on top.
+        let weights: Vec<Weight> = (0..60)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 0.8 } else { -0.8 };
+                Weight::new(first_day + Days::new(i), 90.0 - i as f32 * 0.1 + noise)
+            })
+            .collect();
+
+        let analysis = WeightAnalysis::new(&weights, Some(80.0));
+        let current = analysis.current().unwrap();
+        assert!((current.trend - 85.0).abs() < 1.0);
+
+        let weekly_change = analysis.weekly_change.unwrap();
+        assert!((weekly_change + 0.7).abs() < 0.05);
+
+        let goal_day = analysis.goal_day.unwrap();
+        assert!(goal_day > current.day);
+        assert!(goal_day < current.day + Days::new(100));
+
+        let analysis = WeightAnalysis::new(&weights, Some(95.0));
+        assert_eq!(analysis.goal_day, None);
+    }
+}